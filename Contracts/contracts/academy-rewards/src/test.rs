@@ -1,4 +1,5 @@
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod test {
     use crate::{AcademyRewardsContract, AcademyRewardsContractClient, BadgeMetadata, ContractError, DataKey};
     use soroban_sdk::{testutils::Address as _, testutils::Ledger as _, Address, Env, String};
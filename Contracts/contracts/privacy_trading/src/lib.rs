@@ -206,7 +206,7 @@ impl PrivateTradingContract {
 
         // Emit initialization event
         env.events().publish(
-            (Symbol::new(&env, "initialized"), admin.clone()),
+            (Symbol::new(env, "initialized"), admin.clone()),
             (base_token.clone(), quote_token.clone()),
         );
 
@@ -241,7 +241,7 @@ impl PrivateTradingContract {
         }
 
         // Check nullifier hasn't been used
-        if Self::is_nullifier_used(&env, nullifier_hash.clone()) {
+        if Self::is_nullifier_used(env, nullifier_hash.clone()) {
             return Err(PrivateTradeError::AlreadySpent);
         }
 
@@ -263,7 +263,7 @@ impl PrivateTradingContract {
             status: OrderStatus::Open,
             created_at: env.ledger().timestamp(),
             expires_at,
-            filled_commitment: BytesN::from_array(&env, &[0u8; 32]),
+            filled_commitment: BytesN::from_array(env, &[0u8; 32]),
         };
 
         // Store order
@@ -280,7 +280,7 @@ impl PrivateTradingContract {
             .storage()
             .persistent()
             .get(&DataKey::UserOrders(trader.clone()))
-            .unwrap_or(Vec::new(&env));
+            .unwrap_or(Vec::new(env));
         user_orders.push_back(order_id);
         env.storage()
             .persistent()
@@ -293,7 +293,7 @@ impl PrivateTradingContract {
 
         // Emit order created event
         env.events().publish(
-            (Symbol::new(&env, "order_created"), trader),
+            (Symbol::new(env, "order_created"), trader),
             (order_id, side as u32, price),
         );
 
@@ -331,7 +331,7 @@ impl PrivateTradingContract {
 
         // Emit cancellation event
         env.events().publish(
-            (Symbol::new(&env, "order_cancelled"), trader),
+            (Symbol::new(env, "order_cancelled"), trader),
             order_id,
         );
 
@@ -391,7 +391,7 @@ impl PrivateTradingContract {
         }
 
         // Generate trade ID
-        let trade_id = buy_order_id ^ sell_order_id ^ current_time as u64;
+        let trade_id = buy_order_id ^ sell_order_id ^ current_time;
 
         // Update filled commitments (simplified - in production, aggregate properly)
         buy_order.filled_commitment = base_amount_commitment.clone();
@@ -407,7 +407,7 @@ impl PrivateTradingContract {
 
         // Emit trade execution event
         env.events().publish(
-            (Symbol::new(&env, "trade_executed"), executor),
+            (Symbol::new(env, "trade_executed"), executor),
             TradeExecution {
                 trade_id,
                 buy_order_id,
@@ -472,7 +472,7 @@ impl PrivateTradingContract {
         value: i128,
         blinding_factor: BytesN<32>,
     ) -> bool {
-        PrivacyPool::verify_commitment(&env, &commitment, value, &blinding_factor)
+        PrivacyPool::verify_commitment(env, &commitment, value, &blinding_factor)
     }
 
     /// Pause contract (admin only)
@@ -486,7 +486,7 @@ impl PrivateTradingContract {
         env.storage().instance().set(&DataKey::Paused, &true);
 
         env.events().publish(
-            (Symbol::new(&env, "paused"), admin),
+            (Symbol::new(env, "paused"), admin),
             (),
         );
 
@@ -504,7 +504,7 @@ impl PrivateTradingContract {
         env.storage().instance().set(&DataKey::Paused, &false);
 
         env.events().publish(
-            (Symbol::new(&env, "unpaused"), admin),
+            (Symbol::new(env, "unpaused"), admin),
             (),
         );
 
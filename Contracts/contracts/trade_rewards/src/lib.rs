@@ -0,0 +1,413 @@
+#![no_std]
+//! Reward ledger credited by the trading contract's `trade_and_reward`
+//!
+//! Implements the `add_reward(user, amount)` interface the trading contract
+//! invokes through `safe_invoke`, plus admin tooling for bulk distribution.
+
+use soroban_sdk::{contract, contracterror, contractimpl, symbol_short, token, Address, BytesN, Env, Vec};
+use shared::events::topics;
+
+mod storage;
+use storage::TradeRewardsStorage;
+
+/// Maximum number of entries accepted by `add_rewards_batch`
+const MAX_BATCH_SIZE: u32 = 100;
+
+/// Reward interface version reported to the trading contract
+const REWARD_VERSION: u32 = 1;
+
+/// Maximum vesting schedules kept per user; further rewards join the newest
+const MAX_VESTING_SCHEDULES: u32 = 32;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum TradeRewardsError {
+    Unauthorized = 6001,
+    InvalidAmount = 6002,
+    NotInitialized = 6003,
+    BatchLengthMismatch = 6004,
+    BatchSizeExceeded = 6005,
+    NothingToClaim = 6006,
+    InvalidToken = 6007,
+    InsufficientFunds = 6008,
+    RewardCapExceeded = 6009,
+    ClawbackExceedsBalance = 6010,
+    SnapshotNotFound = 6011,
+}
+
+#[contract]
+pub struct TradeRewardsContract;
+
+#[contractimpl]
+impl TradeRewardsContract {
+    /// Version of the reward interface, checked by the trading contract's
+    /// `add_reward_contract`
+    pub fn reward_version(_env: Env) -> u32 {
+        REWARD_VERSION
+    }
+
+    /// Initialize with an admin and the distributor (normally the trading contract)
+    pub fn init(env: Env, admin: Address, distributor: Address) -> Result<(), TradeRewardsError> {
+        if TradeRewardsStorage::is_initialized(&env) {
+            return Err(TradeRewardsError::Unauthorized);
+        }
+
+        TradeRewardsStorage::set_admin(&env, &admin);
+        TradeRewardsStorage::set_distributor(&env, &distributor);
+
+        Ok(())
+    }
+
+    /// Credit `amount` to `user` (distributor only), returning their new balance
+    pub fn add_reward(env: Env, user: Address, amount: i128) -> Result<i128, TradeRewardsError> {
+        let distributor = TradeRewardsStorage::get_distributor(&env)
+            .ok_or(TradeRewardsError::NotInitialized)?;
+        distributor.require_auth();
+
+        if amount <= 0 {
+            return Err(TradeRewardsError::InvalidAmount);
+        }
+
+        Self::issue(&env, amount)?;
+
+        Ok(Self::accrue(&env, &user, amount))
+    }
+
+    /// Credit `amount` to `user` at most once per `key` (distributor only)
+    ///
+    /// Lets relayers retry a grant after an RPC timeout: a repeated key accrues
+    /// nothing and returns the balance recorded when the key was first used.
+    pub fn add_reward_idempotent(
+        env: Env,
+        user: Address,
+        amount: i128,
+        key: BytesN<32>,
+    ) -> Result<i128, TradeRewardsError> {
+        let distributor = TradeRewardsStorage::get_distributor(&env)
+            .ok_or(TradeRewardsError::NotInitialized)?;
+        distributor.require_auth();
+
+        if let Some(balance) = TradeRewardsStorage::get_processed(&env, &key) {
+            return Ok(balance);
+        }
+
+        if amount <= 0 {
+            return Err(TradeRewardsError::InvalidAmount);
+        }
+
+        Self::issue(&env, amount)?;
+
+        let balance = Self::accrue(&env, &user, amount);
+        TradeRewardsStorage::set_processed(&env, &key, balance);
+
+        Ok(balance)
+    }
+
+    /// Credit `amounts[i]` to `users[i]` in one all-or-nothing call (admin only)
+    ///
+    /// Every entry is validated before anything is accrued, so one bad amount
+    /// rejects the whole batch.
+    pub fn add_rewards_batch(
+        env: Env,
+        users: Vec<Address>,
+        amounts: Vec<i128>,
+    ) -> Result<(), TradeRewardsError> {
+        let admin = TradeRewardsStorage::get_admin(&env)
+            .ok_or(TradeRewardsError::NotInitialized)?;
+        admin.require_auth();
+
+        if users.len() != amounts.len() {
+            return Err(TradeRewardsError::BatchLengthMismatch);
+        }
+
+        if users.len() > MAX_BATCH_SIZE {
+            return Err(TradeRewardsError::BatchSizeExceeded);
+        }
+
+        if amounts.iter().any(|amount| amount <= 0) {
+            return Err(TradeRewardsError::InvalidAmount);
+        }
+
+        let total = amounts.iter().try_fold(0i128, |total, amount| total.checked_add(amount))
+            .ok_or(TradeRewardsError::RewardCapExceeded)?;
+        Self::issue(&env, total)?;
+
+        for (user, amount) in users.iter().zip(amounts.iter()) {
+            Self::accrue(&env, &user, amount);
+        }
+
+        Ok(())
+    }
+
+    /// Set the token accrued rewards are paid out in (admin only)
+    ///
+    /// The contract must hold enough of this token to cover claims.
+    pub fn set_reward_token(env: Env, token: Address) -> Result<(), TradeRewardsError> {
+        let admin = TradeRewardsStorage::get_admin(&env)
+            .ok_or(TradeRewardsError::NotInitialized)?;
+        admin.require_auth();
+
+        TradeRewardsStorage::set_reward_token(&env, &token);
+
+        Ok(())
+    }
+
+    /// Get a user's accrued, unclaimed rewards, vested or not
+    pub fn reward_balance(env: Env, user: Address) -> i128 {
+        TradeRewardsStorage::get_balance(&env, &user)
+    }
+
+    /// Make each new reward vest linearly over `duration` seconds from the
+    /// time it is added; 0 makes new rewards claimable at once (admin only)
+    ///
+    /// Rewards already vesting keep their original schedule.
+    pub fn set_vesting_duration(env: Env, duration: u64) -> Result<(), TradeRewardsError> {
+        let admin = TradeRewardsStorage::get_admin(&env)
+            .ok_or(TradeRewardsError::NotInitialized)?;
+        admin.require_auth();
+
+        TradeRewardsStorage::set_vesting_duration(&env, duration);
+
+        Ok(())
+    }
+
+    /// Get the vesting duration applied to new rewards, in seconds
+    pub fn vesting_duration(env: Env) -> u64 {
+        TradeRewardsStorage::get_vesting_duration(&env)
+    }
+
+    /// Get the part of `user`'s balance that has vested and can be claimed now
+    pub fn claimable(env: Env, user: Address) -> i128 {
+        Self::vested_balance(&env, &user)
+    }
+
+    /// Pay out `user`'s vested rewards in `token`, deducting them from their balance
+    ///
+    /// `token` must be the configured reward token. Returns the amount claimed;
+    /// rewards still vesting stay in the balance.
+    pub fn claim(env: Env, user: Address, token: Address) -> Result<i128, TradeRewardsError> {
+        user.require_auth();
+
+        let reward_token = TradeRewardsStorage::get_reward_token(&env)
+            .ok_or(TradeRewardsError::NotInitialized)?;
+        if token != reward_token {
+            return Err(TradeRewardsError::InvalidToken);
+        }
+
+        let amount = Self::vested_balance(&env, &user);
+        if amount <= 0 {
+            return Err(TradeRewardsError::NothingToClaim);
+        }
+
+        let token_client = token::Client::new(&env, &token);
+        let contract_address = env.current_contract_address();
+        if token_client.balance(&contract_address) < amount {
+            return Err(TradeRewardsError::InsufficientFunds);
+        }
+
+        // Deduct the balance before paying out
+        let balance = TradeRewardsStorage::get_balance(&env, &user);
+        Self::write_balance(&env, &user, balance - amount);
+        Self::prune_vesting(&env, &user);
+        token_client.transfer(&contract_address, &user, &amount);
+
+        env.events().publish((topics::REWARD_CLAIMED, user), amount);
+
+        Ok(amount)
+    }
+
+    /// Deduct `amount` from `user`'s unclaimed rewards, e.g. after wash trading
+    /// is detected (admin only)
+    ///
+    /// Already-claimed rewards cannot be reversed, and lifetime issuance is
+    /// left unchanged. Returns the user's remaining balance.
+    pub fn clawback(env: Env, admin: Address, user: Address, amount: i128) -> Result<i128, TradeRewardsError> {
+        let stored_admin = TradeRewardsStorage::get_admin(&env)
+            .ok_or(TradeRewardsError::NotInitialized)?;
+        admin.require_auth();
+        if admin != stored_admin {
+            return Err(TradeRewardsError::Unauthorized);
+        }
+
+        if amount <= 0 {
+            return Err(TradeRewardsError::InvalidAmount);
+        }
+
+        let balance = TradeRewardsStorage::get_balance(&env, &user);
+        if amount > balance {
+            return Err(TradeRewardsError::ClawbackExceedsBalance);
+        }
+
+        let remaining = balance - amount;
+        Self::write_balance(&env, &user, remaining);
+
+        env.events().publish((topics::REWARD_CLAWED_BACK, user), amount);
+
+        Ok(remaining)
+    }
+
+    /// Cap the lifetime rewards this contract will issue (admin only)
+    pub fn set_max_total_rewards(env: Env, max: i128) -> Result<(), TradeRewardsError> {
+        let admin = TradeRewardsStorage::get_admin(&env)
+            .ok_or(TradeRewardsError::NotInitialized)?;
+        admin.require_auth();
+
+        if max < 0 {
+            return Err(TradeRewardsError::InvalidAmount);
+        }
+
+        TradeRewardsStorage::set_max_total_rewards(&env, max);
+
+        Ok(())
+    }
+
+    /// Get the lifetime rewards issued
+    pub fn total_rewards_issued(env: Env) -> i128 {
+        TradeRewardsStorage::get_total_issued(&env)
+    }
+
+    /// Get how much more can be issued before hitting the cap
+    pub fn remaining_reward_capacity(env: Env) -> i128 {
+        let max = TradeRewardsStorage::get_max_total_rewards(&env);
+        (max - TradeRewardsStorage::get_total_issued(&env)).max(0)
+    }
+
+    /// Record every user's current reward balance as a new snapshot (admin
+    /// only), returning its id
+    ///
+    /// Balances are checkpointed lazily on their next change, so taking a
+    /// snapshot costs the same regardless of the number of users.
+    pub fn snapshot(env: Env) -> Result<u32, TradeRewardsError> {
+        let admin = TradeRewardsStorage::get_admin(&env)
+            .ok_or(TradeRewardsError::NotInitialized)?;
+        admin.require_auth();
+
+        let snapshot_id = TradeRewardsStorage::get_snapshot_id(&env) + 1;
+        TradeRewardsStorage::set_snapshot_id(&env, snapshot_id);
+        TradeRewardsStorage::set_snapshot_ledger(&env, snapshot_id, env.ledger().sequence());
+
+        env.events().publish((symbol_short!("snapshot"),), snapshot_id);
+
+        Ok(snapshot_id)
+    }
+
+    /// Get the ledger sequence a snapshot was taken at
+    pub fn snapshot_ledger(env: Env, snapshot_id: u32) -> Option<u32> {
+        TradeRewardsStorage::get_snapshot_ledger(&env, snapshot_id)
+    }
+
+    /// Get `user`'s reward balance as of `snapshot_id`, for governance weighting
+    ///
+    /// Later accruals, claims and clawbacks do not change the result, so
+    /// voting power cannot be bought after the snapshot.
+    pub fn voting_power_at(env: Env, user: Address, snapshot_id: u32) -> Result<i128, TradeRewardsError> {
+        if snapshot_id == 0 || snapshot_id > TradeRewardsStorage::get_snapshot_id(&env) {
+            return Err(TradeRewardsError::SnapshotNotFound);
+        }
+
+        // The first checkpoint at or after the snapshot holds the balance it
+        // saw; with none, the balance has not changed since
+        let recorded = TradeRewardsStorage::get_checkpoints(&env, &user)
+            .iter()
+            .find(|(checkpoint_id, _)| *checkpoint_id >= snapshot_id)
+            .map(|(_, balance)| balance);
+
+        Ok(recorded.unwrap_or_else(|| TradeRewardsStorage::get_balance(&env, &user)))
+    }
+
+    /// Helper: Store a user's new balance, first checkpointing the old one
+    /// if this is its first change since the latest snapshot
+    fn write_balance(env: &Env, user: &Address, balance: i128) {
+        let snapshot_id = TradeRewardsStorage::get_snapshot_id(env);
+        if snapshot_id > 0 {
+            let mut checkpoints = TradeRewardsStorage::get_checkpoints(env, user);
+            let recorded = checkpoints.last().map(|(id, _)| id == snapshot_id).unwrap_or(false);
+            if !recorded {
+                checkpoints.push_back((snapshot_id, TradeRewardsStorage::get_balance(env, user)));
+                TradeRewardsStorage::set_checkpoints(env, user, &checkpoints);
+            }
+        }
+
+        TradeRewardsStorage::set_balance(env, user, balance);
+    }
+
+    /// Helper: Balance less the still-vesting part of every schedule
+    ///
+    /// Clawbacks come out of the vested part first, so this never goes negative.
+    fn vested_balance(env: &Env, user: &Address) -> i128 {
+        let now = env.ledger().timestamp();
+        let locked: i128 = TradeRewardsStorage::get_vesting(env, user)
+            .iter()
+            .map(|(amount, start, duration)| {
+                let elapsed = now.saturating_sub(start).min(duration);
+                amount - amount * elapsed as i128 / duration as i128
+            })
+            .sum();
+
+        (TradeRewardsStorage::get_balance(env, user) - locked).max(0)
+    }
+
+    /// Helper: Start vesting a new reward, folding it into the newest schedule
+    /// if it starts at the same time or the per-user limit is reached
+    fn add_vesting(env: &Env, user: &Address, amount: i128) {
+        let duration = TradeRewardsStorage::get_vesting_duration(env);
+        if duration == 0 {
+            return;
+        }
+
+        Self::prune_vesting(env, user);
+        let now = env.ledger().timestamp();
+        let mut schedules = TradeRewardsStorage::get_vesting(env, user);
+        match schedules.last() {
+            Some((last_amount, start, last_duration))
+                if (start == now && last_duration == duration) || schedules.len() >= MAX_VESTING_SCHEDULES =>
+            {
+                schedules.set(schedules.len() - 1, (last_amount + amount, start, last_duration));
+            }
+            _ => schedules.push_back((amount, now, duration)),
+        }
+        TradeRewardsStorage::set_vesting(env, user, &schedules);
+    }
+
+    /// Helper: Drop schedules that have fully vested
+    fn prune_vesting(env: &Env, user: &Address) {
+        let now = env.ledger().timestamp();
+        let schedules = TradeRewardsStorage::get_vesting(env, user);
+        let mut remaining = Vec::new(env);
+        for (amount, start, duration) in schedules.iter() {
+            if now < start.saturating_add(duration) {
+                remaining.push_back((amount, start, duration));
+            }
+        }
+        if remaining.len() != schedules.len() {
+            TradeRewardsStorage::set_vesting(env, user, &remaining);
+        }
+    }
+
+    /// Helper: Count `amount` against the issuance cap
+    fn issue(env: &Env, amount: i128) -> Result<(), TradeRewardsError> {
+        let total = TradeRewardsStorage::get_total_issued(env)
+            .checked_add(amount)
+            .filter(|total| *total <= TradeRewardsStorage::get_max_total_rewards(env))
+            .ok_or(TradeRewardsError::RewardCapExceeded)?;
+
+        TradeRewardsStorage::set_total_issued(env, total);
+
+        Ok(())
+    }
+
+    /// Helper: Add to a user's balance, emit a reward event and return the new balance
+    fn accrue(env: &Env, user: &Address, amount: i128) -> i128 {
+        let balance = TradeRewardsStorage::get_balance(env, user) + amount;
+        Self::write_balance(env, user, balance);
+        Self::add_vesting(env, user, amount);
+
+        env.events().publish((topics::REWARD_ADDED, user.clone()), amount);
+
+        balance
+    }
+}
+
+#[cfg(test)]
+mod test;
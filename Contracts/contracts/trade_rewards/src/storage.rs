@@ -0,0 +1,152 @@
+//! Storage module for the Trade Rewards Contract
+//!
+//! - Instance storage for admin, reward token and the distributor allowed to accrue rewards
+//! - Persistent storage for per-user reward balances, balance checkpoints, vesting schedules and processed idempotency keys
+
+use soroban_sdk::{contracttype, Address, BytesN, Env, Vec};
+
+/// Storage keys using enum for type safety and efficiency
+#[contracttype]
+#[derive(Clone, Debug)]
+pub enum TradeRewardsDataKey {
+    Admin,
+    Distributor,          // Contract (normally trading) allowed to call add_reward
+    RewardToken,          // Token rewards are paid out in on claim
+    MaxTotalRewards,      // Cap on lifetime rewards issued
+    TotalIssued,          // Lifetime rewards issued
+    Balance(Address),     // Accrued, unclaimed rewards per user
+    Processed(BytesN<32>), // Balance returned by the add_reward_idempotent call that used this key
+    SnapshotId,           // Latest snapshot ID (0 = none taken)
+    SnapshotLedger(u32),  // Ledger sequence each snapshot was taken at
+    Checkpoints(Address), // (snapshot_id, balance) recorded on a user's first balance change after each snapshot
+    VestingDuration,      // Seconds each new reward takes to vest linearly (0 = claimable at once)
+    Vesting(Address),     // (amount, start, duration) per user for rewards still vesting
+}
+
+/// Storage manager for trade rewards contract
+pub struct TradeRewardsStorage;
+
+impl TradeRewardsStorage {
+    // ============ Configuration ============
+
+    pub fn is_initialized(env: &Env) -> bool {
+        env.storage().instance().has(&TradeRewardsDataKey::Admin)
+    }
+
+    pub fn get_admin(env: &Env) -> Option<Address> {
+        env.storage().instance().get(&TradeRewardsDataKey::Admin)
+    }
+
+    pub fn set_admin(env: &Env, admin: &Address) {
+        env.storage().instance().set(&TradeRewardsDataKey::Admin, admin);
+    }
+
+    pub fn get_distributor(env: &Env) -> Option<Address> {
+        env.storage().instance().get(&TradeRewardsDataKey::Distributor)
+    }
+
+    pub fn set_distributor(env: &Env, distributor: &Address) {
+        env.storage().instance().set(&TradeRewardsDataKey::Distributor, distributor);
+    }
+
+    pub fn get_reward_token(env: &Env) -> Option<Address> {
+        env.storage().instance().get(&TradeRewardsDataKey::RewardToken)
+    }
+
+    pub fn set_reward_token(env: &Env, token: &Address) {
+        env.storage().instance().set(&TradeRewardsDataKey::RewardToken, token);
+    }
+
+    // ============ Issuance ============
+
+    pub fn get_max_total_rewards(env: &Env) -> i128 {
+        env.storage().instance().get(&TradeRewardsDataKey::MaxTotalRewards).unwrap_or(i128::MAX)
+    }
+
+    pub fn set_max_total_rewards(env: &Env, max: i128) {
+        env.storage().instance().set(&TradeRewardsDataKey::MaxTotalRewards, &max);
+    }
+
+    pub fn get_total_issued(env: &Env) -> i128 {
+        env.storage().instance().get(&TradeRewardsDataKey::TotalIssued).unwrap_or(0)
+    }
+
+    pub fn set_total_issued(env: &Env, total: i128) {
+        env.storage().instance().set(&TradeRewardsDataKey::TotalIssued, &total);
+    }
+
+    // ============ Balances (Persistent) ============
+
+    pub fn get_balance(env: &Env, user: &Address) -> i128 {
+        env.storage().persistent()
+            .get(&TradeRewardsDataKey::Balance(user.clone()))
+            .unwrap_or(0)
+    }
+
+    pub fn set_balance(env: &Env, user: &Address, balance: i128) {
+        env.storage().persistent().set(&TradeRewardsDataKey::Balance(user.clone()), &balance);
+    }
+
+    // ============ Idempotency (Persistent) ============
+
+    pub fn get_processed(env: &Env, key: &BytesN<32>) -> Option<i128> {
+        env.storage().persistent().get(&TradeRewardsDataKey::Processed(key.clone()))
+    }
+
+    pub fn set_processed(env: &Env, key: &BytesN<32>, balance: i128) {
+        env.storage().persistent().set(&TradeRewardsDataKey::Processed(key.clone()), &balance);
+    }
+
+    // ============ Snapshots ============
+
+    pub fn get_snapshot_id(env: &Env) -> u32 {
+        env.storage().instance().get(&TradeRewardsDataKey::SnapshotId).unwrap_or(0)
+    }
+
+    pub fn set_snapshot_id(env: &Env, snapshot_id: u32) {
+        env.storage().instance().set(&TradeRewardsDataKey::SnapshotId, &snapshot_id);
+    }
+
+    pub fn get_snapshot_ledger(env: &Env, snapshot_id: u32) -> Option<u32> {
+        env.storage().persistent().get(&TradeRewardsDataKey::SnapshotLedger(snapshot_id))
+    }
+
+    pub fn set_snapshot_ledger(env: &Env, snapshot_id: u32, ledger: u32) {
+        env.storage().persistent().set(&TradeRewardsDataKey::SnapshotLedger(snapshot_id), &ledger);
+    }
+
+    pub fn get_checkpoints(env: &Env, user: &Address) -> Vec<(u32, i128)> {
+        env.storage().persistent()
+            .get(&TradeRewardsDataKey::Checkpoints(user.clone()))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    pub fn set_checkpoints(env: &Env, user: &Address, checkpoints: &Vec<(u32, i128)>) {
+        env.storage().persistent().set(&TradeRewardsDataKey::Checkpoints(user.clone()), checkpoints);
+    }
+
+    // ============ Vesting ============
+
+    pub fn get_vesting_duration(env: &Env) -> u64 {
+        env.storage().instance().get(&TradeRewardsDataKey::VestingDuration).unwrap_or(0)
+    }
+
+    pub fn set_vesting_duration(env: &Env, duration: u64) {
+        env.storage().instance().set(&TradeRewardsDataKey::VestingDuration, &duration);
+    }
+
+    pub fn get_vesting(env: &Env, user: &Address) -> Vec<(i128, u64, u64)> {
+        env.storage().persistent()
+            .get(&TradeRewardsDataKey::Vesting(user.clone()))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    pub fn set_vesting(env: &Env, user: &Address, schedules: &Vec<(i128, u64, u64)>) {
+        let key = TradeRewardsDataKey::Vesting(user.clone());
+        if schedules.is_empty() {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, schedules);
+        }
+    }
+}
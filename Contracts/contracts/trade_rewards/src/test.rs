@@ -0,0 +1,328 @@
+#![cfg(test)]
+
+extern crate std;
+
+use super::*;
+use soroban_sdk::{testutils::Address as _, testutils::Events as _, testutils::Ledger as _, token, Address, BytesN, Env, IntoVal, Symbol, Vec};
+
+fn setup_env() -> (Env, Address, Address, Address) {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, TradeRewardsContract);
+    let admin = Address::generate(&env);
+    let distributor = Address::generate(&env);
+
+    (env, admin, distributor, contract_id)
+}
+
+fn setup_token(env: &Env) -> (Address, token::Client<'_>, token::StellarAssetClient<'_>) {
+    let issuer = Address::generate(env);
+    let token_id = env.register_stellar_asset_contract(issuer);
+    let token_client = token::Client::new(env, &token_id);
+    let token_admin = token::StellarAssetClient::new(env, &token_id);
+    (token_id, token_client, token_admin)
+}
+
+#[test]
+fn test_add_reward_accrues() {
+    let (env, admin, distributor, contract_id) = setup_env();
+    let client = TradeRewardsContractClient::new(&env, &contract_id);
+    client.init(&admin, &distributor);
+
+    let user = Address::generate(&env);
+    assert_eq!(client.add_reward(&user, &25), 25);
+    assert_eq!(client.add_reward(&user, &15), 40);
+    assert_eq!(client.reward_balance(&user), 40);
+
+    assert_eq!(client.try_add_reward(&user, &0), Err(Ok(TradeRewardsError::InvalidAmount)));
+    assert_eq!(client.try_init(&admin, &distributor), Err(Ok(TradeRewardsError::Unauthorized)));
+}
+
+#[test]
+fn test_add_reward_idempotent_ignores_repeated_key() {
+    let (env, admin, distributor, contract_id) = setup_env();
+    let client = TradeRewardsContractClient::new(&env, &contract_id);
+    client.init(&admin, &distributor);
+
+    let user = Address::generate(&env);
+    let key = BytesN::from_array(&env, &[1u8; 32]);
+    assert_eq!(client.add_reward_idempotent(&user, &25, &key), 25);
+    // A retried grant returns the original result without accruing again
+    assert_eq!(client.add_reward_idempotent(&user, &25, &key), 25);
+    assert_eq!(client.reward_balance(&user), 25);
+    assert_eq!(client.total_rewards_issued(), 25);
+
+    let other_key = BytesN::from_array(&env, &[2u8; 32]);
+    assert_eq!(client.add_reward_idempotent(&user, &10, &other_key), 35);
+    assert_eq!(client.reward_balance(&user), 35);
+    assert_eq!(client.total_rewards_issued(), 35);
+}
+
+#[test]
+fn test_add_rewards_batch_accrues_all() {
+    let (env, admin, distributor, contract_id) = setup_env();
+    let client = TradeRewardsContractClient::new(&env, &contract_id);
+    client.init(&admin, &distributor);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    client.add_rewards_batch(
+        &soroban_sdk::vec![&env, alice.clone(), bob.clone(), alice.clone()],
+        &soroban_sdk::vec![&env, 10i128, 20i128, 5i128],
+    );
+
+    assert_eq!(client.reward_balance(&alice), 15);
+    assert_eq!(client.reward_balance(&bob), 20);
+}
+
+#[test]
+fn test_add_rewards_batch_length_mismatch() {
+    let (env, admin, distributor, contract_id) = setup_env();
+    let client = TradeRewardsContractClient::new(&env, &contract_id);
+    client.init(&admin, &distributor);
+
+    let alice = Address::generate(&env);
+    let result = client.try_add_rewards_batch(
+        &soroban_sdk::vec![&env, alice.clone()],
+        &soroban_sdk::vec![&env, 10i128, 20i128],
+    );
+    assert_eq!(result, Err(Ok(TradeRewardsError::BatchLengthMismatch)));
+}
+
+#[test]
+fn test_add_rewards_batch_invalid_amount_aborts_batch() {
+    let (env, admin, distributor, contract_id) = setup_env();
+    let client = TradeRewardsContractClient::new(&env, &contract_id);
+    client.init(&admin, &distributor);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let result = client.try_add_rewards_batch(
+        &soroban_sdk::vec![&env, alice.clone(), bob.clone()],
+        &soroban_sdk::vec![&env, 10i128, 0i128],
+    );
+
+    assert_eq!(result, Err(Ok(TradeRewardsError::InvalidAmount)));
+    assert_eq!(client.reward_balance(&alice), 0);
+    assert_eq!(client.reward_balance(&bob), 0);
+}
+
+#[test]
+fn test_add_rewards_batch_size_cap() {
+    let (env, admin, distributor, contract_id) = setup_env();
+    let client = TradeRewardsContractClient::new(&env, &contract_id);
+    client.init(&admin, &distributor);
+
+    let user = Address::generate(&env);
+    let mut users = Vec::new(&env);
+    let mut amounts = Vec::new(&env);
+    for _ in 0..101 {
+        users.push_back(user.clone());
+        amounts.push_back(1i128);
+    }
+
+    let result = client.try_add_rewards_batch(&users, &amounts);
+    assert_eq!(result, Err(Ok(TradeRewardsError::BatchSizeExceeded)));
+}
+
+#[test]
+fn test_claim_pays_out_and_resets_balance() {
+    let (env, admin, distributor, contract_id) = setup_env();
+    let client = TradeRewardsContractClient::new(&env, &contract_id);
+    client.init(&admin, &distributor);
+
+    let (token_id, token_client, token_admin) = setup_token(&env);
+    client.set_reward_token(&token_id);
+    token_admin.mint(&contract_id, &1000);
+
+    let user = Address::generate(&env);
+    client.add_reward(&user, &25);
+    client.add_reward(&user, &50);
+    assert_eq!(client.reward_balance(&user), 75);
+
+    assert_eq!(client.claim(&user, &token_id), 75);
+    assert_eq!(token_client.balance(&user), 75);
+    assert_eq!(token_client.balance(&contract_id), 925);
+    assert_eq!(client.reward_balance(&user), 0);
+
+    assert_eq!(client.try_claim(&user, &token_id), Err(Ok(TradeRewardsError::NothingToClaim)));
+}
+
+#[test]
+fn test_claim_rejects_wrong_token_and_unfunded_payouts() {
+    let (env, admin, distributor, contract_id) = setup_env();
+    let client = TradeRewardsContractClient::new(&env, &contract_id);
+    client.init(&admin, &distributor);
+
+    let (token_id, _token_client, token_admin) = setup_token(&env);
+    let (other_token, _other_client, other_admin) = setup_token(&env);
+    let user = Address::generate(&env);
+    client.add_reward(&user, &100);
+
+    assert_eq!(client.try_claim(&user, &token_id), Err(Ok(TradeRewardsError::NotInitialized)));
+
+    client.set_reward_token(&token_id);
+    other_admin.mint(&contract_id, &1000);
+    assert_eq!(client.try_claim(&user, &other_token), Err(Ok(TradeRewardsError::InvalidToken)));
+
+    token_admin.mint(&contract_id, &99);
+    assert_eq!(client.try_claim(&user, &token_id), Err(Ok(TradeRewardsError::InsufficientFunds)));
+    assert_eq!(client.reward_balance(&user), 100);
+}
+
+#[test]
+fn test_reward_cap_stops_issuance() {
+    let (env, admin, distributor, contract_id) = setup_env();
+    let client = TradeRewardsContractClient::new(&env, &contract_id);
+    client.init(&admin, &distributor);
+    client.set_max_total_rewards(&100);
+    assert_eq!(client.remaining_reward_capacity(), 100);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    client.add_reward(&alice, &60);
+    client.add_rewards_batch(
+        &soroban_sdk::vec![&env, alice.clone(), bob.clone()],
+        &soroban_sdk::vec![&env, 30i128, 10i128],
+    );
+    assert_eq!(client.total_rewards_issued(), 100);
+    assert_eq!(client.remaining_reward_capacity(), 0);
+
+    // Filled exactly: the next unit is refused and nothing changes
+    assert_eq!(client.try_add_reward(&bob, &1), Err(Ok(TradeRewardsError::RewardCapExceeded)));
+    let result = client.try_add_rewards_batch(
+        &soroban_sdk::vec![&env, bob.clone()],
+        &soroban_sdk::vec![&env, 1i128],
+    );
+    assert_eq!(result, Err(Ok(TradeRewardsError::RewardCapExceeded)));
+    assert_eq!(client.total_rewards_issued(), 100);
+    assert_eq!(client.reward_balance(&alice), 90);
+    assert_eq!(client.reward_balance(&bob), 10);
+}
+
+#[test]
+fn test_batch_over_cap_is_rejected_whole() {
+    let (env, admin, distributor, contract_id) = setup_env();
+    let client = TradeRewardsContractClient::new(&env, &contract_id);
+    client.init(&admin, &distributor);
+    client.set_max_total_rewards(&50);
+
+    let alice = Address::generate(&env);
+    let result = client.try_add_rewards_batch(
+        &soroban_sdk::vec![&env, alice.clone(), alice.clone()],
+        &soroban_sdk::vec![&env, 30i128, 30i128],
+    );
+    assert_eq!(result, Err(Ok(TradeRewardsError::RewardCapExceeded)));
+    assert_eq!(client.total_rewards_issued(), 0);
+    assert_eq!(client.reward_balance(&alice), 0);
+}
+
+#[test]
+fn test_clawback_deducts_unclaimed_rewards() {
+    let (env, admin, distributor, contract_id) = setup_env();
+    let client = TradeRewardsContractClient::new(&env, &contract_id);
+    client.init(&admin, &distributor);
+
+    let user = Address::generate(&env);
+    client.add_reward(&user, &100);
+
+    // Partial clawback
+    assert_eq!(client.clawback(&admin, &user, &30), 70);
+    assert_eq!(client.reward_balance(&user), 70);
+    let (_, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(topics, (Symbol::new(&env, "clawback"), user.clone()).into_val(&env));
+    let amount: i128 = data.into_val(&env);
+    assert_eq!(amount, 30);
+
+    // More than what is left is rejected and nothing changes
+    assert_eq!(
+        client.try_clawback(&admin, &user, &71),
+        Err(Ok(TradeRewardsError::ClawbackExceedsBalance))
+    );
+    assert_eq!(client.reward_balance(&user), 70);
+
+    // Full clawback
+    assert_eq!(client.clawback(&admin, &user, &70), 0);
+    assert_eq!(client.reward_balance(&user), 0);
+
+    assert_eq!(client.try_clawback(&admin, &user, &0), Err(Ok(TradeRewardsError::InvalidAmount)));
+    let stranger = Address::generate(&env);
+    client.add_reward(&user, &10);
+    assert_eq!(client.try_clawback(&stranger, &user, &10), Err(Ok(TradeRewardsError::Unauthorized)));
+}
+
+#[test]
+fn test_voting_power_at_is_fixed_at_snapshot() {
+    let (env, admin, distributor, contract_id) = setup_env();
+    let client = TradeRewardsContractClient::new(&env, &contract_id);
+    client.init(&admin, &distributor);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    client.add_reward(&alice, &100);
+    client.add_reward(&bob, &40);
+
+    assert_eq!(client.try_voting_power_at(&alice, &1), Err(Ok(TradeRewardsError::SnapshotNotFound)));
+    let first = client.snapshot();
+    assert_eq!(first, 1);
+    assert_eq!(client.snapshot_ledger(&first), Some(env.ledger().sequence()));
+
+    // Balances move after the snapshot
+    client.add_reward(&alice, &50);
+    client.clawback(&admin, &bob, &40);
+    client.add_reward(&bob, &500);
+    assert_eq!(client.voting_power_at(&alice, &first), 100);
+    assert_eq!(client.voting_power_at(&bob, &first), 40);
+
+    let second = client.snapshot();
+    client.add_reward(&alice, &1);
+    assert_eq!(client.voting_power_at(&alice, &first), 100);
+    assert_eq!(client.voting_power_at(&alice, &second), 150);
+    assert_eq!(client.voting_power_at(&bob, &second), 500);
+
+    // A user who never held rewards has no voting power
+    let carol = Address::generate(&env);
+    assert_eq!(client.voting_power_at(&carol, &first), 0);
+    assert_eq!(client.try_voting_power_at(&alice, &3), Err(Ok(TradeRewardsError::SnapshotNotFound)));
+}
+
+#[test]
+fn test_rewards_vest_linearly() {
+    let (env, admin, distributor, contract_id) = setup_env();
+    let client = TradeRewardsContractClient::new(&env, &contract_id);
+    client.init(&admin, &distributor);
+
+    let (token_id, token_client, token_admin) = setup_token(&env);
+    client.set_reward_token(&token_id);
+    token_admin.mint(&contract_id, &10_000);
+    client.set_vesting_duration(&100);
+    assert_eq!(client.vesting_duration(), 100);
+    env.ledger().with_mut(|ledger| ledger.timestamp = 1_000);
+
+    let user = Address::generate(&env);
+    client.add_reward(&user, &1000);
+    // The full amount counts towards the balance but nothing is claimable yet
+    assert_eq!(client.reward_balance(&user), 1000);
+    assert_eq!(client.claimable(&user), 0);
+    assert_eq!(client.try_claim(&user, &token_id), Err(Ok(TradeRewardsError::NothingToClaim)));
+
+    // Halfway through only half can be taken
+    env.ledger().with_mut(|ledger| ledger.timestamp = 1_050);
+    assert_eq!(client.claimable(&user), 500);
+    assert_eq!(client.claim(&user, &token_id), 500);
+    assert_eq!(client.reward_balance(&user), 500);
+    assert_eq!(client.claimable(&user), 0);
+
+    // A second reward starts its own schedule
+    client.add_reward(&user, &200);
+    env.ledger().with_mut(|ledger| ledger.timestamp = 1_100);
+    assert_eq!(client.claimable(&user), 600);
+
+    // Everything once both durations have passed
+    env.ledger().with_mut(|ledger| ledger.timestamp = 1_150);
+    assert_eq!(client.claimable(&user), 700);
+    assert_eq!(client.claim(&user, &token_id), 700);
+    assert_eq!(token_client.balance(&user), 1200);
+    assert_eq!(client.reward_balance(&user), 0);
+}
@@ -0,0 +1,63 @@
+//! Escrowed fees for trades that may be disputed
+//!
+//! `trade_escrow` moves the fee into the contract instead of paying the
+//! recipient. Anyone can release it to the recipient once the hold window
+//! ends; until then the admin can refund it to the trader.
+
+use soroban_sdk::{contracttype, Address, Env};
+
+use crate::storage::TradingConfigKey;
+
+/// Lifecycle of an escrowed fee
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EscrowStatus {
+    Held,     // Fee is inside the contract awaiting release or refund
+    Released, // Fee was paid to the recipient
+    Refunded, // Fee was returned to the trader
+}
+
+/// Fee held by the contract until `release_after`
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct EscrowRecord {
+    pub trader: Address,
+    pub fee_token: Address,
+    pub amount: i128,
+    pub recipient: Address,
+    /// First ledger sequence at which the fee can be released
+    pub release_after: u32,
+    pub status: EscrowStatus,
+}
+
+/// Escrow records and per-token held totals
+pub struct EscrowManager;
+
+impl EscrowManager {
+    pub fn get(env: &Env, escrow_id: u64) -> Option<EscrowRecord> {
+        env.storage().persistent().get(&TradingConfigKey::Escrow(escrow_id))
+    }
+
+    pub fn set(env: &Env, escrow_id: u64, record: &EscrowRecord) {
+        env.storage().persistent().set(&TradingConfigKey::Escrow(escrow_id), record);
+    }
+
+    /// Allocate the next escrow id
+    pub fn next_id(env: &Env) -> u64 {
+        let id: u64 = env.storage().instance().get(&TradingConfigKey::NextEscrowId).unwrap_or(1);
+        env.storage().instance().set(&TradingConfigKey::NextEscrowId, &(id + 1));
+        id
+    }
+
+    /// Total of `token` currently held in escrow
+    pub fn held(env: &Env, token: &Address) -> i128 {
+        env.storage().persistent()
+            .get(&TradingConfigKey::EscrowHeld(token.clone()))
+            .unwrap_or(0)
+    }
+
+    pub fn add_held(env: &Env, token: &Address, amount: i128) {
+        let total = Self::held(env, token) + amount;
+        env.storage().persistent().set(&TradingConfigKey::EscrowHeld(token.clone()), &total);
+    }
+}
@@ -1,18 +1,79 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, contracttype, Address, Env, Symbol, symbol_short, Vec};
-use shared::fees::{FeeManager, FeeError};
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, token, vec, Address, BytesN, Env,
+    Error, IntoVal, Symbol, symbol_short, Vec,
+};
+use shared::fees::{FeeManager, FeeError, BPS_DENOMINATOR};
 use shared::governance::{
     GovernanceManager, GovernanceRole, UpgradeProposal,
 };
 use shared::oracle::{OracleAggregate, fetch_aggregate_price};
-use shared::events::{EventEmitter, TradeExecutedEvent, FeeCollectedEvent};
+use shared::safe_call::{errors as safe_call_errors, safe_invoke, safe_invoke_detailed, safe_invoke_with_result};
+use shared::events::{
+    topics, EventEmitter, TradeExecutedEvent, FeeCollectedEvent, ContractPausedEvent, ContractUnpausedEvent,
+    TradeRewardEvent,
+};
 
+mod escrow;
+mod multisig;
+mod roles;
+mod schedule;
 mod storage;
-use storage::{TradingStorage, OptimizedTradeStats, OptimizedOracleConfig, OptimizedOracleStatus, OptimizedTrade, TradingStorageMigration};
+pub use escrow::{EscrowRecord, EscrowStatus};
+pub use multisig::{AdminAction, PendingAction};
+pub use roles::TradingRole;
+pub use schedule::{ScheduleStatus, ScheduledTrade};
+pub use storage::{AdminLogEntry, EventMode, FeeSinkMode, MinFeeMode, ParamChange, PendingParamChange, Tier, TradeRecord};
+use escrow::EscrowManager;
+use multisig::MultisigManager;
+use roles::RoleManager;
+use schedule::ScheduleManager;
+use storage::{TradingStorage, PendingAdmin, OptimizedTradeStats, OptimizedOracleConfig, OptimizedOracleStatus, OptimizedTrade, TradingStorageMigration};
 
 /// Version of this contract implementation
 const CONTRACT_VERSION: u32 = 2;
 
+/// Extend instance storage once its remaining TTL drops below this many ledgers (~1 day)
+const INSTANCE_TTL_THRESHOLD: u32 = 17_280;
+
+/// Ledger count instance storage TTL is extended to (~30 days)
+const INSTANCE_TTL_EXTEND_TO: u32 = 518_400;
+
+/// Pause flag blocking trades and fee collection
+pub const PAUSE_TRADE: u32 = 1 << 0;
+
+/// Pause flag blocking reward cross-calls from trade_and_reward
+pub const PAUSE_REWARD: u32 = 1 << 1;
+
+/// Every pause flag; what `pause()` sets
+pub const PAUSE_ALL: u32 = PAUSE_TRADE | PAUSE_REWARD;
+
+/// Length of a rate-limit bucket, in seconds
+const RATE_LIMIT_WINDOW: u64 = 86_400;
+
+/// Maximum number of fee discount tiers
+const MAX_DISCOUNT_TIERS: u32 = 10;
+
+/// Maximum number of allowlisted reward contracts
+pub const MAX_REWARD_CONTRACTS: u32 = 20;
+
+/// Maximum number of allowlisted fee tokens
+pub const MAX_FEE_TOKENS: u32 = 20;
+
+/// Number of volume tiers above `Tier::None`
+const MAX_TIER_THRESHOLDS: u32 = 3;
+
+/// Maximum fee tokens `trade_auto` compares in one call
+pub const MAX_AUTO_CANDIDATES: u32 = 10;
+
+/// Largest reward multiplier a campaign may apply (10x), in basis points
+pub const MAX_REWARD_MULTIPLIER_BPS: u32 = 100_000;
+
+/// Id returned instead of a trade or escrow id when the call tripped the
+/// circuit breaker; it still succeeds so the pause is committed, and real
+/// ids start at 1
+pub const BREAKER_TRIPPED: u64 = 0;
+
 /// Trading contract with upgradeability and governance
 #[contract]
 pub struct UpgradeableTradingContract;
@@ -68,43 +129,156 @@ pub struct BatchTradeOperation {
     pub gas_saved: i128, // Estimated gas savings
 }
 
+/// Outcome of a successful trade_and_reward call
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TradeReceipt {
+    pub trade_id: u64,
+    pub fee_paid: i128,
+    pub reward_amount: i128,
+    pub referral_amount: i128,
+    pub reward_contract: Address,
+    /// Trader's reward total, if the reward contract's `add_reward` returns one
+    pub reward_total: Option<i128>,
+    pub ledger: u32,
+    /// Reward and refund cross-calls the trade made, for cost attribution
+    pub cross_calls: u32,
+}
+
+/// Outcome of a successful trade_auto call
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AutoTradeReceipt {
+    pub trade_id: u64,
+    /// Candidate token the fee was paid in
+    pub fee_token: Address,
+    pub fee_paid: i128,
+}
+
+/// First failing check reported by `can_trade()`
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TradeCheck {
+    Ok,
+    Paused,
+    TokenNotAllowed,
+    InvalidFee,
+    FeeOutOfBounds,
+    RateLimited,
+    InsufficientBalance,
+    BelowMinTrade,
+}
+
+/// Snapshot of the contract's main settings, returned by `config()`
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TradingConfig {
+    pub admin: Address,
+    pub paused: bool,
+    pub fee_bps: u32,
+    pub min_fee: i128,
+    pub max_fee: i128,
+    pub version: u32,
+}
+
+/// Operational status for monitoring, returned by `health()`
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct HealthStatus {
+    pub paused: bool,
+    pub terminated: bool,
+    pub initialized: bool,
+    /// Approximate ledgers until instance storage expires, from the
+    /// contract's own last extension; a lower bound, 0 if unknown
+    pub instance_ttl_remaining: u32,
+}
+
 // Note: TradeStats, OracleConfig, OracleStatus are now re-exported from storage module
 
+/// Outcome of `settle_trade`
+enum Settlement {
+    /// The trade was recorded; `fee_holder` is the address the routed fee
+    /// was actually paid to, which may be the fallback recipient
+    Settled { trade_id: u64, fee_holder: Address },
+    /// The circuit breaker tripped and paused trading; no fee moved
+    BreakerTripped,
+}
+
+impl Settlement {
+    /// Id handed back to callers, `BREAKER_TRIPPED` if nothing was traded
+    fn trade_id(&self) -> u64 {
+        match self {
+            Settlement::Settled { trade_id, .. } => *trade_id,
+            Settlement::BreakerTripped => BREAKER_TRIPPED,
+        }
+    }
+}
+
+#[contracterror]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
 #[repr(u32)]
-pub enum TradeError {
+pub enum TradingError {
     Unauthorized = 3001,
     InvalidAmount = 3002,
-    ContractPaused = 3003,
+    Paused = 3003,
     NotInitialized = 3004,
     BatchSizeExceeded = 3005,
-    BatchOperationFailed = 3006,
     OracleFailure = 3007,
+    InvalidFeeBps = 3008,
+    NoPendingAdmin = 3009,
+    RewardContractNotAllowed = 3010,
+    BatchLengthMismatch = 3011,
+    EmptyBatch = 3012,
+    NotPaused = 3013,
+    RateLimited = 3014,
+    InvalidWeights = 3015,
+    InvalidPauseFlag = 3016,
+    InvalidFeeBounds = 3017,
+    InsufficientBalance = 3018,
+    TokenNotAllowed = 3019,
+    NoPendingChange = 3020,
+    TimelockNotElapsed = 3021,
+    AlreadyInitialized = 3022,
+    InvalidAdmin = 3023,
+    NotAdmin = 3025,
+    FeeTooHigh = 3026,
+    Reentrant = 3027,
+    RewardFailed = 3028,
+    InvalidNonce = 3029,
+    InvalidWindow = 3030,
+    Expired = 3031,
+    AllowlistFull = 3032,
+    InvalidMultiplier = 3033,
+    Terminated = 3034,
+    ArithmeticOverflow = 3035,
+    NoDefaultRewardContract = 3036,
+    NotSigner = 3037,
+    AlreadyApproved = 3038,
+    InvalidThreshold = 3039,
+    ActionNotFound = 3040,
+    ActionAlreadyExecuted = 3041,
+    MultisigRequired = 3042,
+    SelfTrade = 3043,
+    RewardIfaceMismatch = 3044,
+    EscrowNotFound = 3045,
+    EscrowClosed = 3046,
+    EscrowLocked = 3047,
+    PermitUnsupported = 3049,
+    PermitRejected = 3050,
+    BelowMinTrade = 3051,
+    TransferAmountMismatch = 3052,
+    RewardTooSmall = 3053,
 }
 
-impl From<TradeError> for soroban_sdk::Error {
-    fn from(error: TradeError) -> Self {
-        soroban_sdk::Error::from_contract_error(error as u32)
-    }
-}
-
-impl From<&TradeError> for soroban_sdk::Error {
-    fn from(error: &TradeError) -> Self {
-        soroban_sdk::Error::from_contract_error(*error as u32)
-    }
-}
-
-impl From<soroban_sdk::Error> for TradeError {
-    fn from(_error: soroban_sdk::Error) -> Self {
-        TradeError::Unauthorized
-    }
-}
-
-impl From<FeeError> for TradeError {
+impl From<FeeError> for TradingError {
     fn from(error: FeeError) -> Self {
         match error {
-            FeeError::InsufficientBalance => TradeError::Unauthorized,
-            FeeError::InvalidAmount => TradeError::InvalidAmount,
+            FeeError::InsufficientBalance => TradingError::InsufficientBalance,
+            FeeError::InvalidAmount => TradingError::InvalidAmount,
+            FeeError::FeeTooHigh => TradingError::FeeTooHigh,
+            FeeError::RateLimited => TradingError::RateLimited,
+            FeeError::TokenNotAllowed => TradingError::TokenNotAllowed,
+            FeeError::ArithmeticOverflow => TradingError::ArithmeticOverflow,
         }
     }
 }
@@ -112,48 +286,127 @@ impl From<FeeError> for TradeError {
 #[contractimpl]
 impl UpgradeableTradingContract {
     /// Initialize the contract with admin and initial approvers
+    ///
+    /// `default_reward`, if given, is allowlisted and used by
+    /// `trade_and_reward_default`.
     pub fn init(
         env: Env,
         admin: Address,
         approvers: soroban_sdk::Vec<Address>,
         executor: Address,
-    ) -> Result<(), TradeError> {
+        default_reward: Option<Address>,
+    ) -> Result<(), TradingError> {
+        Self::init_core(&env, &admin, &approvers, Some(&executor))?;
+
+        if let Some(reward_id) = &default_reward {
+            TradingStorage::allow_reward_contract(&env, reward_id);
+        }
+        TradingStorage::set_default_reward_contract(&env, &default_reward);
+
+        Self::extend_instance_ttl(&env);
+
+        Ok(())
+    }
+
+    /// Initialize the contract and apply its whole configuration in one call
+    ///
+    /// Sets the fee rate and bounds from `config`, pauses everything if
+    /// `config.paused`, and allowlists `fee_tokens` and `reward_contracts`
+    /// immediately, with the same checks as the individual setters.
+    /// `config.admin` must equal `admin`; `config.version` is ignored. No
+    /// governance approvers or executor are registered.
+    pub fn initialize_full(
+        env: Env,
+        admin: Address,
+        config: TradingConfig,
+        fee_tokens: Vec<Address>,
+        reward_contracts: Vec<Address>,
+    ) -> Result<(), TradingError> {
+        if config.admin != admin {
+            return Err(TradingError::InvalidAdmin);
+        }
+        if config.fee_bps > BPS_DENOMINATOR {
+            return Err(TradingError::InvalidFeeBps);
+        }
+        if config.min_fee < 0 || config.min_fee > config.max_fee {
+            return Err(TradingError::InvalidFeeBounds);
+        }
+        if fee_tokens.len() > MAX_FEE_TOKENS || reward_contracts.len() > MAX_REWARD_CONTRACTS {
+            return Err(TradingError::AllowlistFull);
+        }
+
+        Self::init_core(&env, &admin, &Vec::new(&env), None)?;
+
+        TradingStorage::set_fee_bps(&env, config.fee_bps);
+        TradingStorage::set_fee_bounds(&env, config.min_fee, config.max_fee);
+        for token in fee_tokens.iter() {
+            TradingStorage::allow_fee_token(&env, &token);
+        }
+        for reward_id in reward_contracts.iter() {
+            Self::check_reward_version(&env, &reward_id)?;
+            TradingStorage::allow_reward_contract(&env, &reward_id);
+        }
+        if config.paused {
+            TradingStorage::set_paused(&env, true);
+        }
+
+        Self::extend_instance_ttl(&env);
+
+        Ok(())
+    }
+
+    /// Helper: Require `reward_id` to report at least `min_reward_version`
+    fn check_reward_version(env: &Env, reward_id: &Address) -> Result<(), TradingError> {
+        let version = safe_invoke_with_result::<u32>(env, reward_id, &Symbol::new(env, "reward_version"), Vec::new(env))
+            .map_err(|_| TradingError::RewardIfaceMismatch)?;
+        if version < TradingStorage::get_min_reward_version(env) {
+            return Err(TradingError::RewardIfaceMismatch);
+        }
+        Ok(())
+    }
+
+    /// Helper: Mark the contract initialized and store its roles, stats and version
+    fn init_core(
+        env: &Env,
+        admin: &Address,
+        approvers: &Vec<Address>,
+        executor: Option<&Address>,
+    ) -> Result<(), TradingError> {
         // Check if already initialized using optimized storage
-        if TradingStorage::is_initialized(&env) {
-            return Err(TradeError::Unauthorized);
+        if TradingStorage::is_initialized(env) {
+            return Err(TradingError::AlreadyInitialized);
+        }
+
+        // The contract cannot administer itself
+        if *admin == env.current_contract_address() {
+            return Err(TradingError::InvalidAdmin);
         }
 
         // Set initialization flag
-        TradingStorage::set_initialized(&env);
+        TradingStorage::set_initialized(env);
 
-        let mut roles = soroban_sdk::Map::new(&env);
+        let mut roles = soroban_sdk::Map::new(env);
         roles.set(admin.clone(), GovernanceRole::Admin);
         for approver in approvers.iter() {
             roles.set(approver, GovernanceRole::Approver);
         }
-        roles.set(executor.clone(), GovernanceRole::Executor);
-        TradingStorage::set_roles(&env, &roles);
-
-        let roles_key = symbol_short!("roles");
-        env.storage().persistent().set(&roles_key, &roles);
+        if let Some(executor) = executor {
+            roles.set(executor.clone(), GovernanceRole::Executor);
+        }
+        Self::store_roles(env, &roles);
 
         // Initialize stats in instance storage
-        TradingStorage::set_stats(&env, &OptimizedTradeStats::default());
+        TradingStorage::set_stats(env, &OptimizedTradeStats::default());
 
         // Store contract version
-        TradingStorage::set_version(&env, CONTRACT_VERSION);
+        TradingStorage::set_version(env, CONTRACT_VERSION);
 
         Ok(())
     }
     
     /// Migrate storage from legacy format (admin only)
-    pub fn migrate_storage(env: Env, admin: Address) -> Result<u64, TradeError> {
-        admin.require_auth();
-        
-        // Verify admin role
-        if !Self::is_admin(&env, &admin) {
-            return Err(TradeError::Unauthorized);
-        }
+    pub fn migrate_storage(env: Env, admin: Address) -> Result<u64, TradingError> {
+        Self::require_admin(&env, &admin)?;
         
         if !TradingStorageMigration::has_legacy_data(&env) {
             return Ok(0);
@@ -165,320 +418,4166 @@ impl UpgradeableTradingContract {
         Ok(migrated)
     }
 
-    pub fn set_oracle_config(
-        env: Env,
-        admin: Address,
-        oracles: Vec<Address>,
-        max_staleness: u64,
-        min_sources: u32,
-    ) -> Result<(), TradeError> {
-        admin.require_auth();
+    /// Replace the contract code with an uploaded wasm (admin only, while paused)
+    ///
+    /// State is preserved across the upgrade. Call `migrate` from the new code
+    /// afterwards to bring storage up to the new `CONTRACT_VERSION`.
+    ///
+    /// Once admin signers are configured this must go through `propose_action`.
+    pub fn upgrade(env: Env, admin: Address, new_wasm_hash: BytesN<32>) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+        Self::require_no_multisig(&env)?;
+
+        Self::run_admin_action(&env, &admin, &AdminAction::Upgrade(new_wasm_hash))
+    }
 
-        // Verify admin role using optimized storage
-        let role = TradingStorage::get_role(&env, &admin)
-            .ok_or(TradeError::Unauthorized)?;
+    /// Permanently shut the contract down (admin only, while paused)
+    ///
+    /// Intended as the final step after migrating to a new deployment. Every
+    /// mutating entrypoint, including unpause, fails with `Terminated`
+    /// afterwards; there is no way to undo it. Read-only getters and
+    /// `bump_ttl` keep working so the final state stays inspectable, and
+    /// users can still recover their own funds with `withdraw`,
+    /// `claim_rebate` and `cancel_scheduled`.
+    pub fn terminate(env: Env, admin: Address) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
 
-        if role != GovernanceRole::Admin {
-            return Err(TradeError::Unauthorized);
+        if !TradingStorage::is_paused(&env) {
+            return Err(TradingError::NotPaused);
         }
 
-        // Store in instance storage (cheaper for config data)
-        let config = OptimizedOracleConfig {
-            oracles,
-            max_staleness,
-            min_sources,
-        };
-        TradingStorage::set_oracle_config(&env, &config);
+        TradingStorage::set_terminated(&env);
+        Self::log_admin_action(&env, &admin, "terminate", 0);
+
+        env.events().publish((topics::CONTRACT_TERMINATED,), admin);
 
         Ok(())
     }
 
-    /// Execute a trade with fee collection
-    pub fn trade(
-        env: Env,
-        trader: Address,
-        pair: Symbol,
-        amount: i128,
-        price: i128,
-        is_buy: bool,
-        fee_token: Address,
-        fee_amount: i128,
-        fee_recipient: Address,
-    ) -> Result<u64, FeeError> {
-        trader.require_auth();
+    /// Check whether `terminate` has been called
+    pub fn is_terminated(env: Env) -> bool {
+        TradingStorage::is_terminated(&env)
+    }
 
-        // Verify not paused using optimized storage
-        if TradingStorage::is_paused(&env) {
-            panic!("PAUSED");
-        }
+    /// Reset trading parameters to their defaults in one call (admin only, while paused)
+    ///
+    /// Clears fee rates and bounds, limits, fee sink, event and self-trade
+    /// settings, the unpause delay, both allowlists, any queued change and
+    /// the pause itself, so trading resumes with a fresh config.
+    /// Accrued fees and other balances, trade history, the admin and roles
+    /// are kept.
+    pub fn reset_config(env: Env, admin: Address) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
 
-        // Collect fee first
-        FeeManager::collect_fee(&env, &fee_token, &trader, &fee_recipient, fee_amount)?;
+        if !TradingStorage::is_paused(&env) {
+            return Err(TradingError::NotPaused);
+        }
 
-        // Create trade record with optimized storage
-        let trade_id = TradingStorage::increment_trade_stats(&env, amount);
-        let trade = OptimizedTrade {
-            id: trade_id,
-            trader: trader.clone(),
-            pair,
-            amount,
-            price,
-            timestamp: env.ledger().timestamp(),
-            is_buy,
-        };
+        TradingStorage::reset_config(&env);
+        Self::log_admin_action(&env, &admin, "reset_config", 0);
 
-        // Store trade with optimized individual key
-        TradingStorage::set_trade(&env, &trade);
+        env.events().publish((Symbol::new(&env, "config_reset"),), admin);
 
-        Ok(trade_id)
+        Ok(())
     }
 
-    /// Execute multiple trades in a single transaction
-    pub fn batch_trade(
-        env: Env,
-        requests: soroban_sdk::Vec<BatchTradeRequest>,
-    ) -> Result<BatchTradeOperation, TradeError> {
-        // Maximum batch size to prevent resource exhaustion
-        const MAX_BATCH_SIZE: u32 = 50;
-        
-        if requests.len() > MAX_BATCH_SIZE {
-            return Err(TradeError::BatchSizeExceeded);
+    /// Recover tokens held by the contract itself (admin only, while paused)
+    ///
+    /// Accrued fees, escrows, deposits and the rebate pool are not
+    /// sweepable; anything else here was sent by mistake or is rounding dust.
+    pub fn sweep(env: Env, admin: Address, token: Address, to: Address, amount: i128) -> Result<(), TradingError> {
+        Self::check_not_reentrant(&env)?;
+        Self::require_admin(&env, &admin)?;
+
+        if !TradingStorage::is_paused(&env) {
+            return Err(TradingError::NotPaused);
         }
 
-        // Verify not paused using optimized storage
-        if TradingStorage::is_paused(&env) {
-            return Err(TradeError::ContractPaused);
+        if amount <= 0 {
+            return Err(TradingError::InvalidAmount);
         }
 
-        let mut successful_trades = soroban_sdk::Vec::new(&env);
-        let mut failed_trades = soroban_sdk::Vec::new(&env);
-        let mut total_fees_collected = 0i128;
-        let mut total_gas_saved = 0i128;
+        let token_client = token::Client::new(&env, &token);
+        let contract_address = env.current_contract_address();
+        let sweepable = token_client.balance(&contract_address)
+            - TradingStorage::get_accrued_fees(&env, &token)
+            - EscrowManager::held(&env, &token)
+            - TradingStorage::get_deposits_held(&env, &token)
+            - TradingStorage::get_rebate_pool(&env, &token);
+        if sweepable < amount {
+            return Err(TradingError::InsufficientBalance);
+        }
 
-        // Get current stats from optimized storage
-        let mut stats = TradingStorage::get_stats(&env);
+        token_client.transfer(&contract_address, &to, &amount);
 
-        // Process each trade request
-        for (index, request) in requests.iter().enumerate() {
-            // Authenticate the trader
-            request.trader.require_auth();
+        env.events().publish((topics::TOKENS_SWEPT, token), (to, amount));
 
-            let result = match Self::process_single_trade(
-                &env,
-                &request,
-                &mut stats,
-                index as u32,
-            ) {
-                Ok(trade_id) => {
-                    successful_trades.push_back(trade_id);
-                    total_fees_collected += request.fee_amount;
-                    total_gas_saved += 1000i128; // Estimated gas savings per trade
-                    BatchTradeResult {
-                        trade_id: Some(trade_id),
-                        success: true,
-                        error_code: None,
-                    }
-                }
-                Err(error) => BatchTradeResult {
-                    trade_id: None,
-                    success: false,
-                    error_code: Some(error as u32),
-                },
-            };
+        Ok(())
+    }
 
-            failed_trades.push_back(result);
-        }
+    /// Choose whether single-trade fees go straight to the fee recipient
+    /// (`Direct`) or accrue inside the contract (`Accrue`) (admin only)
+    pub fn set_fee_sink(env: Env, admin: Address, mode: FeeSinkMode) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
 
-        // Update stats in optimized storage
-        TradingStorage::set_stats(&env, &stats);
+        TradingStorage::set_fee_sink(&env, mode);
 
-        Ok(BatchTradeOperation {
-            successful_trades,
-            failed_trades,
-            total_fees_collected,
-            gas_saved: total_gas_saved,
-        })
+        Ok(())
     }
 
-    /// Process a single trade within a batch operation
-    fn process_single_trade(
-        env: &Env,
-        request: &BatchTradeRequest,
-        stats: &mut OptimizedTradeStats,
-        _batch_index: u32,
-    ) -> Result<u64, TradeError> {
-        // Validate amount
-        if request.amount <= 0 {
-            return Err(TradeError::InvalidAmount);
-        }
-
-        // Collect fee first
-        FeeManager::collect_fee(
-            env,
-            &request.fee_token,
-            &request.trader,
-            &request.fee_recipient,
-            request.fee_amount,
-        )?;
+    /// Allow or forbid trades whose fee recipient is the trader (admin only)
+    pub fn set_allow_self_trade(env: Env, admin: Address, allowed: bool) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
 
-        // Emit fee collected event
-        EventEmitter::fee_collected(env, FeeCollectedEvent {
-            payer: request.trader.clone(),
-            recipient: request.fee_recipient.clone(),
-            amount: request.fee_amount,
-            token: request.fee_token.clone(),
-            timestamp: env.ledger().timestamp(),
-        });
+        TradingStorage::set_self_trade_allowed(&env, allowed);
 
-        // Create trade record with optimized storage
-        let trade_id = stats.last_trade_id + 1;
-        let timestamp = env.ledger().timestamp();
-        let trade = OptimizedTrade {
-            id: trade_id,
-            trader: request.trader.clone(),
-            pair: request.pair.clone(),
-            amount: request.amount,
-            price: request.price,
-            timestamp,
-            is_buy: request.is_buy,
-        };
+        Ok(())
+    }
 
-        // Update stats
-        stats.total_trades += 1;
-        stats.total_volume += request.amount;
-        stats.last_trade_id = trade_id;
+    /// Whether trades may route their fee back to the trader
+    pub fn allow_self_trade(env: Env) -> bool {
+        TradingStorage::is_self_trade_allowed(&env)
+    }
 
-        // Store trade with optimized individual key
-        TradingStorage::set_trade(env, &trade);
+    /// Choose how much trades log: `Full` events, `Minimal` topic-only
+    /// events, or `Off` (admin only)
+    ///
+    /// Covers the per-trade fee collected and trade executed events; admin,
+    /// pause and reward events are always emitted in full.
+    pub fn set_event_mode(env: Env, admin: Address, mode: EventMode) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
 
-        // Emit trade executed event with batch index
-        EventEmitter::trade_executed(env, TradeExecutedEvent {
-            trade_id,
-            trader: request.trader.clone(),
-            pair: request.pair.clone(),
-            amount: request.amount,
-            price: request.price,
-            is_buy: request.is_buy,
-            fee_amount: request.fee_amount,
-            fee_token: request.fee_token.clone(),
-            timestamp,
-        });
+        TradingStorage::set_event_mode(&env, mode);
 
-        Ok(trade_id)
+        Ok(())
     }
 
-    /// Get current contract version
-    pub fn get_version(env: Env) -> u32 {
-        TradingStorage::get_version(&env)
+    /// Get the current trade event mode
+    pub fn event_mode(env: Env) -> EventMode {
+        TradingStorage::get_event_mode(&env)
     }
 
-    /// Get trading statistics
-    pub fn get_stats(env: Env) -> OptimizedTradeStats {
-        TradingStorage::get_stats(&env)
+    /// Get the current fee sink mode
+    pub fn fee_sink(env: Env) -> FeeSinkMode {
+        TradingStorage::get_fee_sink(&env)
     }
-    
-    /// Get trade by ID
-    pub fn get_trade(env: Env, trade_id: u64) -> Option<OptimizedTrade> {
-        TradingStorage::get_trade(&env, trade_id)
+
+    /// Get the fees in `token` accrued by the contract and not yet withdrawn
+    pub fn accrued_fees(env: Env, token: Address) -> i128 {
+        TradingStorage::get_accrued_fees(&env, &token)
     }
-    
+
+    /// Withdraw `amount` of accrued `token` fees to `to` (admin only)
+    pub fn withdraw_fees(env: Env, admin: Address, token: Address, to: Address, amount: i128) -> Result<(), TradingError> {
+        Self::check_not_reentrant(&env)?;
+        Self::require_admin(&env, &admin)?;
+
+        if amount <= 0 {
+            return Err(TradingError::InvalidAmount);
+        }
+
+        if TradingStorage::get_accrued_fees(&env, &token) < amount {
+            return Err(TradingError::InsufficientBalance);
+        }
+
+        TradingStorage::add_accrued_fees(&env, &token, -amount)?;
+        token::Client::new(&env, &token).transfer(&env.current_contract_address(), &to, &amount);
+
+        env.events().publish((topics::FEES_WITHDRAWN, token), (to, amount));
+
+        Ok(())
+    }
+
+    /// Withdraw accrued `token` fees to several recipients at once, sending
+    /// `amounts[i]` to `recipients[i]` (admin only)
+    ///
+    /// The total must not exceed the accrued balance. Transfers run in one
+    /// call, so a failed transfer reverts every portion.
+    pub fn withdraw_fees_split(
+        env: Env,
+        admin: Address,
+        token: Address,
+        recipients: Vec<Address>,
+        amounts: Vec<i128>,
+    ) -> Result<(), TradingError> {
+        const MAX_SPLIT_RECIPIENTS: u32 = 20;
+
+        Self::check_not_reentrant(&env)?;
+        Self::require_admin(&env, &admin)?;
+
+        if recipients.is_empty() {
+            return Err(TradingError::EmptyBatch);
+        }
+
+        if recipients.len() != amounts.len() {
+            return Err(TradingError::BatchLengthMismatch);
+        }
+
+        if recipients.len() > MAX_SPLIT_RECIPIENTS {
+            return Err(TradingError::BatchSizeExceeded);
+        }
+
+        if amounts.iter().any(|amount| amount <= 0) {
+            return Err(TradingError::InvalidAmount);
+        }
+
+        let total = amounts
+            .iter()
+            .try_fold(0i128, |total, amount| total.checked_add(amount))
+            .ok_or(TradingError::ArithmeticOverflow)?;
+        if TradingStorage::get_accrued_fees(&env, &token) < total {
+            return Err(TradingError::InsufficientBalance);
+        }
+
+        TradingStorage::add_accrued_fees(&env, &token, -total)?;
+        let token_client = token::Client::new(&env, &token);
+        let contract_address = env.current_contract_address();
+        for (to, amount) in recipients.iter().zip(amounts.iter()) {
+            token_client.transfer(&contract_address, &to, &amount);
+            env.events().publish((topics::FEES_WITHDRAWN, token.clone()), (to, amount));
+        }
+
+        Ok(())
+    }
+
+    /// Bring stored state up to this code's version (admin only)
+    ///
+    /// Returns the stored version after migrating.
+    pub fn migrate(env: Env, admin: Address) -> Result<u32, TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        TradingStorage::check_and_migrate(&env);
+
+        Ok(TradingStorage::get_version(&env))
+    }
+
+    pub fn set_oracle_config(
+        env: Env,
+        admin: Address,
+        oracles: Vec<Address>,
+        max_staleness: u64,
+        min_sources: u32,
+    ) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        // Store in instance storage (cheaper for config data)
+        let config = OptimizedOracleConfig {
+            oracles,
+            max_staleness,
+            min_sources,
+        };
+        TradingStorage::set_oracle_config(&env, &config);
+
+        Ok(())
+    }
+
+    /// Set the fee rate, in basis points, charged on trade amounts (admin only, timelocked)
+    pub fn set_fee_bps(env: Env, admin: Address, bps: u32) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+        Self::require_no_multisig(&env)?;
+
+        Self::run_admin_action(&env, &admin, &AdminAction::SetFeeBps(bps))?;
+
+        Ok(())
+    }
+
+    /// Get the fee rate, in basis points, charged on trade amounts
+    pub fn fee_bps(env: Env) -> u32 {
+        TradingStorage::get_fee_bps(&env)
+    }
+
+    /// Cap the fee volume a single trader can route per day (admin only)
+    ///
+    /// A limit of 0 disables rate limiting.
+    pub fn set_daily_limit(env: Env, admin: Address, limit: i128) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        if limit < 0 {
+            return Err(TradingError::InvalidAmount);
+        }
+
+        TradingStorage::set_daily_limit(&env, limit);
+
+        Ok(())
+    }
+
+    /// Get the per-trader daily fee volume limit (0 = unlimited)
+    pub fn daily_limit(env: Env) -> i128 {
+        TradingStorage::get_daily_limit(&env)
+    }
+
+    /// Auto-pause trading when a single trade's fee exceeds `threshold` (admin only)
+    ///
+    /// A threshold of 0 disables the breaker. Every path that takes a fee
+    /// checks it; the tripping call succeeds without moving the fee so the
+    /// pause is committed, returning `BREAKER_TRIPPED` where it returns an id.
+    pub fn set_circuit_breaker(env: Env, admin: Address, threshold: i128) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        if threshold < 0 {
+            return Err(TradingError::InvalidAmount);
+        }
+
+        TradingStorage::set_circuit_breaker(&env, threshold);
+
+        Ok(())
+    }
+
+    /// Get the circuit-breaker fee threshold (0 = disabled)
+    pub fn circuit_breaker(env: Env) -> i128 {
+        TradingStorage::get_circuit_breaker(&env)
+    }
+
+    /// Helper: Set `PAUSE_TRADE` if `fee_amount` exceeds the circuit-breaker
+    /// threshold, returning whether it tripped
+    fn trip_circuit_breaker(env: &Env, fee_amount: i128) -> bool {
+        let threshold = TradingStorage::get_circuit_breaker(env);
+        if threshold == 0 || fee_amount <= threshold {
+            return false;
+        }
+
+        let reason = Symbol::new(env, "circuit_breaker");
+        let flags = TradingStorage::get_pause_flags(env) | PAUSE_TRADE;
+        Self::update_pause_flags(env, env.current_contract_address(), flags, reason.clone());
+        env.events().publish((reason,), fee_amount);
+
+        true
+    }
+
+    /// Clamp bps-derived fees into `[min_fee, max_fee]` (admin only, timelocked)
+    pub fn set_fee_bounds(env: Env, admin: Address, min_fee: i128, max_fee: i128) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        if min_fee < 0 || min_fee > max_fee {
+            return Err(TradingError::InvalidFeeBounds);
+        }
+
+        Self::schedule_change(&env, ParamChange::FeeBounds(min_fee, max_fee));
+        Self::log_admin_action(&env, &admin, "set_fee_bounds", max_fee);
+
+        Ok(())
+    }
+
+    /// Get the fee clamp as `(min_fee, max_fee)`
+    pub fn fee_bounds(env: Env) -> (i128, i128) {
+        TradingStorage::get_fee_bounds(&env)
+    }
+
+    /// Charge `fee` instead of zero when a nonzero fee rate rounds a tiny
+    /// trade's fee down to nothing (admin only, timelocked)
+    ///
+    /// `fee` is in the fee token's smallest unit; 0 disables the floor. The
+    /// result is still subject to discounts and the fee bounds.
+    pub fn set_min_nonzero_fee(env: Env, admin: Address, fee: i128) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        if fee < 0 {
+            return Err(TradingError::InvalidAmount);
+        }
+
+        Self::schedule_change(&env, ParamChange::MinNonzeroFee(fee));
+
+        Ok(())
+    }
+
+    /// Get the fee charged when a bps fee rounds down to zero
+    pub fn min_nonzero_fee(env: Env) -> i128 {
+        TradingStorage::get_min_nonzero_fee(&env)
+    }
+
+    /// Choose how the minimum nonzero fee is set (admin only, timelocked)
+    ///
+    /// `Fixed` uses `min_nonzero_fee` for every token. `DecimalDerived(n)`
+    /// charges `10^(decimals - n)` of the fee token instead, so the floor is
+    /// the same economic size whatever the token's precision; a token with
+    /// `n` or fewer decimals gets a floor of 1. Decimals are read through
+    /// `decimals()` and cached. Tokens whose decimals cannot be read fall
+    /// back to `min_nonzero_fee`.
+    pub fn set_min_fee_mode(env: Env, admin: Address, mode: MinFeeMode) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        Self::schedule_change(&env, ParamChange::MinFeeMode(mode));
+
+        Ok(())
+    }
+
+    /// Get how the minimum nonzero fee is set
+    pub fn min_fee_mode(env: Env) -> MinFeeMode {
+        TradingStorage::get_min_fee_mode(&env)
+    }
+
+    /// Reject trades whose amount is below `amount` with `BelowMinTrade`
+    /// (admin only)
+    ///
+    /// Keeps dust trades out of the stats and recent-trade buffer; 0
+    /// disables the minimum.
+    pub fn set_min_trade_amount(env: Env, admin: Address, amount: i128) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        if amount < 0 {
+            return Err(TradingError::InvalidAmount);
+        }
+
+        TradingStorage::set_min_trade_amount(&env, amount);
+
+        Ok(())
+    }
+
+    /// Get the smallest accepted trade amount
+    pub fn min_trade_amount(env: Env) -> i128 {
+        TradingStorage::get_min_trade_amount(&env)
+    }
+
+    /// Make each trader wait `seconds` after a trade before their next one,
+    /// rejecting early trades with `RateLimited` (admin only)
+    ///
+    /// 0 disables the cooldown.
+    pub fn set_cooldown(env: Env, admin: Address, seconds: u64) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        TradingStorage::set_cooldown(&env, seconds);
+
+        Ok(())
+    }
+
+    /// Get the ledger timestamp from which `trader` may trade again; 0 if
+    /// the cooldown is off or they have not traded under it
+    pub fn next_allowed_trade(env: Env, trader: Address) -> u64 {
+        Self::next_trade_at(&env, &trader)
+    }
+
+    /// Reject any fee above `bps` of the payer's current fee-token balance
+    /// with `FeeTooHigh` (admin only)
+    ///
+    /// A safety rail against a misconfigured rate draining a holder in one
+    /// trade; 0 disables the cap.
+    pub fn set_max_fee_pct(env: Env, admin: Address, bps: u32) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        if bps > BPS_DENOMINATOR {
+            return Err(TradingError::InvalidFeeBps);
+        }
+
+        TradingStorage::set_max_fee_pct(&env, bps);
+
+        Ok(())
+    }
+
+    /// Get the largest fee allowed, as bps of the payer's balance
+    pub fn max_fee_pct(env: Env) -> u32 {
+        TradingStorage::get_max_fee_pct(&env)
+    }
+
+    /// Helper: Reject `fee` if it exceeds `max_fee_pct` of `payer`'s balance
+    fn check_fee_share(env: &Env, fee_token: &Address, payer: &Address, fee: i128) -> Result<(), TradingError> {
+        let max_pct = TradingStorage::get_max_fee_pct(env);
+        if max_pct == 0 || fee == 0 {
+            return Ok(());
+        }
+
+        let balance = token::Client::new(env, fee_token).balance(payer);
+        if fee > FeeManager::calculate_bps_fee(balance, max_pct)? {
+            return Err(TradingError::FeeTooHigh);
+        }
+        Ok(())
+    }
+
+    /// Burn `bps` of every single-trade fee through the fee token's `burn`,
+    /// sending the remainder to the fee recipient (admin only, timelocked)
+    pub fn set_burn_bps(env: Env, admin: Address, bps: u32) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        if bps > BPS_DENOMINATOR {
+            return Err(TradingError::InvalidFeeBps);
+        }
+        let total = bps
+            .checked_add(TradingStorage::get_rebate_bps(&env))
+            .ok_or(TradingError::InvalidFeeBps)?;
+        if total > BPS_DENOMINATOR {
+            return Err(TradingError::InvalidFeeBps);
+        }
+
+        Self::schedule_change(&env, ParamChange::BurnBps(bps));
+
+        Ok(())
+    }
+
+    /// Get the share of each single-trade fee that is burned, in basis points
+    pub fn burn_bps(env: Env) -> u32 {
+        TradingStorage::get_burn_bps(&env)
+    }
+
+    /// Pay `bps` of every single-trade fee into the rebate pool of the fee
+    /// token (admin only, timelocked)
+    ///
+    /// Together with `burn_bps` this may not exceed 100% of the fee.
+    pub fn set_rebate_bps(env: Env, admin: Address, bps: u32) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        if bps > BPS_DENOMINATOR {
+            return Err(TradingError::InvalidFeeBps);
+        }
+        let total = bps
+            .checked_add(TradingStorage::get_burn_bps(&env))
+            .ok_or(TradingError::InvalidFeeBps)?;
+        if total > BPS_DENOMINATOR {
+            return Err(TradingError::InvalidFeeBps);
+        }
+
+        Self::schedule_change(&env, ParamChange::RebateBps(bps));
+
+        Ok(())
+    }
+
+    /// Get the share of each single-trade fee paid into the rebate pool, in basis points
+    pub fn rebate_bps(env: Env) -> u32 {
+        TradingStorage::get_rebate_bps(&env)
+    }
+
+    /// Get the unclaimed rebates held for `token`
+    pub fn rebate_pool_balance(env: Env, token: Address) -> i128 {
+        TradingStorage::get_rebate_pool(&env, &token)
+    }
+
+    /// Claim `user`'s share of the `token` rebate pool, returning the amount paid
+    ///
+    /// The share is the user's fees that funded the pool since their last
+    /// claim, over everyone's unclaimed fees, so claims are proportional
+    /// regardless of who claims first. Works while the contract is paused.
+    pub fn claim_rebate(env: Env, user: Address, token: Address) -> Result<i128, TradingError> {
+        Self::check_not_reentrant(&env)?;
+        user.require_auth();
+
+        let (volume, total) = TradingStorage::get_rebate_volume(&env, &user, &token);
+        if volume == 0 {
+            return Ok(0);
+        }
+
+        let rebate = TradingStorage::get_rebate_pool(&env, &token)
+            .checked_mul(volume)
+            .ok_or(TradingError::ArithmeticOverflow)?
+            / total;
+        TradingStorage::add_rebate_volume(&env, &user, &token, -volume)?;
+        TradingStorage::add_rebate_pool(&env, &token, -rebate)?;
+        if rebate > 0 {
+            token::Client::new(&env, &token).transfer(&env.current_contract_address(), &user, &rebate);
+        }
+
+        env.events().publish((symbol_short!("rebate"), user), (token, rebate));
+
+        Ok(rebate)
+    }
+
+    /// Helper: Split `fee_amount` into `(burned, rebate, routed)`; the
+    /// rounding remainder goes to the recipient
+    ///
+    /// The rebate is capped so the parts never exceed the fee, even if
+    /// pending burn and rebate changes were applied together.
+    fn split_fee(env: &Env, fee_amount: i128) -> Result<(i128, i128, i128), TradingError> {
+        let burned = FeeManager::calculate_bps_fee(fee_amount, TradingStorage::get_burn_bps(env))?;
+        let rebate = FeeManager::calculate_bps_fee(fee_amount, TradingStorage::get_rebate_bps(env))?
+            .min(fee_amount - burned);
+        Ok((burned, rebate, fee_amount - burned - rebate))
+    }
+
+    /// Waive trade fees from `start_ledger` up to, but excluding, `end_ledger` (admin only)
+    ///
+    /// Replaces any previously scheduled holiday.
+    pub fn set_fee_holiday(env: Env, admin: Address, start_ledger: u32, end_ledger: u32) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        if start_ledger >= end_ledger {
+            return Err(TradingError::InvalidWindow);
+        }
+
+        TradingStorage::set_fee_holiday(&env, start_ledger, end_ledger);
+
+        Ok(())
+    }
+
+    /// Get the fee holiday window as `(start_ledger, end_ledger)`, if one is set
+    pub fn fee_holiday(env: Env) -> Option<(u32, u32)> {
+        TradingStorage::get_fee_holiday(&env)
+    }
+
+    /// Scale `trade_and_reward` rewards by `multiplier_bps` from `start_ledger`
+    /// up to, but excluding, `end_ledger` (admin only)
+    ///
+    /// 10_000 bps is 1x. Replaces any previously scheduled campaign; outside
+    /// the window rewards are passed through unscaled.
+    pub fn set_reward_multiplier(
+        env: Env,
+        admin: Address,
+        multiplier_bps: u32,
+        start_ledger: u32,
+        end_ledger: u32,
+    ) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        if multiplier_bps == 0 || multiplier_bps > MAX_REWARD_MULTIPLIER_BPS {
+            return Err(TradingError::InvalidMultiplier);
+        }
+        if start_ledger >= end_ledger {
+            return Err(TradingError::InvalidWindow);
+        }
+
+        TradingStorage::set_reward_multiplier(&env, multiplier_bps, start_ledger, end_ledger);
+
+        Ok(())
+    }
+
+    /// Get the reward campaign as `(multiplier_bps, start_ledger, end_ledger)`, if one is set
+    pub fn reward_multiplier(env: Env) -> Option<(u32, u32, u32)> {
+        TradingStorage::get_reward_multiplier(&env)
+    }
+
+    /// Helper: `reward_amount` scaled by the active reward campaign, if any
+    fn boosted_reward(env: &Env, reward_amount: i128) -> Result<i128, TradingError> {
+        match TradingStorage::get_reward_multiplier(env) {
+            Some((multiplier_bps, start_ledger, end_ledger)) => {
+                let ledger = env.ledger().sequence();
+                if ledger < start_ledger || ledger >= end_ledger {
+                    return Ok(reward_amount);
+                }
+                reward_amount
+                    .checked_mul(multiplier_bps as i128)
+                    .map(|scaled| scaled / BPS_DENOMINATOR as i128)
+                    .ok_or(TradingError::ArithmeticOverflow)
+            }
+            None => Ok(reward_amount),
+        }
+    }
+
+    /// Override the fee rate for trades paying in `token` (admin only, timelocked)
+    pub fn set_token_fee_bps(env: Env, admin: Address, token: Address, bps: u32) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        if bps > BPS_DENOMINATOR {
+            return Err(TradingError::InvalidFeeBps);
+        }
+
+        Self::schedule_change(&env, ParamChange::TokenFeeBps(token, bps));
+        Self::log_admin_action(&env, &admin, "set_token_fee_bps", bps as i128);
+
+        Ok(())
+    }
+
+    /// Get the fee rate applied to trades paying in `token`, falling back to the global rate
+    pub fn token_fee_bps(env: Env, token: Address) -> u32 {
+        TradingStorage::get_token_fee_bps(&env, &token)
+    }
+
+    /// Preview the fee `trade` would charge on `trade_amount` in `fee_token`,
+    /// before any reward-balance discount, without mutating state
+    pub fn quote_fee(env: Env, trade_amount: i128, fee_token: Address) -> Result<i128, TradingError> {
+        Self::compute_trade_fee(&env, trade_amount, &fee_token, None)
+    }
+
+    /// Fee `trade` would charge `trader` on `trade_amount` in `fee_token` right now
+    ///
+    /// This is the canonical quote: it runs the same computation as `trade`,
+    /// so the oracle or stored rate, minimum nonzero fee, fee holiday,
+    /// `trader`'s discount tier and the fee bounds all apply in trade order.
+    pub fn effective_fee(env: Env, trader: Address, fee_token: Address, trade_amount: i128) -> Result<i128, TradingError> {
+        Self::compute_trade_fee(&env, trade_amount, &fee_token, Some(&trader))
+    }
+
+    /// Pre-fund `amount` of `token` for later `trade_from_balance` calls,
+    /// returning the new balance
+    pub fn deposit(env: Env, trader: Address, token: Address, amount: i128) -> Result<i128, TradingError> {
+        Self::check_not_reentrant(&env)?;
+        Self::extend_instance_ttl(&env);
+        trader.require_auth();
+        Self::require_not_paused(&env, PAUSE_TRADE)?;
+        Self::require_token_not_paused(&env, &token)?;
+
+        if !TradingStorage::is_fee_token_allowed(&env, &token) {
+            return Err(TradingError::TokenNotAllowed);
+        }
+
+        if amount <= 0 {
+            return Err(TradingError::InvalidAmount);
+        }
+
+        FeeManager::collect_fee(&env, &token, &trader, &env.current_contract_address(), amount)?;
+        TradingStorage::add_deposits_held(&env, &token, amount)?;
+        let balance = TradingStorage::add_deposit(&env, &trader, &token, amount)?;
+
+        env.events().publish((symbol_short!("deposit"), trader), (token, amount));
+
+        Ok(balance)
+    }
+
+    /// Pay `fee` to `recipient` out of `trader`'s pre-funded balance
+    ///
+    /// No token moves: the fee is credited to `recipient`'s own balance,
+    /// which they can `withdraw`. Counts towards fee volume and the
+    /// recipient's received totals like a regular trade fee.
+    pub fn trade_from_balance(
+        env: Env,
+        trader: Address,
+        fee_token: Address,
+        fee: i128,
+        recipient: Address,
+    ) -> Result<(), TradingError> {
+        Self::check_not_reentrant(&env)?;
+        Self::extend_instance_ttl(&env);
+        trader.require_auth();
+        Self::require_not_paused(&env, PAUSE_TRADE)?;
+        Self::require_token_not_paused(&env, &fee_token)?;
+
+        if !TradingStorage::is_fee_token_allowed(&env, &fee_token) {
+            return Err(TradingError::TokenNotAllowed);
+        }
+
+        if fee <= 0 {
+            return Err(TradingError::InvalidAmount);
+        }
+
+        // A tripped breaker must commit the pause, so the fee is skipped rather than reverted
+        if Self::trip_circuit_breaker(&env, fee) {
+            return Ok(());
+        }
+
+        TradingStorage::add_deposit(&env, &trader, &fee_token, -fee)?;
+        TradingStorage::add_deposit(&env, &recipient, &fee_token, fee)?;
+        TradingStorage::add_fee_volume(&env, &fee_token, fee)?;
+        Self::record_tier_volume(&env, &trader, fee)?;
+        TradingStorage::add_received(&env, &recipient, fee, 1)?;
+
+        Self::emit_fee_collected(&env, FeeCollectedEvent {
+            payer: trader,
+            recipient,
+            amount: fee,
+            token: fee_token,
+            timestamp: env.ledger().timestamp(),
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw `amount` of `trader`'s pre-funded `token` balance, returning
+    /// what remains
+    ///
+    /// Works while the contract is paused so balances are never locked in.
+    pub fn withdraw(env: Env, trader: Address, token: Address, amount: i128) -> Result<i128, TradingError> {
+        Self::check_not_reentrant(&env)?;
+        trader.require_auth();
+
+        if amount <= 0 {
+            return Err(TradingError::InvalidAmount);
+        }
+
+        let balance = TradingStorage::add_deposit(&env, &trader, &token, -amount)?;
+        TradingStorage::add_deposits_held(&env, &token, -amount)?;
+        token::Client::new(&env, &token).transfer(&env.current_contract_address(), &trader, &amount);
+
+        env.events().publish((symbol_short!("withdraw"), trader), (token, amount));
+
+        Ok(balance)
+    }
+
+    /// Get `trader`'s pre-funded balance of `token`
+    pub fn balance_of(env: Env, trader: Address, token: Address) -> i128 {
+        TradingStorage::get_deposit(&env, &trader, &token)
+    }
+
+    /// Hold a `fee` from `trader` in escrow for `recipient` until ledger
+    /// `release_after`, returning the escrow id
+    ///
+    /// Use this for trades that may be contested: the fee sits inside the
+    /// contract until `release_escrow` pays it out, or the admin refunds it
+    /// with `refund_escrow` before the deadline. The self-trade, cooldown
+    /// and daily-limit checks run when the escrow opens. Returns
+    /// `BREAKER_TRIPPED` without taking the fee if it trips the circuit breaker.
+    pub fn trade_escrow(
+        env: Env,
+        trader: Address,
+        fee_token: Address,
+        fee: i128,
+        recipient: Address,
+        release_after: u32,
+    ) -> Result<u64, TradingError> {
+        Self::check_not_reentrant(&env)?;
+        Self::extend_instance_ttl(&env);
+        trader.require_auth();
+        Self::require_not_paused(&env, PAUSE_TRADE)?;
+        Self::require_token_not_paused(&env, &fee_token)?;
+
+        if !TradingStorage::is_fee_token_allowed(&env, &fee_token) {
+            return Err(TradingError::TokenNotAllowed);
+        }
+
+        if fee <= 0 {
+            return Err(TradingError::InvalidAmount);
+        }
+
+        if Self::trip_circuit_breaker(&env, fee) {
+            return Ok(BREAKER_TRIPPED);
+        }
+        Self::consume_trade_limits(&env, &trader, &recipient, fee)?;
+
+        let contract_address = env.current_contract_address();
+        FeeManager::collect_fee(&env, &fee_token, &trader, &contract_address, fee)?;
+        EscrowManager::add_held(&env, &fee_token, fee);
+
+        let escrow_id = EscrowManager::next_id(&env);
+        EscrowManager::set(&env, escrow_id, &EscrowRecord {
+            trader,
+            fee_token,
+            amount: fee,
+            recipient,
+            release_after,
+            status: EscrowStatus::Held,
+        });
+
+        env.events().publish((symbol_short!("escrow"), escrow_id), fee);
+
+        Ok(escrow_id)
+    }
+
+    /// Pay an escrowed fee to its recipient once `release_after` is reached
+    ///
+    /// Anyone can trigger the release; the funds only ever go to the
+    /// recipient named when the escrow was opened. The fee then counts
+    /// towards fee and tier volume and the recipient's received totals.
+    pub fn release_escrow(env: Env, escrow_id: u64) -> Result<(), TradingError> {
+        Self::check_not_reentrant(&env)?;
+        let mut record = Self::held_escrow(&env, escrow_id)?;
+        if env.ledger().sequence() < record.release_after {
+            return Err(TradingError::EscrowLocked);
+        }
+
+        Self::settle_escrow(&env, escrow_id, &mut record, EscrowStatus::Released);
+        Self::record_fee_paid(&env, &record.trader, &record.fee_token, record.amount, &record.recipient, record.amount)?;
+
+        env.events().publish((symbol_short!("esc_rel"), escrow_id), record.amount);
+
+        Ok(())
+    }
+
+    /// Return an escrowed fee to the trader before `release_after` (admin only)
+    ///
+    /// Fails with `Expired` once the hold window has ended.
+    pub fn refund_escrow(env: Env, admin: Address, escrow_id: u64) -> Result<(), TradingError> {
+        Self::check_not_reentrant(&env)?;
+        Self::require_admin(&env, &admin)?;
+
+        let mut record = Self::held_escrow(&env, escrow_id)?;
+        if env.ledger().sequence() >= record.release_after {
+            return Err(TradingError::Expired);
+        }
+
+        Self::settle_escrow(&env, escrow_id, &mut record, EscrowStatus::Refunded);
+
+        env.events().publish((symbol_short!("esc_ref"), escrow_id), record.amount);
+
+        Ok(())
+    }
+
+    /// Get an escrow record
+    pub fn escrow(env: Env, escrow_id: u64) -> Result<EscrowRecord, TradingError> {
+        EscrowManager::get(&env, escrow_id).ok_or(TradingError::EscrowNotFound)
+    }
+
+    /// Get the total of `token` currently held in escrow
+    pub fn escrow_held(env: Env, token: Address) -> i128 {
+        EscrowManager::held(&env, &token)
+    }
+
+    /// Helper: Load an escrow that has not been released or refunded yet
+    fn held_escrow(env: &Env, escrow_id: u64) -> Result<EscrowRecord, TradingError> {
+        let record = EscrowManager::get(env, escrow_id).ok_or(TradingError::EscrowNotFound)?;
+        if record.status != EscrowStatus::Held {
+            return Err(TradingError::EscrowClosed);
+        }
+        Ok(record)
+    }
+
+    /// Helper: Pay out a held escrow to the recipient (`Released`) or back to
+    /// the trader (`Refunded`) and close it
+    fn settle_escrow(
+        env: &Env,
+        escrow_id: u64,
+        record: &mut EscrowRecord,
+        status: EscrowStatus,
+    ) {
+        let to = match status {
+            EscrowStatus::Refunded => record.trader.clone(),
+            _ => record.recipient.clone(),
+        };
+
+        record.status = status;
+        EscrowManager::set(env, escrow_id, record);
+        EscrowManager::add_held(env, &record.fee_token, -record.amount);
+        token::Client::new(env, &record.fee_token).transfer(&env.current_contract_address(), &to, &record.amount);
+    }
+
+    /// Commit to a trade paying `fee` to `recipient` at ledger `execute_at`,
+    /// returning the scheduled trade id
+    ///
+    /// With `escrow` the fee moves into the contract now and counts towards
+    /// `escrow_held`; otherwise it is pulled from the trader's allowance to
+    /// this contract on execution.
+    #[allow(clippy::too_many_arguments)]
+    pub fn schedule_trade(
+        env: Env,
+        trader: Address,
+        pair: Symbol,
+        amount: i128,
+        price: i128,
+        is_buy: bool,
+        fee_token: Address,
+        fee: i128,
+        recipient: Address,
+        execute_at: u32,
+        escrow: bool,
+    ) -> Result<u64, TradingError> {
+        Self::check_not_reentrant(&env)?;
+        Self::extend_instance_ttl(&env);
+        trader.require_auth();
+        Self::require_not_paused(&env, PAUSE_TRADE)?;
+        Self::require_token_not_paused(&env, &fee_token)?;
+
+        if !TradingStorage::is_fee_token_allowed(&env, &fee_token) {
+            return Err(TradingError::TokenNotAllowed);
+        }
+
+        if fee <= 0 {
+            return Err(TradingError::InvalidAmount);
+        }
+
+        if escrow {
+            let contract_address = env.current_contract_address();
+            FeeManager::collect_fee(&env, &fee_token, &trader, &contract_address, fee)?;
+            EscrowManager::add_held(&env, &fee_token, fee);
+        }
+
+        let schedule_id = ScheduleManager::next_id(&env);
+        ScheduleManager::set(&env, schedule_id, &ScheduledTrade {
+            trader,
+            pair,
+            amount,
+            price,
+            is_buy,
+            fee_token,
+            fee,
+            recipient,
+            execute_at,
+            escrowed: escrow,
+            status: ScheduleStatus::Pending,
+        });
+
+        env.events().publish((symbol_short!("scheduled"), schedule_id), (fee, execute_at));
+
+        Ok(schedule_id)
+    }
+
+    /// Execute a scheduled trade once `execute_at` is reached, returning the
+    /// trade id
+    ///
+    /// Anyone can trigger execution. The trade settles like `trade` with a
+    /// fixed fee, so the min-trade, self-trade, cooldown and daily-limit
+    /// checks apply at execution time and it counts towards the same volumes
+    /// and received totals. Fails with `EscrowLocked` before `execute_at`,
+    /// and with `InsufficientBalance` if an unescrowed fee is no longer
+    /// covered by the trader's allowance. A fee that trips the circuit
+    /// breaker returns `BREAKER_TRIPPED` and leaves the trade pending.
+    pub fn execute_scheduled(env: Env, schedule_id: u64) -> Result<u64, TradingError> {
+        Self::check_not_reentrant(&env)?;
+        Self::require_not_paused(&env, PAUSE_TRADE)?;
+
+        let mut record = Self::pending_schedule(&env, schedule_id)?;
+        if env.ledger().sequence() < record.execute_at {
+            return Err(TradingError::EscrowLocked);
+        }
+        Self::require_token_not_paused(&env, &record.fee_token)?;
+
+        // The trade stays pending so it can run once trading resumes
+        if Self::trip_circuit_breaker(&env, record.fee) {
+            return Ok(BREAKER_TRIPPED);
+        }
+
+        record.status = ScheduleStatus::Executed;
+        ScheduleManager::set(&env, schedule_id, &record);
+
+        let contract_address = env.current_contract_address();
+        if record.escrowed {
+            EscrowManager::add_held(&env, &record.fee_token, -record.fee);
+        } else {
+            Self::check_fee_share(&env, &record.fee_token, &record.trader, record.fee)?;
+            let token_client = token::Client::new(&env, &record.fee_token);
+            // Report a missing or revoked allowance instead of a token trap
+            if token_client.allowance(&record.trader, &contract_address) < record.fee {
+                return Err(TradingError::InsufficientBalance);
+            }
+            token_client.transfer_from(&contract_address, &record.trader, &contract_address, &record.fee);
+        }
+
+        // The contract now holds the fee, so it pays it on as the sponsor
+        let settlement = Self::settle_trade(
+            &env,
+            Some(contract_address),
+            record.trader,
+            record.pair,
+            record.amount,
+            record.price,
+            record.is_buy,
+            record.fee_token.clone(),
+            record.fee,
+            record.recipient,
+            record.fee_token,
+            None,
+        )?;
+
+        env.events().publish((Symbol::new(&env, "sched_exec"), schedule_id), record.fee);
+
+        Ok(settlement.trade_id())
+    }
+
+    /// Cancel a pending scheduled trade, returning any escrowed fee (trader only)
+    pub fn cancel_scheduled(env: Env, trader: Address, schedule_id: u64) -> Result<(), TradingError> {
+        Self::check_not_reentrant(&env)?;
+        trader.require_auth();
+
+        let mut record = Self::pending_schedule(&env, schedule_id)?;
+        if record.trader != trader {
+            return Err(TradingError::Unauthorized);
+        }
+
+        record.status = ScheduleStatus::Cancelled;
+        ScheduleManager::set(&env, schedule_id, &record);
+
+        if record.escrowed {
+            EscrowManager::add_held(&env, &record.fee_token, -record.fee);
+            token::Client::new(&env, &record.fee_token).transfer(&env.current_contract_address(), &trader, &record.fee);
+        }
+
+        env.events().publish((Symbol::new(&env, "sched_cancel"), schedule_id), record.fee);
+
+        Ok(())
+    }
+
+    /// Get a scheduled trade
+    pub fn scheduled_trade(env: Env, schedule_id: u64) -> Result<ScheduledTrade, TradingError> {
+        ScheduleManager::get(&env, schedule_id).ok_or(TradingError::EscrowNotFound)
+    }
+
+    /// Helper: Load a scheduled trade that is still pending
+    fn pending_schedule(env: &Env, schedule_id: u64) -> Result<ScheduledTrade, TradingError> {
+        let record = ScheduleManager::get(env, schedule_id).ok_or(TradingError::EscrowNotFound)?;
+        if record.status != ScheduleStatus::Pending {
+            return Err(TradingError::EscrowClosed);
+        }
+        Ok(record)
+    }
+
+    /// Get the contract's own balance of each of `tokens`, for reconciling
+    /// accrued fees, escrows and deposits against actual holdings
+    ///
+    /// A token whose `balance` call fails is reported with a zero balance.
+    pub fn contract_balances(env: Env, tokens: Vec<Address>) -> Vec<(Address, i128)> {
+        let contract_address = env.current_contract_address();
+        let mut balances = Vec::new(&env);
+        for token in tokens.iter() {
+            let args = vec![&env, contract_address.into_val(&env)];
+            let balance = safe_invoke_with_result::<i128>(&env, &token, &Symbol::new(&env, "balance"), args)
+                .unwrap_or(0);
+            balances.push_back((token, balance));
+        }
+        balances
+    }
+
+    /// Get the decimals `token` reports, so frontends can render quoted fees
+    /// in human units
+    ///
+    /// The first successful lookup is cached per token and later calls skip
+    /// the cross-call. Returns `None`, caching nothing, if the token has no
+    /// `decimals` method or the call fails.
+    pub fn decimals(env: Env, token: Address) -> Option<u32> {
+        if let Some(decimals) = TradingStorage::get_token_decimals(&env, &token) {
+            return Some(decimals);
+        }
+
+        let decimals = Self::fetch_decimals(&env, &token)?;
+        TradingStorage::set_token_decimals(&env, &token, decimals);
+
+        Some(decimals)
+    }
+
+    /// Helper: Ask `token` for its decimals, bypassing the cache
+    fn fetch_decimals(env: &Env, token: &Address) -> Option<u32> {
+        safe_invoke_with_result::<u32>(env, token, &Symbol::new(env, "decimals"), Vec::new(env)).ok()
+    }
+
+    /// Helper: Fee charged by the bps-based trade entrypoints, from
+    /// `FeeManager::compute_fee` with the current rate, floor and bounds and
+    /// `trader`'s reward discount; zero during a fee holiday
+    fn compute_trade_fee(
+        env: &Env,
+        amount: i128,
+        fee_token: &Address,
+        trader: Option<&Address>,
+    ) -> Result<i128, TradingError> {
+        let bps = Self::current_fee_bps(env, fee_token);
+        let min_nonzero_fee = Self::min_nonzero_fee_for(env, fee_token);
+        let discount_bps = trader.map_or(0, |trader| Self::discount_bps_for(env, trader));
+        let (min_fee, max_fee) = TradingStorage::get_fee_bounds(env);
+        let fee = FeeManager::compute_fee(amount, bps, min_nonzero_fee, discount_bps, min_fee, max_fee)?;
+        if let Some((start_ledger, end_ledger)) = TradingStorage::get_fee_holiday(env) {
+            let ledger = env.ledger().sequence();
+            if ledger >= start_ledger && ledger < end_ledger {
+                return Ok(0);
+            }
+        }
+        Ok(fee)
+    }
+
+    /// Helper: Minimum nonzero fee in `fee_token` under the current `MinFeeMode`
+    ///
+    /// Quotes go through here, so a decimals lookup reads the cache without
+    /// filling it. A derived floor too large for an i128 saturates.
+    fn min_nonzero_fee_for(env: &Env, fee_token: &Address) -> i128 {
+        let MinFeeMode::DecimalDerived(n) = TradingStorage::get_min_fee_mode(env) else {
+            return TradingStorage::get_min_nonzero_fee(env);
+        };
+        let decimals = TradingStorage::get_token_decimals(env, fee_token).or_else(|| Self::fetch_decimals(env, fee_token));
+        let Some(decimals) = decimals else {
+            return TradingStorage::get_min_nonzero_fee(env);
+        };
+
+        10i128.saturating_pow(decimals.saturating_sub(n))
+    }
+
+    /// Helper: Count a committed reward failure, removing the contract from
+    /// the allowlist once it reaches `max_reward_failures`
+    fn record_reward_failure(env: &Env, reward_id: &Address) {
+        let failures = TradingStorage::get_reward_failures(env, reward_id).saturating_add(1);
+        let max_failures = TradingStorage::get_max_reward_failures(env);
+        if max_failures == 0 || failures < max_failures {
+            TradingStorage::set_reward_failures(env, reward_id, failures);
+            return;
+        }
+
+        TradingStorage::set_reward_failures(env, reward_id, 0);
+        TradingStorage::disallow_reward_contract(env, reward_id);
+        env.events().publish((Symbol::new(env, "reward_disabled"), reward_id.clone()), failures);
+    }
+
+    /// Helper: Map a failed `add_reward` cross-call onto a trading error
+    ///
+    /// Only a call positively classified as `FUNCTION_NOT_FOUND` is an
+    /// interface mismatch. Rejections, traps, auth failures and the host's
+    /// narrowed errors, which hide which of those happened, are all failed rewards.
+    fn reward_error(code: u32) -> TradingError {
+        if code == safe_call_errors::FUNCTION_NOT_FOUND {
+            TradingError::RewardIfaceMismatch
+        } else {
+            TradingError::RewardFailed
+        }
+    }
+
+    /// Helper: Fee rate reported by the fee oracle, or the stored rate for
+    /// `fee_token` if no oracle is set or its answer is unusable
+    fn current_fee_bps(env: &Env, fee_token: &Address) -> u32 {
+        let stored_bps = TradingStorage::get_token_fee_bps(env, fee_token);
+        let oracle = match TradingStorage::get_fee_oracle(env) {
+            Some(oracle) => oracle,
+            None => return stored_bps,
+        };
+
+        let args = vec![env, fee_token.into_val(env)];
+        match safe_invoke_with_result::<u32>(env, &oracle, &Symbol::new(env, "fee_bps"), args) {
+            Ok(bps) if bps <= BPS_DENOMINATOR => bps,
+            _ => stored_bps,
+        }
+    }
+
+    /// Helper: Best discount `trader`'s reward balance qualifies for; 0 if
+    /// discounts are off or the balance lookup fails
+    fn discount_bps_for(env: &Env, trader: &Address) -> u32 {
+        let source = match TradingStorage::get_discount_source(env) {
+            Some(source) => source,
+            None => return 0,
+        };
+        let tiers = TradingStorage::get_discount_tiers(env);
+        if tiers.is_empty() {
+            return 0;
+        }
+
+        let args = vec![env, trader.into_val(env)];
+        let balance = match safe_invoke_with_result::<i128>(env, &source, &Symbol::new(env, "reward_balance"), args) {
+            Ok(balance) => balance,
+            Err(_) => return 0,
+        };
+
+        tiers
+            .iter()
+            .filter(|(min_balance, _)| balance >= *min_balance)
+            .map(|(_, discount_bps)| discount_bps)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Execute a trade, charging `amount * token_fee_bps(fee_token) / 10_000` (rounded down, then clamped to the fee bounds) in `fee_token`
+    ///
+    /// `settle_token` names the traded asset when it differs from the fee
+    /// token; it is recorded in the trade event, recent trades and
+    /// `settle_volume`, but no `settle_token` balance moves. It defaults to
+    /// `fee_token` and must be allowlisted as well.
+    ///
+    /// `tag` attributes the trade to a campaign or UI source: it is included
+    /// in the trade event and its fee is added to `tag_volume(tag)`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn trade(
+        env: Env,
+        trader: Address,
+        pair: Symbol,
+        amount: i128,
+        price: i128,
+        is_buy: bool,
+        fee_token: Address,
+        fee_recipient: Address,
+        settle_token: Option<Address>,
+        tag: Option<Symbol>,
+    ) -> Result<u64, TradingError> {
+        Self::check_not_reentrant(&env)?;
+
+        let fee_amount = Self::compute_trade_fee(&env, amount, &fee_token, Some(&trader))?;
+        let settle_token = settle_token.unwrap_or_else(|| fee_token.clone());
+
+        Self::execute_trade(
+            &env,
+            None,
+            trader,
+            pair,
+            amount,
+            price,
+            is_buy,
+            fee_token,
+            fee_amount,
+            fee_recipient,
+            settle_token,
+            tag,
+        )
+        .map(|settlement| settlement.trade_id())
+    }
+
+    /// Execute a bps-fee trade in whichever of `candidate_tokens` the trader
+    /// can afford at the lowest `effective_fee`, ties going to the earlier one
+    ///
+    /// Candidates that are not allowlisted or are paused are skipped. Fails
+    /// with `TokenNotAllowed` if none are usable, `InsufficientBalance` if the
+    /// trader cannot cover the fee in any of them, and `BatchSizeExceeded` for
+    /// more than `MAX_AUTO_CANDIDATES` (10) candidates.
+    #[allow(clippy::too_many_arguments)]
+    pub fn trade_auto(
+        env: Env,
+        trader: Address,
+        pair: Symbol,
+        amount: i128,
+        price: i128,
+        is_buy: bool,
+        candidate_tokens: Vec<Address>,
+        fee_recipient: Address,
+    ) -> Result<AutoTradeReceipt, TradingError> {
+        Self::check_not_reentrant(&env)?;
+
+        if candidate_tokens.len() > MAX_AUTO_CANDIDATES {
+            return Err(TradingError::BatchSizeExceeded);
+        }
+
+        let mut best: Option<(Address, i128)> = None;
+        let mut any_usable = false;
+        for token in candidate_tokens.iter() {
+            // Only query vetted token contracts
+            if !TradingStorage::is_fee_token_allowed(&env, &token) || TradingStorage::is_token_paused(&env, &token) {
+                continue;
+            }
+            any_usable = true;
+
+            let fee = Self::compute_trade_fee(&env, amount, &token, Some(&trader))?;
+            let cheaper = best.as_ref().map(|(_, best_fee)| fee < *best_fee).unwrap_or(true);
+            if cheaper && token::Client::new(&env, &token).balance(&trader) >= fee {
+                best = Some((token, fee));
+            }
+        }
+
+        let (fee_token, fee_amount) = match best {
+            Some(best) => best,
+            None if any_usable => return Err(TradingError::InsufficientBalance),
+            None => return Err(TradingError::TokenNotAllowed),
+        };
+
+        let settlement = Self::execute_trade(
+            &env,
+            None,
+            trader,
+            pair,
+            amount,
+            price,
+            is_buy,
+            fee_token.clone(),
+            fee_amount,
+            fee_recipient,
+            fee_token.clone(),
+            None,
+        )?;
+
+        let fee_paid = match settlement {
+            Settlement::Settled { .. } => fee_amount,
+            Settlement::BreakerTripped => 0,
+        };
+        Ok(AutoTradeReceipt {
+            trade_id: settlement.trade_id(),
+            fee_token,
+            fee_paid,
+        })
+    }
+
+    /// Execute a bps-fee trade whose fee is paid by `sponsor` instead of `trader`
+    ///
+    /// Both must authorize the call. The fee, discount and daily limit are
+    /// computed for `trader`, and the trade is recorded and emitted under
+    /// `trader`; only the fee transfer debits `sponsor`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn trade_sponsored(
+        env: Env,
+        sponsor: Address,
+        trader: Address,
+        pair: Symbol,
+        amount: i128,
+        price: i128,
+        is_buy: bool,
+        fee_token: Address,
+        fee_recipient: Address,
+    ) -> Result<u64, TradingError> {
+        Self::check_not_reentrant(&env)?;
+
+        let fee_amount = Self::compute_trade_fee(&env, amount, &fee_token, Some(&trader))?;
+
+        Self::execute_trade(
+            &env,
+            Some(sponsor),
+            trader,
+            pair,
+            amount,
+            price,
+            is_buy,
+            fee_token.clone(),
+            fee_amount,
+            fee_recipient,
+            fee_token,
+            None,
+        )
+        .map(|settlement| settlement.trade_id())
+    }
+
+    /// Execute a bps-fee trade whose fee allowance comes from a token permit,
+    /// so no separate approve transaction is needed
+    ///
+    /// The fee token must expose
+    /// `permit(owner, spender, amount, deadline, signature)`; it is called to
+    /// let this contract spend the fee, which is then pulled in with
+    /// `transfer_from` and paid on as in `trade`. Tokens without `permit` fail
+    /// with `PermitUnsupported`, and a permit the token refuses with
+    /// `PermitRejected`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn trade_with_permit(
+        env: Env,
+        trader: Address,
+        pair: Symbol,
+        amount: i128,
+        price: i128,
+        is_buy: bool,
+        fee_token: Address,
+        fee_recipient: Address,
+        deadline: u32,
+        signature: BytesN<64>,
+    ) -> Result<u64, TradingError> {
+        Self::check_not_reentrant(&env)?;
+        Self::require_not_paused(&env, PAUSE_TRADE)?;
+        Self::require_token_not_paused(&env, &fee_token)?;
+
+        // Only call into vetted token contracts
+        if !TradingStorage::is_fee_token_allowed(&env, &fee_token) {
+            return Err(TradingError::TokenNotAllowed);
+        }
+
+        let fee_amount = Self::compute_trade_fee(&env, amount, &fee_token, Some(&trader))?;
+        Self::check_fee_share(&env, &fee_token, &trader, fee_amount)?;
+        let contract_address = env.current_contract_address();
+        if fee_amount > 0 {
+            let args = vec![
+                &env,
+                trader.into_val(&env),
+                contract_address.into_val(&env),
+                fee_amount.into_val(&env),
+                deadline.into_val(&env),
+                signature.into_val(&env),
+            ];
+            safe_invoke(&env, &fee_token, &Symbol::new(&env, "permit"), args).map_err(|code| {
+                if code == safe_call_errors::CALLEE_CONTRACT_ERROR {
+                    TradingError::PermitRejected
+                } else {
+                    TradingError::PermitUnsupported
+                }
+            })?;
+            token::Client::new(&env, &fee_token).transfer_from(&contract_address, &trader, &contract_address, &fee_amount);
+        }
+
+        // The contract now holds the fee, so it pays it on as the sponsor
+        let settlement = Self::execute_trade(
+            &env,
+            Some(contract_address.clone()),
+            trader.clone(),
+            pair,
+            amount,
+            price,
+            is_buy,
+            fee_token.clone(),
+            fee_amount,
+            fee_recipient,
+            fee_token.clone(),
+            None,
+        )?;
+
+        // A tripped circuit breaker skips the trade; hand the pulled fee back
+        if matches!(settlement, Settlement::BreakerTripped) && fee_amount > 0 {
+            token::Client::new(&env, &fee_token).transfer(&contract_address, &trader, &fee_amount);
+        }
+
+        Ok(settlement.trade_id())
+    }
+
+    /// Let `operator` place trades for `trader` with `trade_as`
+    pub fn add_operator(env: Env, trader: Address, operator: Address) -> Result<(), TradingError> {
+        Self::require_not_terminated(&env)?;
+        trader.require_auth();
+
+        TradingStorage::set_operator(&env, &trader, &operator, true);
+
+        env.events().publish((symbol_short!("operator"), trader), (operator, true));
+
+        Ok(())
+    }
+
+    /// Revoke `operator`'s right to trade for `trader`
+    pub fn remove_operator(env: Env, trader: Address, operator: Address) -> Result<(), TradingError> {
+        Self::require_not_terminated(&env)?;
+        trader.require_auth();
+
+        TradingStorage::set_operator(&env, &trader, &operator, false);
+
+        env.events().publish((symbol_short!("operator"), trader), (operator, false));
+
+        Ok(())
+    }
+
+    /// Check whether `operator` may trade for `trader`
+    pub fn is_operator(env: Env, trader: Address, operator: Address) -> bool {
+        TradingStorage::is_operator(&env, &trader, &operator)
+    }
+
+    /// Execute a bps-fee trade for `trader`, signed only by one of their operators
+    ///
+    /// The trade is attributed to `trader` and the fee is debited from them
+    /// with `transfer_from`, so `trader` must have approved this contract to
+    /// spend the fee token. Fails with `Unauthorized` unless `operator` was
+    /// added with `add_operator`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn trade_as(
+        env: Env,
+        operator: Address,
+        trader: Address,
+        pair: Symbol,
+        amount: i128,
+        price: i128,
+        is_buy: bool,
+        fee_token: Address,
+        fee_recipient: Address,
+    ) -> Result<u64, TradingError> {
+        Self::check_not_reentrant(&env)?;
+        operator.require_auth();
+
+        if !TradingStorage::is_operator(&env, &trader, &operator) {
+            return Err(TradingError::Unauthorized);
+        }
+
+        Self::settle_from_allowance(&env, trader, pair, amount, price, is_buy, fee_token, fee_recipient)
+    }
+
+    /// Let `relayer` submit `trade_with_allowance` trades for `trader`
+    pub fn approve_relayer(env: Env, trader: Address, relayer: Address) -> Result<(), TradingError> {
+        Self::require_not_terminated(&env)?;
+        trader.require_auth();
+
+        TradingStorage::set_relayer(&env, &trader, &relayer, true);
+
+        env.events().publish((symbol_short!("relayer"), trader), (relayer, true));
+
+        Ok(())
+    }
+
+    /// Revoke `relayer`'s right to spend `trader`'s allowance
+    pub fn revoke_relayer(env: Env, trader: Address, relayer: Address) -> Result<(), TradingError> {
+        Self::require_not_terminated(&env)?;
+        trader.require_auth();
+
+        TradingStorage::set_relayer(&env, &trader, &relayer, false);
+
+        env.events().publish((symbol_short!("relayer"), trader), (relayer, false));
+
+        Ok(())
+    }
+
+    /// Check whether `relayer` may spend `trader`'s allowance with `trade_with_allowance`
+    pub fn is_relayer(env: Env, trader: Address, relayer: Address) -> bool {
+        TradingStorage::is_relayer(&env, &trader, &relayer)
+    }
+
+    /// Execute a bps-fee trade for `trader` paid from their token allowance
+    ///
+    /// No trader signature is needed per trade, but `relayer` must have been
+    /// approved by `trader` with `approve_relayer`; anyone else fails with
+    /// `Unauthorized`. The allowance `trader` granted this contract on
+    /// `fee_token` bounds how much the relayer can spend. Fails with
+    /// `InsufficientBalance` if the allowance does not cover the fee.
+    #[allow(clippy::too_many_arguments)]
+    pub fn trade_with_allowance(
+        env: Env,
+        relayer: Address,
+        trader: Address,
+        pair: Symbol,
+        amount: i128,
+        price: i128,
+        is_buy: bool,
+        fee_token: Address,
+        fee_recipient: Address,
+    ) -> Result<u64, TradingError> {
+        Self::check_not_reentrant(&env)?;
+        relayer.require_auth();
+
+        if !TradingStorage::is_relayer(&env, &trader, &relayer) {
+            return Err(TradingError::Unauthorized);
+        }
+
+        Self::settle_from_allowance(&env, trader, pair, amount, price, is_buy, fee_token, fee_recipient)
+    }
+
+    /// Execute a bps-fee trade that consumes `nonce`, for relayed trade intents
+    ///
+    /// `nonce` must equal `current_nonce(trader)`; replayed or skipped nonces
+    /// are rejected with `InvalidNonce`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn trade_with_nonce(
+        env: Env,
+        trader: Address,
+        nonce: u64,
+        pair: Symbol,
+        amount: i128,
+        price: i128,
+        is_buy: bool,
+        fee_token: Address,
+        fee_recipient: Address,
+    ) -> Result<u64, TradingError> {
+        Self::check_not_reentrant(&env)?;
+
+        let expected = TradingStorage::get_nonce(&env, &trader);
+        if nonce != expected {
+            return Err(TradingError::InvalidNonce);
+        }
+        TradingStorage::set_nonce(&env, &trader, expected + 1);
+
+        let fee_amount = Self::compute_trade_fee(&env, amount, &fee_token, Some(&trader))?;
+
+        Self::execute_trade(
+            &env,
+            None,
+            trader,
+            pair,
+            amount,
+            price,
+            is_buy,
+            fee_token.clone(),
+            fee_amount,
+            fee_recipient,
+            fee_token,
+            None,
+        )
+        .map(|settlement| settlement.trade_id())
+    }
+
+    /// Dry-run the checks a trade of `amount` paying `fee` in `fee_token`
+    /// must pass, returning the first one that fails without transferring anything
+    pub fn can_trade(env: Env, trader: Address, fee_token: Address, fee: i128, amount: i128) -> TradeCheck {
+        if TradingStorage::is_operation_paused(&env, PAUSE_TRADE) || TradingStorage::is_token_paused(&env, &fee_token) {
+            return TradeCheck::Paused;
+        }
+
+        if !TradingStorage::is_fee_token_allowed(&env, &fee_token) {
+            return TradeCheck::TokenNotAllowed;
+        }
+
+        if amount < TradingStorage::get_min_trade_amount(&env) {
+            return TradeCheck::BelowMinTrade;
+        }
+
+        if fee < 0 {
+            return TradeCheck::InvalidFee;
+        }
+
+        let (min_fee, max_fee) = TradingStorage::get_fee_bounds(&env);
+        if fee < min_fee || fee > max_fee {
+            return TradeCheck::FeeOutOfBounds;
+        }
+
+        if Self::check_daily_limit(&env, &trader, fee).is_err()
+            || Self::next_trade_at(&env, &trader) > env.ledger().timestamp()
+        {
+            return TradeCheck::RateLimited;
+        }
+
+        if token::Client::new(&env, &fee_token).balance(&trader) < fee {
+            return TradeCheck::InsufficientBalance;
+        }
+
+        TradeCheck::Ok
+    }
+
+    /// Execute a bps-fee trade that reverts with `Expired` once the ledger
+    /// timestamp is past `deadline`
+    #[allow(clippy::too_many_arguments)]
+    pub fn trade_with_deadline(
+        env: Env,
+        trader: Address,
+        pair: Symbol,
+        amount: i128,
+        price: i128,
+        is_buy: bool,
+        fee_token: Address,
+        fee_recipient: Address,
+        deadline: u64,
+    ) -> Result<u64, TradingError> {
+        Self::check_not_reentrant(&env)?;
+
+        if env.ledger().timestamp() > deadline {
+            return Err(TradingError::Expired);
+        }
+
+        let fee_amount = Self::compute_trade_fee(&env, amount, &fee_token, Some(&trader))?;
+
+        Self::execute_trade(
+            &env,
+            None,
+            trader,
+            pair,
+            amount,
+            price,
+            is_buy,
+            fee_token.clone(),
+            fee_amount,
+            fee_recipient,
+            fee_token,
+            None,
+        )
+        .map(|settlement| settlement.trade_id())
+    }
+
+    /// Get the nonce the next `trade_with_nonce` call from `trader` must use
+    pub fn current_nonce(env: Env, trader: Address) -> u64 {
+        TradingStorage::get_nonce(&env, &trader)
+    }
+
+    /// Execute a bps-fee trade, reverting if the computed fee exceeds `max_fee`
+    #[allow(clippy::too_many_arguments)]
+    pub fn trade_checked(
+        env: Env,
+        trader: Address,
+        pair: Symbol,
+        amount: i128,
+        price: i128,
+        is_buy: bool,
+        fee_token: Address,
+        fee_recipient: Address,
+        max_fee: i128,
+    ) -> Result<u64, TradingError> {
+        Self::check_not_reentrant(&env)?;
+
+        let fee_amount = Self::compute_trade_fee(&env, amount, &fee_token, Some(&trader))?;
+        if fee_amount > max_fee {
+            return Err(TradingError::FeeTooHigh);
+        }
+
+        Self::execute_trade(
+            &env,
+            None,
+            trader,
+            pair,
+            amount,
+            price,
+            is_buy,
+            fee_token.clone(),
+            fee_amount,
+            fee_recipient,
+            fee_token,
+            None,
+        )
+        .map(|settlement| settlement.trade_id())
+    }
+
+    /// Execute a trade with a caller-supplied flat fee
+    #[allow(clippy::too_many_arguments)]
+    pub fn trade_fixed_fee(
+        env: Env,
+        trader: Address,
+        pair: Symbol,
+        amount: i128,
+        price: i128,
+        is_buy: bool,
+        fee_token: Address,
+        fee_amount: i128,
+        fee_recipient: Address,
+    ) -> Result<u64, TradingError> {
+        Self::check_not_reentrant(&env)?;
+
+        Self::execute_trade(
+            &env,
+            None,
+            trader,
+            pair,
+            amount,
+            price,
+            is_buy,
+            fee_token.clone(),
+            fee_amount,
+            fee_recipient,
+            fee_token,
+            None,
+        )
+        .map(|settlement| settlement.trade_id())
+    }
+
+    /// Accept `token` as a fee token (admin only, timelocked)
+    ///
+    /// Fails with `AllowlistFull` once `MAX_FEE_TOKENS` are allowlisted.
+    pub fn allow_fee_token(env: Env, admin: Address, token: Address) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        if !TradingStorage::is_fee_token_allowed(&env, &token)
+            && TradingStorage::get_fee_token_list(&env).len() >= MAX_FEE_TOKENS
+        {
+            return Err(TradingError::AllowlistFull);
+        }
+
+        Self::schedule_change(&env, ParamChange::AllowFeeToken(token));
+        Self::log_admin_action(&env, &admin, "allow_fee_token", 0);
+
+        Ok(())
+    }
+
+    /// Stop accepting `token` as a fee token (admin only, takes effect immediately)
+    pub fn disallow_fee_token(env: Env, admin: Address, token: Address) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        TradingStorage::disallow_fee_token(&env, &token);
+        Self::log_admin_action(&env, &admin, "disallow_fee_token", 0);
+
+        Ok(())
+    }
+
+    /// Check whether a fee token is on the allowlist
+    pub fn is_fee_token_allowed(env: Env, token: Address) -> bool {
+        TradingStorage::is_fee_token_allowed(&env, &token)
+    }
+
+    /// List the allowlisted fee tokens in the order they were added
+    ///
+    /// Bounded by `MAX_FEE_TOKENS`.
+    pub fn list_fee_tokens(env: Env) -> Vec<Address> {
+        TradingStorage::get_fee_token_list(&env)
+    }
+
+    /// Register the Stellar Asset Contract wrapping native XLM and accept it
+    /// as a fee token (admin only, timelocked)
+    ///
+    /// Fees are moved through the standard token interface, so the native
+    /// SAC settles exactly like any other fee token.
+    pub fn set_native_token(env: Env, admin: Address, token: Address) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        Self::schedule_change(&env, ParamChange::NativeToken(token));
+
+        Ok(())
+    }
+
+    /// Get the configured native XLM asset contract, if any
+    pub fn native_token(env: Env) -> Option<Address> {
+        TradingStorage::get_native_token(&env)
+    }
+
+    /// Set the share of a trader's reward, in basis points, granted to their referrer (admin only)
+    pub fn set_referral_bps(env: Env, admin: Address, bps: u32) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        if bps > BPS_DENOMINATOR {
+            return Err(TradingError::InvalidFeeBps);
+        }
+
+        TradingStorage::set_referral_bps(&env, bps);
+
+        Ok(())
+    }
+
+    /// Get the referral reward share, in basis points
+    pub fn referral_bps(env: Env) -> u32 {
+        TradingStorage::get_referral_bps(&env)
+    }
+
+    /// Set fee discount tiers as `(min_reward_balance, discount_bps)` pairs (admin only)
+    ///
+    /// A trader gets the largest discount whose threshold their reward balance meets.
+    pub fn set_discount_tiers(env: Env, admin: Address, tiers: Vec<(i128, u32)>) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        if tiers.len() > MAX_DISCOUNT_TIERS {
+            return Err(TradingError::BatchSizeExceeded);
+        }
+
+        for (min_balance, discount_bps) in tiers.iter() {
+            if min_balance < 0 {
+                return Err(TradingError::InvalidAmount);
+            }
+            if discount_bps > BPS_DENOMINATOR {
+                return Err(TradingError::InvalidFeeBps);
+            }
+        }
+
+        TradingStorage::set_discount_tiers(&env, &tiers);
+
+        Ok(())
+    }
+
+    /// Get the fee discount tiers
+    pub fn discount_tiers(env: Env) -> Vec<(i128, u32)> {
+        TradingStorage::get_discount_tiers(&env)
+    }
+
+    /// Set the lifetime fee volumes at which traders reach Bronze, Silver and
+    /// Gold (admin only)
+    ///
+    /// Up to `MAX_TIER_THRESHOLDS` positive, strictly ascending values; fewer
+    /// leave the higher tiers unreachable and an empty list turns tiers off.
+    /// Volume is summed across fee tokens in their smallest units. Tiers are
+    /// recomputed on each trader's next trade.
+    pub fn set_tier_thresholds(env: Env, admin: Address, thresholds: Vec<i128>) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        if thresholds.len() > MAX_TIER_THRESHOLDS {
+            return Err(TradingError::BatchSizeExceeded);
+        }
+
+        let mut previous = 0;
+        for threshold in thresholds.iter() {
+            if threshold <= previous {
+                return Err(TradingError::InvalidAmount);
+            }
+            previous = threshold;
+        }
+
+        TradingStorage::set_tier_thresholds(&env, &thresholds);
+
+        Ok(())
+    }
+
+    /// Get the tier thresholds
+    pub fn tier_thresholds(env: Env) -> Vec<i128> {
+        TradingStorage::get_tier_thresholds(&env)
+    }
+
+    /// Get the tier `trader` reached as of their last trade
+    pub fn tier(env: Env, trader: Address) -> Tier {
+        TradingStorage::get_trader_tier(&env, &trader)
+    }
+
+    /// Get the lifetime fees `trader` has paid across all fee tokens
+    pub fn trader_fee_volume(env: Env, trader: Address) -> i128 {
+        TradingStorage::get_trader_fee_volume(&env, &trader)
+    }
+
+    /// Set the allowlisted reward contract whose `reward_balance` drives fee discounts,
+    /// or `None` to turn discounts off (admin only)
+    pub fn set_discount_source(env: Env, admin: Address, reward_id: Option<Address>) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        if let Some(reward_id) = &reward_id {
+            if !TradingStorage::is_reward_contract_allowed(&env, reward_id) {
+                return Err(TradingError::RewardContractNotAllowed);
+            }
+        }
+
+        TradingStorage::set_discount_source(&env, &reward_id);
+
+        Ok(())
+    }
+
+    /// Set or clear the contract queried for the live fee rate (admin only)
+    ///
+    /// While set, bps-based trades ask the oracle's `fee_bps(fee_token)` for
+    /// the rate and fall back to `token_fee_bps` if the call fails or returns
+    /// more than 10_000.
+    pub fn set_fee_oracle(env: Env, admin: Address, oracle: Option<Address>) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        TradingStorage::set_fee_oracle(&env, &oracle);
+
+        Ok(())
+    }
+
+    /// Get the fee oracle, if any
+    pub fn fee_oracle(env: Env) -> Option<Address> {
+        TradingStorage::get_fee_oracle(&env)
+    }
+
+    /// Set where single-trade fees go when the fee token rejects the transfer
+    /// to the trade's recipient, or `None` to fail such trades (admin only)
+    pub fn set_fallback_recipient(env: Env, admin: Address, recipient: Option<Address>) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        TradingStorage::set_fallback_recipient(&env, &recipient);
+
+        Ok(())
+    }
+
+    /// Get the fallback fee recipient, if any
+    pub fn fallback_recipient(env: Env) -> Option<Address> {
+        TradingStorage::get_fallback_recipient(&env)
+    }
+
+    /// Get the reward contract used for fee discounts, if any
+    pub fn discount_source(env: Env) -> Option<Address> {
+        TradingStorage::get_discount_source(&env)
+    }
+
+    /// Allow a reward contract to be used by trade_and_reward (admin only, timelocked)
+    ///
+    /// The candidate must export `reward_version() -> u32` reporting at least
+    /// `min_reward_version`; otherwise this fails with `RewardIfaceMismatch`.
+    /// Fails with `AllowlistFull` once `MAX_REWARD_CONTRACTS` are allowlisted.
+    pub fn add_reward_contract(env: Env, admin: Address, reward_id: Address) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        if !TradingStorage::is_reward_contract_allowed(&env, &reward_id)
+            && TradingStorage::get_reward_contract_count(&env) >= MAX_REWARD_CONTRACTS
+        {
+            return Err(TradingError::AllowlistFull);
+        }
+
+        Self::check_reward_version(&env, &reward_id)?;
+
+        Self::schedule_change(&env, ParamChange::AllowRewardContract(reward_id));
+        Self::log_admin_action(&env, &admin, "add_reward_contract", 0);
+
+        Ok(())
+    }
+
+    /// Set the lowest `reward_version` add_reward_contract accepts (admin only)
+    ///
+    /// Already allowlisted contracts are not re-checked.
+    pub fn set_min_reward_version(env: Env, admin: Address, version: u32) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        TradingStorage::set_min_reward_version(&env, version);
+
+        Ok(())
+    }
+
+    /// Get the lowest reward interface version accepted for new reward contracts
+    pub fn min_reward_version(env: Env) -> u32 {
+        TradingStorage::get_min_reward_version(&env)
+    }
+
+    /// Remove a reward contract from the allowlist (admin only, takes effect immediately)
+    pub fn remove_reward_contract(env: Env, admin: Address, reward_id: Address) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        TradingStorage::disallow_reward_contract(&env, &reward_id);
+        Self::log_admin_action(&env, &admin, "remove_reward_contract", 0);
+
+        Ok(())
+    }
+
+    /// Check whether a reward contract is on the allowlist
+    pub fn is_reward_contract_allowed(env: Env, reward_id: Address) -> bool {
+        TradingStorage::is_reward_contract_allowed(&env, &reward_id)
+    }
+
+    /// Auto-remove a reward contract from the allowlist after `max`
+    /// consecutive failed `add_reward` calls; 0 disables this (admin only)
+    ///
+    /// Only failures that commit count: a failed plain `trade_and_reward`
+    /// reverts entirely, counter included, unless rewards are best-effort,
+    /// while `trade_and_reward_refundable` settles the trade and records the
+    /// failure. Any successful reward resets the count.
+    pub fn set_max_reward_failures(env: Env, admin: Address, max: u32) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        TradingStorage::set_max_reward_failures(&env, max);
+
+        Ok(())
+    }
+
+    /// Require `trade_and_reward` rewards to be at least `bps` of the fee
+    /// charged, after any reward multiplier (admin only)
+    ///
+    /// Smaller rewards fail with `RewardTooSmall` before any cross-call. `bps`
+    /// may not exceed 10_000; 0 disables the check.
+    pub fn set_min_reward_ratio(env: Env, admin: Address, bps: u32) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        if bps > BPS_DENOMINATOR {
+            return Err(TradingError::InvalidFeeBps);
+        }
+
+        TradingStorage::set_min_reward_ratio(&env, bps);
+
+        Ok(())
+    }
+
+    /// Get the minimum reward as basis points of the fee (0 = no minimum)
+    pub fn min_reward_ratio(env: Env) -> u32 {
+        TradingStorage::get_min_reward_ratio(&env)
+    }
+
+    /// Choose whether a failed reward call reverts `trade_and_reward` (admin only)
+    ///
+    /// When `false`, rewards are best-effort: the trade and fee still commit,
+    /// the receipt reports no reward, and a `("reward_skipped", user)` event
+    /// carries the reward contract and error code. Defaults to `true`.
+    pub fn set_reward_required(env: Env, admin: Address, required: bool) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        TradingStorage::set_reward_required(&env, required);
+
+        Ok(())
+    }
+
+    /// Whether a failed reward call reverts `trade_and_reward`
+    pub fn reward_required(env: Env) -> bool {
+        TradingStorage::is_reward_required(&env)
+    }
+
+    /// Get the consecutive failures that auto-disable a reward contract
+    pub fn max_reward_failures(env: Env) -> u32 {
+        TradingStorage::get_max_reward_failures(&env)
+    }
+
+    /// Get a reward contract's current run of committed `add_reward` failures
+    pub fn reward_failure_count(env: Env, reward_id: Address) -> u32 {
+        TradingStorage::get_reward_failures(&env, &reward_id)
+    }
+
+    /// Set the allowlisted reward contract `trade_and_reward_default` credits,
+    /// or `None` to clear it (admin only)
+    pub fn set_default_reward_contract(env: Env, admin: Address, reward_id: Option<Address>) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        if let Some(reward_id) = &reward_id {
+            if !TradingStorage::is_reward_contract_allowed(&env, reward_id) {
+                return Err(TradingError::RewardContractNotAllowed);
+            }
+        }
+
+        TradingStorage::set_default_reward_contract(&env, &reward_id);
+
+        Ok(())
+    }
+
+    /// Get the default reward contract, if any
+    pub fn default_reward_contract(env: Env) -> Option<Address> {
+        TradingStorage::get_default_reward_contract(&env)
+    }
+
+    /// Get the number of allowlisted reward contracts
+    pub fn reward_contract_count(env: Env) -> u32 {
+        TradingStorage::get_reward_contract_count(&env)
+    }
+
+    /// List the allowlisted reward contracts in the order they were added
+    ///
+    /// Bounded by `MAX_REWARD_CONTRACTS`.
+    pub fn list_reward_contracts(env: Env) -> Vec<Address> {
+        TradingStorage::get_reward_contract_list(&env)
+    }
+
+    /// Set how many ledgers fee and allowlist changes wait before taking effect (admin only)
+    ///
+    /// With a delay of 0 changes apply immediately. Otherwise the new delay is
+    /// itself queued behind the current one.
+    pub fn set_timelock_delay(env: Env, admin: Address, delay: u32) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        Self::schedule_change(&env, ParamChange::TimelockDelay(delay));
+
+        Ok(())
+    }
+
+    /// Get the parameter change timelock, in ledgers
+    pub fn timelock_delay(env: Env) -> u32 {
+        TradingStorage::get_timelock_delay(&env)
+    }
+
+    /// Get the queued parameter change, if any
+    pub fn pending_change(env: Env) -> Option<PendingParamChange> {
+        TradingStorage::get_pending_change(&env)
+    }
+
+    /// Apply the queued parameter change once its timelock has elapsed (callable by anyone)
+    pub fn execute_pending_change(env: Env) -> Result<ParamChange, TradingError> {
+        Self::require_not_terminated(&env)?;
+
+        let pending = TradingStorage::get_pending_change(&env)
+            .ok_or(TradingError::NoPendingChange)?;
+
+        if env.ledger().sequence() < pending.execute_after {
+            return Err(TradingError::TimelockNotElapsed);
+        }
+
+        TradingStorage::clear_pending_change(&env);
+        Self::apply_change(&env, &pending.change);
+
+        Ok(pending.change)
+    }
+
+    /// Helper: Apply `change` now, or queue it if a timelock delay is configured
+    ///
+    /// A newly queued change replaces any change already pending.
+    fn schedule_change(env: &Env, change: ParamChange) {
+        let delay = TradingStorage::get_timelock_delay(env);
+        if delay == 0 {
+            Self::apply_change(env, &change);
+            return;
+        }
+
+        let pending = PendingParamChange {
+            change,
+            execute_after: env.ledger().sequence().saturating_add(delay),
+        };
+        TradingStorage::set_pending_change(env, &pending);
+
+        env.events().publish((symbol_short!("chg_queue"),), pending);
+    }
+
+    /// Helper: Write a parameter change to storage
+    fn apply_change(env: &Env, change: &ParamChange) {
+        match change {
+            ParamChange::FeeBps(bps) => TradingStorage::set_fee_bps(env, *bps),
+            ParamChange::FeeBounds(min_fee, max_fee) => {
+                TradingStorage::set_fee_bounds(env, *min_fee, *max_fee)
+            }
+            ParamChange::AllowFeeToken(token) => TradingStorage::allow_fee_token(env, token),
+            ParamChange::AllowRewardContract(reward_id) => {
+                TradingStorage::allow_reward_contract(env, reward_id)
+            }
+            ParamChange::TimelockDelay(delay) => TradingStorage::set_timelock_delay(env, *delay),
+            ParamChange::TokenFeeBps(token, bps) => TradingStorage::set_token_fee_bps(env, token, *bps),
+            ParamChange::MinNonzeroFee(fee) => TradingStorage::set_min_nonzero_fee(env, *fee),
+            ParamChange::MinFeeMode(mode) => TradingStorage::set_min_fee_mode(env, *mode),
+            ParamChange::BurnBps(bps) => TradingStorage::set_burn_bps(env, *bps),
+            ParamChange::UnpauseDelay(delay) => TradingStorage::set_unpause_delay(env, *delay),
+            ParamChange::RebateBps(bps) => TradingStorage::set_rebate_bps(env, *bps),
+            ParamChange::NativeToken(token) => {
+                TradingStorage::set_native_token(env, token);
+                TradingStorage::allow_fee_token(env, token);
+            }
+        }
+    }
+
+    /// Execute a trade and credit a reward on `reward_id` in one atomic call
+    ///
+    /// `reward_id` must be on the reward contract allowlist. The fee is charged as in `trade`, then `add_reward(trader, reward_amount)` is
+    /// invoked on the reward contract through `safe_invoke`. If the reward call fails
+    /// the whole invocation, including the fee transfer, is rolled back, unless
+    /// `set_reward_required(false)` made rewards best-effort.
+    ///
+    /// If `referrer` is set and differs from the trader, a second `add_reward`
+    /// call credits them `referral_bps` of `reward_amount`; it is atomic with
+    /// the fee and the trader's reward.
+    ///
+    /// Returns a `TradeReceipt` describing the fee paid and rewards credited.
+    #[allow(clippy::too_many_arguments)]
+    pub fn trade_and_reward(
+        env: Env,
+        trader: Address,
+        pair: Symbol,
+        amount: i128,
+        price: i128,
+        is_buy: bool,
+        fee_token: Address,
+        fee_recipient: Address,
+        reward_id: Address,
+        reward_amount: i128,
+        referrer: Option<Address>,
+    ) -> Result<TradeReceipt, TradingError> {
+        Self::check_not_reentrant(&env)?;
+        TradingStorage::set_locked(&env, true);
+
+        let result = Self::execute_trade_and_reward(
+            &env,
+            trader,
+            pair,
+            amount,
+            price,
+            is_buy,
+            fee_token,
+            fee_recipient,
+            reward_id,
+            reward_amount,
+            referrer,
+            false,
+            &mut None,
+        );
+
+        // Release the lock on every path so a failed call cannot wedge the contract
+        TradingStorage::set_locked(&env, false);
+
+        result
+    }
+
+    /// Like `trade_and_reward`, but a failed reward call can report the
+    /// reward contract's own error instead of `RewardFailed`
+    ///
+    /// With `set_verbose_errors(true)` the raw error from the failing
+    /// `add_reward` call is returned as is, e.g. the reward contract's
+    /// contract error code. Otherwise, and for every other failure, the
+    /// `TradingError` code is returned exactly as `trade_and_reward` would.
+    /// The rollback is the same in both modes.
+    #[allow(clippy::too_many_arguments)]
+    pub fn trade_and_reward_verbose(
+        env: Env,
+        trader: Address,
+        pair: Symbol,
+        amount: i128,
+        price: i128,
+        is_buy: bool,
+        fee_token: Address,
+        fee_recipient: Address,
+        reward_id: Address,
+        reward_amount: i128,
+        referrer: Option<Address>,
+    ) -> Result<TradeReceipt, Error> {
+        Self::check_not_reentrant(&env)?;
+        TradingStorage::set_locked(&env, true);
+
+        let mut callee_error = None;
+        let result = Self::execute_trade_and_reward(
+            &env,
+            trader,
+            pair,
+            amount,
+            price,
+            is_buy,
+            fee_token,
+            fee_recipient,
+            reward_id,
+            reward_amount,
+            referrer,
+            false,
+            &mut callee_error,
+        );
+
+        TradingStorage::set_locked(&env, false);
+
+        result.map_err(|error| match callee_error {
+            Some(raw) if TradingStorage::is_verbose_errors(&env) => raw,
+            _ => error.into(),
+        })
+    }
+
+    /// Choose whether `trade_and_reward_verbose` exposes raw reward contract
+    /// errors (admin only)
+    ///
+    /// Defaults to `false`, keeping callee internals out of production error codes.
+    pub fn set_verbose_errors(env: Env, admin: Address, verbose: bool) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        TradingStorage::set_verbose_errors(&env, verbose);
+
+        Ok(())
+    }
+
+    /// Check whether raw reward contract errors are exposed
+    pub fn verbose_errors(env: Env) -> bool {
+        TradingStorage::is_verbose_errors(&env)
+    }
+
+    /// Like `trade_and_reward`, but credits the default reward contract
+    ///
+    /// Fails with `NoDefaultRewardContract` if none is set. The default must
+    /// still be on the allowlist when the trade executes.
+    #[allow(clippy::too_many_arguments)]
+    pub fn trade_and_reward_default(
+        env: Env,
+        trader: Address,
+        pair: Symbol,
+        amount: i128,
+        price: i128,
+        is_buy: bool,
+        fee_token: Address,
+        fee_recipient: Address,
+        reward_amount: i128,
+        referrer: Option<Address>,
+    ) -> Result<TradeReceipt, TradingError> {
+        let reward_id = TradingStorage::get_default_reward_contract(&env)
+            .ok_or(TradingError::NoDefaultRewardContract)?;
+
+        Self::trade_and_reward(
+            env,
+            trader,
+            pair,
+            amount,
+            price,
+            is_buy,
+            fee_token,
+            fee_recipient,
+            reward_id,
+            reward_amount,
+            referrer,
+        )
+    }
+
+    /// Like `trade_and_reward`, but a failed reward call refunds the fee instead of reverting
+    ///
+    /// `fee_recipient` must co-sign the call so it can return the fee. The
+    /// trade is still recorded. On reward failure the fee is transferred back
+    /// from `fee_recipient` to the trader, a `("refund", trader)` event is
+    /// emitted, and the receipt reports a zero fee and zero reward. A fee
+    /// rerouted to the fallback recipient is refunded from there, with the
+    /// fallback's auth. The burned share is not refunded; the refunded part
+    /// comes off the fee and tier volume and the daily limit, and the trader's
+    /// cooldown goes back to where it was before the trade.
+    #[allow(clippy::too_many_arguments)]
+    pub fn trade_and_reward_refundable(
+        env: Env,
+        trader: Address,
+        pair: Symbol,
+        amount: i128,
+        price: i128,
+        is_buy: bool,
+        fee_token: Address,
+        fee_recipient: Address,
+        reward_id: Address,
+        reward_amount: i128,
+    ) -> Result<TradeReceipt, TradingError> {
+        Self::check_not_reentrant(&env)?;
+        fee_recipient.require_auth();
+        TradingStorage::set_locked(&env, true);
+
+        let result = Self::execute_trade_and_reward(
+            &env,
+            trader,
+            pair,
+            amount,
+            price,
+            is_buy,
+            fee_token,
+            fee_recipient,
+            reward_id,
+            reward_amount,
+            None,
+            true,
+            &mut None,
+        );
+
+        TradingStorage::set_locked(&env, false);
+
+        result
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn execute_trade_and_reward(
+        env: &Env,
+        trader: Address,
+        pair: Symbol,
+        amount: i128,
+        price: i128,
+        is_buy: bool,
+        fee_token: Address,
+        fee_recipient: Address,
+        reward_id: Address,
+        reward_amount: i128,
+        referrer: Option<Address>,
+        refundable: bool,
+        callee_error: &mut Option<Error>,
+    ) -> Result<TradeReceipt, TradingError> {
+        Self::require_not_paused(env, PAUSE_TRADE | PAUSE_REWARD)?;
+
+        if !TradingStorage::is_reward_contract_allowed(env, &reward_id) {
+            return Err(TradingError::RewardContractNotAllowed);
+        }
+
+        let fee_amount = Self::compute_trade_fee(env, amount, &fee_token, Some(&trader))?;
+
+        // The reward contract validates the scaled amount against its own rules and caps
+        let reward_amount = Self::boosted_reward(env, reward_amount)?;
+        let min_reward = FeeManager::calculate_bps_fee(fee_amount, TradingStorage::get_min_reward_ratio(env))?;
+        if reward_amount < min_reward {
+            return Err(TradingError::RewardTooSmall);
+        }
+
+        trader.require_auth();
+        // A refund hands back the cooldown the trade starts
+        let last_trade_at = if refundable { TradingStorage::get_last_trade_at(env, &trader) } else { None };
+        let settlement = Self::settle_trade(
+            env,
+            None,
+            trader.clone(),
+            pair,
+            amount,
+            price,
+            is_buy,
+            fee_token.clone(),
+            fee_amount,
+            fee_recipient.clone(),
+            fee_token.clone(),
+            None,
+        )?;
+
+        // The circuit breaker tripped: nothing was traded, so nothing is rewarded
+        let Settlement::Settled { trade_id, fee_holder } = settlement else {
+            return Ok(TradeReceipt {
+                trade_id: BREAKER_TRIPPED,
+                fee_paid: 0,
+                reward_amount: 0,
+                referral_amount: 0,
+                reward_contract: reward_id,
+                reward_total: None,
+                ledger: env.ledger().sequence(),
+                cross_calls: 0,
+            });
+        };
+
+        // Reward contracts that return nothing decode as `None`
+        let args = vec![env, trader.clone().into_val(env), reward_amount.into_val(env)];
+        let mut cross_calls = 1;
+        let reward_result =
+            safe_invoke_detailed::<Option<i128>>(env, &reward_id, &Symbol::new(env, "add_reward"), args);
+        let reward_total = match reward_result {
+            Ok(total) => {
+                if TradingStorage::get_reward_failures(env, &reward_id) != 0 {
+                    TradingStorage::set_reward_failures(env, &reward_id, 0);
+                }
+                total
+            }
+            Err((code, raw)) if !refundable => {
+                if TradingStorage::is_reward_required(env) {
+                    *callee_error = Some(raw);
+                    return Err(Self::reward_error(code));
+                }
+                Self::record_reward_failure(env, &reward_id);
+                Self::emit_reward_skipped(env, &trader, &reward_id, code);
+
+                return Ok(TradeReceipt {
+                    trade_id,
+                    fee_paid: fee_amount,
+                    reward_amount: 0,
+                    referral_amount: 0,
+                    reward_contract: reward_id,
+                    reward_total: None,
+                    ledger: env.ledger().sequence(),
+                    cross_calls,
+                });
+            }
+            Err(_) => {
+                Self::record_reward_failure(env, &reward_id);
+
+                // Settle the trade but hand the fee back to the trader; a
+                // burned share is gone and cannot be refunded. The fee is
+                // taken back from whoever was actually paid it
+                if fee_holder != fee_recipient && fee_holder != env.current_contract_address() {
+                    fee_holder.require_auth();
+                }
+                let (_, rebate, routed) = Self::split_fee(env, fee_amount)?;
+                FeeManager::collect_fee(env, &fee_token, &fee_holder, &trader, routed)?;
+                if routed > 0 {
+                    cross_calls += 1;
+                }
+                if rebate > 0 {
+                    FeeManager::collect_fee(env, &fee_token, &env.current_contract_address(), &trader, rebate)?;
+                    cross_calls += 1;
+                    TradingStorage::add_rebate_pool(env, &fee_token, -rebate)?;
+                    TradingStorage::add_rebate_volume(env, &trader, &fee_token, -fee_amount)?;
+                }
+                let refunded = routed + rebate;
+                TradingStorage::add_fee_volume(env, &fee_token, -refunded)?;
+                Self::record_tier_volume(env, &trader, -refunded)?;
+                Self::release_trade_limits(env, &trader, refunded, last_trade_at);
+                TradingStorage::add_received(env, &fee_holder, -routed, -1)?;
+                if fee_holder == env.current_contract_address() {
+                    TradingStorage::add_accrued_fees(env, &fee_token, -routed)?;
+                }
+
+                env.events().publish(
+                    (topics::FEE_REFUNDED, trader),
+                    (fee_token, routed),
+                );
+
+                return Ok(TradeReceipt {
+                    trade_id,
+                    fee_paid: 0,
+                    reward_amount: 0,
+                    referral_amount: 0,
+                    reward_contract: reward_id,
+                    reward_total: None,
+                    ledger: env.ledger().sequence(),
+                    cross_calls,
+                });
+            }
+        };
+
+        EventEmitter::trade_reward(env, TradeRewardEvent {
+            trade_id,
+            user: trader.clone(),
+            reward_contract: reward_id.clone(),
+            amount: reward_amount,
+            timestamp: env.ledger().timestamp(),
+        });
+
+        let mut referral_amount = 0;
+        if let Some(referrer) = referrer.filter(|referrer| *referrer != trader) {
+            referral_amount = FeeManager::calculate_bps_fee(reward_amount, TradingStorage::get_referral_bps(env))?;
+            if referral_amount > 0 {
+                let args = vec![env, referrer.clone().into_val(env), referral_amount.into_val(env)];
+                cross_calls += 1;
+                match safe_invoke_detailed::<soroban_sdk::Val>(env, &reward_id, &Symbol::new(env, "add_reward"), args) {
+                    Ok(_) => EventEmitter::trade_reward(env, TradeRewardEvent {
+                        trade_id,
+                        user: referrer,
+                        reward_contract: reward_id.clone(),
+                        amount: referral_amount,
+                        timestamp: env.ledger().timestamp(),
+                    }),
+                    Err((code, _)) if !refundable && !TradingStorage::is_reward_required(env) => {
+                        Self::emit_reward_skipped(env, &referrer, &reward_id, code);
+                        referral_amount = 0;
+                    }
+                    Err((code, raw)) => {
+                        *callee_error = Some(raw);
+                        return Err(Self::reward_error(code));
+                    }
+                }
+            }
+        }
+
+        Ok(TradeReceipt {
+            trade_id,
+            fee_paid: fee_amount,
+            reward_amount,
+            referral_amount,
+            reward_contract: reward_id,
+            reward_total,
+            ledger: env.ledger().sequence(),
+            cross_calls,
+        })
+    }
+
+    /// Helper: Report a best-effort reward call that failed for `user`
+    fn emit_reward_skipped(env: &Env, user: &Address, reward_id: &Address, code: u32) {
+        env.events().publish(
+            (Symbol::new(env, "reward_skipped"), user.clone()),
+            (reward_id.clone(), Self::reward_error(code) as u32),
+        );
+    }
+
+    /// Collect the fee and record a single trade
+    ///
+    /// The fee is debited from `sponsor` if one is given, otherwise from
+    /// `trader`; the trade itself is always attributed to `trader`.
+    #[allow(clippy::too_many_arguments)]
+    fn execute_trade(
+        env: &Env,
+        sponsor: Option<Address>,
+        trader: Address,
+        pair: Symbol,
+        amount: i128,
+        price: i128,
+        is_buy: bool,
+        fee_token: Address,
+        fee_amount: i128,
+        fee_recipient: Address,
+        settle_token: Address,
+        tag: Option<Symbol>,
+    ) -> Result<Settlement, TradingError> {
+        trader.require_auth();
+        Self::settle_trade(
+            env,
+            sponsor,
+            trader,
+            pair,
+            amount,
+            price,
+            is_buy,
+            fee_token,
+            fee_amount,
+            fee_recipient,
+            settle_token,
+            tag,
+        )
+    }
+
+    /// Like `execute_trade`, but without the trader's auth, for callers that
+    /// authorized the trade some other way
+    #[allow(clippy::too_many_arguments)]
+    fn settle_trade(
+        env: &Env,
+        sponsor: Option<Address>,
+        trader: Address,
+        pair: Symbol,
+        amount: i128,
+        price: i128,
+        is_buy: bool,
+        fee_token: Address,
+        fee_amount: i128,
+        fee_recipient: Address,
+        settle_token: Address,
+        tag: Option<Symbol>,
+    ) -> Result<Settlement, TradingError> {
+        Self::extend_instance_ttl(env);
+        let payer = match sponsor {
+            Some(sponsor) => {
+                // A contract is only implicitly authorized as the direct invoker,
+                // so fees it already holds are paid on without a self-auth
+                if sponsor != env.current_contract_address() {
+                    sponsor.require_auth();
+                }
+                sponsor
+            }
+            None => trader.clone(),
+        };
+
+        Self::require_not_paused(env, PAUSE_TRADE)?;
+        Self::require_token_not_paused(env, &fee_token)?;
+        Self::require_token_not_paused(env, &settle_token)?;
+
+        // Only call into vetted token contracts
+        if !TradingStorage::is_fee_token_allowed(env, &fee_token)
+            || !TradingStorage::is_fee_token_allowed(env, &settle_token)
+        {
+            return Err(TradingError::TokenNotAllowed);
+        }
+
+        if amount < TradingStorage::get_min_trade_amount(env) {
+            return Err(TradingError::BelowMinTrade);
+        }
+
+        // A tripped breaker must commit the pause, so the trade is skipped rather than reverted
+        if Self::trip_circuit_breaker(env, fee_amount) {
+            return Ok(Settlement::BreakerTripped);
+        }
+
+        Self::consume_trade_limits(env, &trader, &fee_recipient, fee_amount)?;
+
+        // Fees pulled in up front were checked against the trader before the pull
+        if payer != env.current_contract_address() {
+            Self::check_fee_share(env, &fee_token, &payer, fee_amount)?;
+        }
+
+        // Collect fee first
+        let (fee_recipient, routed) = Self::pay_fee(env, &trader, &fee_token, &payer, fee_recipient, fee_amount)?;
+
+        let timestamp = env.ledger().timestamp();
+        Self::emit_fee_collected(env, FeeCollectedEvent {
+            payer,
+            recipient: fee_recipient.clone(),
+            amount: routed,
+            token: fee_token.clone(),
+            timestamp,
+        });
+
+        // Create trade record with optimized storage
+        let trade_id = TradingStorage::increment_trade_stats(env, amount)?;
+        let trade = OptimizedTrade {
+            id: trade_id,
+            trader: trader.clone(),
+            pair: pair.clone(),
+            amount,
+            price,
+            timestamp,
+            is_buy,
+        };
+
+        // Store trade with optimized individual key
+        TradingStorage::set_trade(env, &trade);
+        TradingStorage::add_settle_volume(env, &settle_token, amount)?;
+        if let Some(tag) = &tag {
+            TradingStorage::add_tag_volume(env, tag, fee_amount)?;
+        }
+        TradingStorage::push_recent_trade(env, &TradeRecord {
+            trade_id,
+            trader: trader.clone(),
+            pair: pair.clone(),
+            amount,
+            price,
+            is_buy,
+            fee_token: fee_token.clone(),
+            fee_paid: fee_amount,
+            settle_token: settle_token.clone(),
+            ledger: env.ledger().sequence(),
+            timestamp,
+        });
+
+        Self::emit_trade_executed(env, TradeExecutedEvent {
+            trade_id,
+            trader,
+            pair,
+            amount,
+            price,
+            is_buy,
+            fee_amount,
+            fee_token,
+            settle_token,
+            tag: tag.unwrap_or_else(|| Symbol::new(env, "")),
+            timestamp,
+        });
+
+        Ok(Settlement::Settled { trade_id, fee_holder: fee_recipient })
+    }
+
+    /// Helper: Pull the fee from `trader`'s allowance and settle with the contract as sponsor
+    #[allow(clippy::too_many_arguments)]
+    fn settle_from_allowance(
+        env: &Env,
+        trader: Address,
+        pair: Symbol,
+        amount: i128,
+        price: i128,
+        is_buy: bool,
+        fee_token: Address,
+        fee_recipient: Address,
+    ) -> Result<u64, TradingError> {
+        Self::require_not_paused(env, PAUSE_TRADE)?;
+        Self::require_token_not_paused(env, &fee_token)?;
+
+        // Only call into vetted token contracts
+        if !TradingStorage::is_fee_token_allowed(env, &fee_token) {
+            return Err(TradingError::TokenNotAllowed);
+        }
+
+        let fee_amount = Self::compute_trade_fee(env, amount, &fee_token, Some(&trader))?;
+        Self::check_fee_share(env, &fee_token, &trader, fee_amount)?;
+        let contract_address = env.current_contract_address();
+        if fee_amount > 0 {
+            let token_client = token::Client::new(env, &fee_token);
+            // Report a missing or revoked allowance instead of a token trap
+            if token_client.allowance(&trader, &contract_address) < fee_amount {
+                return Err(TradingError::InsufficientBalance);
+            }
+            token_client.transfer_from(&contract_address, &trader, &contract_address, &fee_amount);
+        }
+
+        // The contract now holds the fee, so it pays it on as the sponsor
+        let settlement = Self::settle_trade(
+            env,
+            Some(contract_address.clone()),
+            trader.clone(),
+            pair,
+            amount,
+            price,
+            is_buy,
+            fee_token.clone(),
+            fee_amount,
+            fee_recipient,
+            fee_token.clone(),
+            None,
+        )?;
+
+        // A tripped circuit breaker skips the trade; hand the pulled fee back
+        if matches!(settlement, Settlement::BreakerTripped) && fee_amount > 0 {
+            token::Client::new(env, &fee_token).transfer(&contract_address, &trader, &fee_amount);
+        }
+
+        Ok(settlement.trade_id())
+    }
+
+    /// Helper: Pay `trader`'s `fee_amount` fee out of `payer`, burning and
+    /// pooling the configured shares and recording it in the fee totals
+    ///
+    /// Returns the address paid, which follows the fee sink and any fallback
+    /// reroute, and the share routed to it.
+    fn pay_fee(
+        env: &Env,
+        trader: &Address,
+        fee_token: &Address,
+        payer: &Address,
+        fee_recipient: Address,
+        fee_amount: i128,
+    ) -> Result<(Address, i128), TradingError> {
+        let fee_recipient = Self::fee_destination(env, fee_recipient);
+        let (burned, rebate, routed) = Self::split_fee(env, fee_amount)?;
+        let fee_recipient = Self::route_fee(env, fee_token, payer, fee_recipient, routed)?;
+        FeeManager::burn_fee(env, fee_token, payer, burned)?;
+        if rebate > 0 {
+            Self::collect_exact(env, fee_token, payer, &env.current_contract_address(), rebate)?;
+            TradingStorage::add_rebate_pool(env, fee_token, rebate)?;
+            TradingStorage::add_rebate_volume(env, trader, fee_token, fee_amount)?;
+        }
+        Self::record_fee_paid(env, trader, fee_token, fee_amount, &fee_recipient, routed)?;
+        Ok((fee_recipient, routed))
+    }
+
+    /// Helper: Pay `amount` to `recipient`, or to the fallback recipient if
+    /// the token rejects that transfer, returning the address paid
+    ///
+    /// A reroute emits `("fee_rerouted", recipient)` with `(fallback, amount)`.
+    /// Without a fallback a rejected transfer fails the trade.
+    fn route_fee(
+        env: &Env,
+        token: &Address,
+        payer: &Address,
+        recipient: Address,
+        amount: i128,
+    ) -> Result<Address, TradingError> {
+        let fallback = match TradingStorage::get_fallback_recipient(env) {
+            Some(fallback) if amount > 0 && fallback != recipient && *payer != recipient => fallback,
+            _ => {
+                Self::collect_exact(env, token, payer, &recipient, amount)?;
+                return Ok(recipient);
+            }
+        };
+
+        // A payer who cannot cover the fee is not the recipient's fault
+        let token_client = token::Client::new(env, token);
+        if token_client.balance(payer) < amount {
+            return Err(TradingError::InsufficientBalance);
+        }
+
+        let before = token_client.balance(&recipient);
+        let args = vec![env, payer.into_val(env), recipient.into_val(env), amount.into_val(env)];
+        if safe_invoke(env, token, &symbol_short!("transfer"), args).is_ok() {
+            if token_client.balance(&recipient) - before != amount {
+                return Err(TradingError::TransferAmountMismatch);
+            }
+            return Ok(recipient);
+        }
+
+        Self::collect_exact(env, token, payer, &fallback, amount)?;
+        env.events().publish((Symbol::new(env, "fee_rerouted"), recipient), (fallback.clone(), amount));
+        Ok(fallback)
+    }
+
+    /// Helper: Collect a fee and check `destination` received exactly `amount`
+    ///
+    /// Fee-on-transfer and rebasing tokens can accept a transfer while
+    /// moving a different amount; those fail with `TransferAmountMismatch`.
+    fn collect_exact(
+        env: &Env,
+        token: &Address,
+        payer: &Address,
+        destination: &Address,
+        amount: i128,
+    ) -> Result<(), TradingError> {
+        // A transfer to oneself leaves the balance unchanged by design
+        if payer == destination || amount == 0 {
+            return FeeManager::collect_fee(env, token, payer, destination, amount).map_err(TradingError::from);
+        }
+
+        let token_client = token::Client::new(env, token);
+        let before = token_client.balance(destination);
+        FeeManager::collect_fee(env, token, payer, destination, amount)?;
+        if token_client.balance(destination) - before != amount {
+            return Err(TradingError::TransferAmountMismatch);
+        }
+        Ok(())
+    }
+
+    /// Helper: Emit a fee collected event as allowed by the event mode
+    fn emit_fee_collected(env: &Env, event: FeeCollectedEvent) {
+        match TradingStorage::get_event_mode(env) {
+            EventMode::Full => EventEmitter::fee_collected(env, event),
+            EventMode::Minimal => env.events().publish((topics::FEE_COLLECTED,), ()),
+            EventMode::Off => {}
+        }
+    }
+
+    /// Helper: Emit a trade executed event as allowed by the event mode
+    fn emit_trade_executed(env: &Env, event: TradeExecutedEvent) {
+        match TradingStorage::get_event_mode(env) {
+            EventMode::Full => EventEmitter::trade_executed(env, event),
+            EventMode::Minimal => env.events().publish((topics::TRADE_EXECUTED,), ()),
+            EventMode::Off => {}
+        }
+    }
+
+    /// Helper: Address single-trade fees are paid to under the current fee sink mode
+    fn fee_destination(env: &Env, fee_recipient: Address) -> Address {
+        match TradingStorage::get_fee_sink(env) {
+            FeeSinkMode::Direct => fee_recipient,
+            FeeSinkMode::Accrue => env.current_contract_address(),
+        }
+    }
+
+    /// Execute multiple trades in a single transaction
+    pub fn batch_trade(
+        env: Env,
+        requests: soroban_sdk::Vec<BatchTradeRequest>,
+    ) -> Result<BatchTradeOperation, TradingError> {
+        // Maximum batch size to prevent resource exhaustion
+        const MAX_BATCH_SIZE: u32 = 50;
+        
+        Self::check_not_reentrant(&env)?;
+
+        if requests.len() > MAX_BATCH_SIZE {
+            return Err(TradingError::BatchSizeExceeded);
+        }
+
+        Self::require_not_paused(&env, PAUSE_TRADE)?;
+
+        let mut successful_trades = soroban_sdk::Vec::new(&env);
+        let mut failed_trades = soroban_sdk::Vec::new(&env);
+        let mut total_fees_collected = 0i128;
+        let mut total_gas_saved = 0i128;
+
+        // Get current stats from optimized storage
+        let mut stats = TradingStorage::get_stats(&env);
+
+        // Process each trade request
+        for (index, request) in requests.iter().enumerate() {
+            // Authenticate the trader
+            request.trader.require_auth();
+
+            let result = match Self::process_single_trade(
+                &env,
+                &request,
+                &mut stats,
+                index as u32,
+            ) {
+                Ok(trade_id) => {
+                    successful_trades.push_back(trade_id);
+                    total_fees_collected = total_fees_collected
+                        .checked_add(request.fee_amount)
+                        .ok_or(TradingError::ArithmeticOverflow)?;
+                    total_gas_saved += 1000i128; // Estimated gas savings per trade
+                    BatchTradeResult {
+                        trade_id: Some(trade_id),
+                        success: true,
+                        error_code: None,
+                    }
+                }
+                Err(error) => BatchTradeResult {
+                    trade_id: None,
+                    success: false,
+                    error_code: Some(error as u32),
+                },
+            };
+
+            failed_trades.push_back(result);
+        }
+
+        // Update stats in optimized storage
+        TradingStorage::set_stats(&env, &stats);
+
+        Ok(BatchTradeOperation {
+            successful_trades,
+            failed_trades,
+            total_fees_collected,
+            gas_saved: total_gas_saved,
+        })
+    }
+
+    /// Pay several fees from one trader in a single all-or-nothing transaction
+    ///
+    /// `fees[i]` is transferred in `fee_token` to `recipients[i]`. If any transfer
+    /// fails the whole batch reverts. Returns the total fees collected, 0 if
+    /// any fee trips the circuit breaker.
+    ///
+    /// Every recipient passes the self-trade check, and the batch counts as
+    /// one trade for the cooldown and, with its total fee, the daily limit.
+    /// Each fee is held to `max_fee_pct` of the trader's balance when it is paid.
+    /// Fees are burned, pooled and routed like `trade` fees.
+    pub fn batch_trade_atomic(
+        env: Env,
+        trader: Address,
+        fee_token: Address,
+        fees: Vec<i128>,
+        recipients: Vec<Address>,
+    ) -> Result<i128, TradingError> {
+        // Maximum batch size to prevent unbounded loops
+        const MAX_ATOMIC_BATCH_SIZE: u32 = 100;
+
+        Self::check_not_reentrant(&env)?;
+
+        if fees.is_empty() {
+            return Err(TradingError::EmptyBatch);
+        }
+
+        if fees.len() != recipients.len() {
+            return Err(TradingError::BatchLengthMismatch);
+        }
+
+        if fees.len() > MAX_ATOMIC_BATCH_SIZE {
+            return Err(TradingError::BatchSizeExceeded);
+        }
+
+        Self::require_not_paused(&env, PAUSE_TRADE)?;
+        Self::require_token_not_paused(&env, &fee_token)?;
+
+        if !TradingStorage::is_fee_token_allowed(&env, &fee_token) {
+            return Err(TradingError::TokenNotAllowed);
+        }
+
+        trader.require_auth();
+
+        // One oversized fee trips the breaker for the whole batch before anything moves
+        if Self::trip_circuit_breaker(&env, fees.iter().max().unwrap_or(0)) {
+            return Ok(0);
+        }
+
+        // The batch is one trade for the daily limit and cooldown
+        let mut batch_fees = 0i128;
+        for (fee_amount, recipient) in fees.iter().zip(recipients.iter()) {
+            Self::check_self_trade(&env, &trader, &recipient)?;
+            batch_fees = batch_fees.checked_add(fee_amount).ok_or(TradingError::ArithmeticOverflow)?;
+        }
+        Self::consume_daily_limit(&env, &trader, batch_fees)?;
+        Self::consume_cooldown(&env, &trader)?;
+
+        let mut total_fees_collected = 0i128;
+        for (fee_amount, recipient) in fees.iter().zip(recipients.iter()) {
+            Self::check_fee_share(&env, &fee_token, &trader, fee_amount)?;
+            let (recipient, routed) = Self::pay_fee(&env, &trader, &fee_token, &trader, recipient, fee_amount)?;
+
+            Self::emit_fee_collected(&env, FeeCollectedEvent {
+                payer: trader.clone(),
+                recipient,
+                amount: routed,
+                token: fee_token.clone(),
+                timestamp: env.ledger().timestamp(),
+            });
+
+            total_fees_collected = total_fees_collected
+                .checked_add(fee_amount)
+                .ok_or(TradingError::ArithmeticOverflow)?;
+        }
+
+        Ok(total_fees_collected)
+    }
+
+    /// Split one fee between several recipients in proportion to `weights`
+    ///
+    /// `weights` are basis points and must sum to 10_000. Each share rounds
+    /// down and the remainder goes to the first recipient, so the shares
+    /// always add up to `fee`. Each share is burned, pooled and routed like a
+    /// `trade` fee. Returns the shares, or no amounts if the fee trips the
+    /// circuit breaker. The fee passes the same self-trade, daily-limit,
+    /// cooldown and `max_fee_pct` checks as `trade`.
+    pub fn trade_split(
+        env: Env,
+        trader: Address,
+        fee_token: Address,
+        fee: i128,
+        recipients: Vec<Address>,
+        weights: Vec<u32>,
+    ) -> Result<Vec<i128>, TradingError> {
+        // Maximum number of recipients to keep the split bounded
+        const MAX_SPLIT_RECIPIENTS: u32 = 10;
+
+        Self::check_not_reentrant(&env)?;
+
+        if recipients.is_empty() {
+            return Err(TradingError::EmptyBatch);
+        }
+
+        if recipients.len() != weights.len() {
+            return Err(TradingError::BatchLengthMismatch);
+        }
+
+        if recipients.len() > MAX_SPLIT_RECIPIENTS {
+            return Err(TradingError::BatchSizeExceeded);
+        }
+
+        let total_weight: u64 = weights.iter().map(u64::from).sum();
+        if total_weight != BPS_DENOMINATOR as u64 {
+            return Err(TradingError::InvalidWeights);
+        }
+
+        if fee < 0 {
+            return Err(TradingError::InvalidAmount);
+        }
+
+        Self::require_not_paused(&env, PAUSE_TRADE)?;
+        Self::require_token_not_paused(&env, &fee_token)?;
+
+        if !TradingStorage::is_fee_token_allowed(&env, &fee_token) {
+            return Err(TradingError::TokenNotAllowed);
+        }
+
+        trader.require_auth();
+
+        let mut shares = Vec::new(&env);
+        if Self::trip_circuit_breaker(&env, fee) {
+            return Ok(shares);
+        }
+        for recipient in recipients.iter() {
+            Self::check_self_trade(&env, &trader, &recipient)?;
+        }
+        Self::consume_daily_limit(&env, &trader, fee)?;
+        Self::consume_cooldown(&env, &trader)?;
+        Self::check_fee_share(&env, &fee_token, &trader, fee)?;
+
+        let mut allocated = 0i128;
+        for weight in weights.iter() {
+            let share = FeeManager::calculate_bps_fee(fee, weight)?;
+            shares.push_back(share);
+            allocated += share;
+        }
+        let first = shares.get_unchecked(0);
+        shares.set(0, first + (fee - allocated));
+
+        for (share, recipient) in shares.iter().zip(recipients.iter()) {
+            let (recipient, routed) = Self::pay_fee(&env, &trader, &fee_token, &trader, recipient, share)?;
+
+            Self::emit_fee_collected(&env, FeeCollectedEvent {
+                payer: trader.clone(),
+                recipient,
+                amount: routed,
+                token: fee_token.clone(),
+                timestamp: env.ledger().timestamp(),
+            });
+        }
+
+        Ok(shares)
+    }
+
+    /// Process a single trade within a batch operation
+    fn process_single_trade(
+        env: &Env,
+        request: &BatchTradeRequest,
+        stats: &mut OptimizedTradeStats,
+        _batch_index: u32,
+    ) -> Result<u64, TradingError> {
+        // Validate amount
+        if request.amount <= 0 {
+            return Err(TradingError::InvalidAmount);
+        }
+
+        if request.amount < TradingStorage::get_min_trade_amount(env) {
+            return Err(TradingError::BelowMinTrade);
+        }
+
+        Self::require_token_not_paused(env, &request.fee_token)?;
+
+        if !TradingStorage::is_fee_token_allowed(env, &request.fee_token) {
+            return Err(TradingError::TokenNotAllowed);
+        }
+
+        // The batch itself succeeds, so a trip sticks and pauses the remaining requests
+        if TradingStorage::is_operation_paused(env, PAUSE_TRADE) || Self::trip_circuit_breaker(env, request.fee_amount) {
+            return Err(TradingError::Paused);
+        }
+
+        Self::check_fee_share(env, &request.fee_token, &request.trader, request.fee_amount)?;
+
+        // Collect fee first
+        FeeManager::collect_fee(
+            env,
+            &request.fee_token,
+            &request.trader,
+            &request.fee_recipient,
+            request.fee_amount,
+        )?;
+        TradingStorage::add_fee_volume(env, &request.fee_token, request.fee_amount)?;
+        Self::record_tier_volume(env, &request.trader, request.fee_amount)?;
+
+        // Emit fee collected event
+        Self::emit_fee_collected(env, FeeCollectedEvent {
+            payer: request.trader.clone(),
+            recipient: request.fee_recipient.clone(),
+            amount: request.fee_amount,
+            token: request.fee_token.clone(),
+            timestamp: env.ledger().timestamp(),
+        });
+
+        // Create trade record with optimized storage
+        let trade_id = stats.last_trade_id + 1;
+        let timestamp = env.ledger().timestamp();
+        let trade = OptimizedTrade {
+            id: trade_id,
+            trader: request.trader.clone(),
+            pair: request.pair.clone(),
+            amount: request.amount,
+            price: request.price,
+            timestamp,
+            is_buy: request.is_buy,
+        };
+
+        // Update stats
+        stats.total_trades += 1;
+        stats.total_volume = stats.total_volume
+            .checked_add(request.amount)
+            .ok_or(TradingError::ArithmeticOverflow)?;
+        stats.last_trade_id = trade_id;
+
+        // Store trade with optimized individual key
+        TradingStorage::set_trade(env, &trade);
+        TradingStorage::add_settle_volume(env, &request.fee_token, request.amount)?;
+        TradingStorage::push_recent_trade(env, &TradeRecord {
+            trade_id,
+            trader: request.trader.clone(),
+            pair: request.pair.clone(),
+            amount: request.amount,
+            price: request.price,
+            is_buy: request.is_buy,
+            fee_token: request.fee_token.clone(),
+            fee_paid: request.fee_amount,
+            settle_token: request.fee_token.clone(),
+            ledger: env.ledger().sequence(),
+            timestamp,
+        });
+
+        // Emit trade executed event with batch index
+        Self::emit_trade_executed(env, TradeExecutedEvent {
+            trade_id,
+            trader: request.trader.clone(),
+            pair: request.pair.clone(),
+            amount: request.amount,
+            price: request.price,
+            is_buy: request.is_buy,
+            fee_amount: request.fee_amount,
+            fee_token: request.fee_token.clone(),
+            settle_token: request.fee_token.clone(),
+            tag: Symbol::new(env, ""),
+            timestamp,
+        });
+
+        Ok(trade_id)
+    }
+
+    /// Get current contract version
+    pub fn get_version(env: Env) -> u32 {
+        TradingStorage::get_version(&env)
+    }
+
+    /// Get the current admin; fails with `NotInitialized` before `init`
+    pub fn admin(env: Env) -> Result<Address, TradingError> {
+        let roles = TradingStorage::get_roles(&env).ok_or(TradingError::NotInitialized)?;
+        roles
+            .iter()
+            .find(|(_, role)| *role == GovernanceRole::Admin)
+            .map(|(address, _)| address)
+            .ok_or(TradingError::NotInitialized)
+    }
+
+    /// Check whether `address` is the current admin, without requiring auth
+    pub fn is_admin(env: Env, address: Address) -> bool {
+        Self::has_admin_role(&env, &address)
+    }
+
+    /// Get admin, pause state, fee settings and version in a single call
+    pub fn config(env: Env) -> Result<TradingConfig, TradingError> {
+        let admin = Self::admin(env.clone())?;
+        let (min_fee, max_fee) = TradingStorage::get_fee_bounds(&env);
+
+        Ok(TradingConfig {
+            admin,
+            paused: TradingStorage::is_paused(&env),
+            fee_bps: TradingStorage::get_fee_bps(&env),
+            min_fee,
+            max_fee,
+            version: TradingStorage::get_version(&env),
+        })
+    }
+
+    /// Report pause, termination, initialization and instance TTL in one read
+    ///
+    /// `instance_ttl_remaining` is an approximation, not the host's live TTL:
+    /// it counts down from the ledger recorded each time the contract extends
+    /// its own instance, as `bump_ttl` and the trade entrypoints do.
+    /// Extensions made outside the contract, such as an
+    /// `ExtendFootprintTTL` operation, are not recorded, so the real TTL may be
+    /// longer than reported but never shorter.
+    pub fn health(env: Env) -> HealthStatus {
+        let live_until = TradingStorage::get_instance_live_until(&env);
+
+        HealthStatus {
+            paused: TradingStorage::is_paused(&env),
+            terminated: TradingStorage::is_terminated(&env),
+            initialized: TradingStorage::is_initialized(&env),
+            instance_ttl_remaining: live_until.saturating_sub(env.ledger().sequence()),
+        }
+    }
+
+    /// Get trading statistics
+    pub fn get_stats(env: Env) -> OptimizedTradeStats {
+        TradingStorage::get_stats(&env)
+    }
+    
+    /// Get the cumulative fees collected in `token`
+    pub fn total_fees(env: Env, token: Address) -> i128 {
+        TradingStorage::get_fee_volume(&env, &token)
+    }
+
+    /// Get the cumulative trade amount settled in `token`
+    pub fn settle_volume(env: Env, token: Address) -> i128 {
+        TradingStorage::get_settle_volume(&env, &token)
+    }
+
+    /// Get the cumulative trade fees delivered to `recipient`
+    ///
+    /// With the `Accrue` fee sink, fees are counted against the contract's
+    /// own address. Burned shares and refunded fees are excluded.
+    pub fn received_total(env: Env, recipient: Address) -> i128 {
+        TradingStorage::get_received(&env, &recipient).0
+    }
+
+    /// Get the number of trades whose fee was delivered to `recipient`
+    pub fn received_count(env: Env, recipient: Address) -> u64 {
+        TradingStorage::get_received(&env, &recipient).1
+    }
+
+    /// Get the cumulative fees paid by trades tagged with `tag`
+    pub fn tag_volume(env: Env, tag: Symbol) -> i128 {
+        TradingStorage::get_tag_volume(&env, &tag)
+    }
+
+    /// Get the total number of trades executed
+    pub fn total_trades(env: Env) -> u64 {
+        TradingStorage::get_stats(&env).total_trades
+    }
+    
+    /// Get trade by ID
+    pub fn get_trade(env: Env, trade_id: u64) -> Option<OptimizedTrade> {
+        TradingStorage::get_trade(&env, trade_id)
+    }
+    
+    /// Page through the most recent trades, newest first
+    ///
+    /// `start` counts back from the newest trade (0 = newest). Only the last
+    /// `MAX_RECENT_TRADES` (256) trades are retained; `limit` is capped to that.
+    pub fn recent_trades(env: Env, start: u32, limit: u32) -> Vec<TradeRecord> {
+        let end = start
+            .saturating_add(limit.min(TradingStorage::MAX_RECENT_TRADES))
+            .min(TradingStorage::get_recent_trade_count(&env));
+        let mut records = Vec::new(&env);
+        for age in start..end {
+            if let Some(record) = TradingStorage::get_recent_trade(&env, age) {
+                records.push_back(record);
+            }
+        }
+        records
+    }
+
     /// Get trades by trader
     pub fn get_trades_by_trader(env: Env, trader: Address) -> soroban_sdk::Vec<OptimizedTrade> {
         TradingStorage::get_trader_trades(&env, &trader)
     }
 
-    pub fn refresh_oracle_price(env: Env, pair: Symbol) -> Result<OracleAggregate, TradeError> {
-        let config = TradingStorage::get_oracle_config(&env)
-            .ok_or(TradeError::NotInitialized)?;
+    pub fn refresh_oracle_price(env: Env, pair: Symbol) -> Result<OracleAggregate, TradingError> {
+        Self::require_not_terminated(&env)?;
+
+        let config = TradingStorage::get_oracle_config(&env)
+            .ok_or(TradingError::NotInitialized)?;
+
+        let aggregate =
+            fetch_aggregate_price(&env, &config.oracles, &pair, config.max_staleness, config.min_sources)
+                .map_err(|_| TradingError::OracleFailure)?;
+
+        let mut status = TradingStorage::get_oracle_status(&env);
+        status.last_pair = aggregate.pair.clone();
+        status.last_price = aggregate.median_price;
+        status.last_updated_at = env.ledger().timestamp();
+        status.last_source_count = aggregate.source_count;
+        status.consecutive_failures = 0;
+
+        TradingStorage::set_oracle_status(&env, &status);
+
+        env.events().publish(
+            (symbol_short!("orc_upd"),),
+            (aggregate.pair.clone(), aggregate.median_price, aggregate.source_count),
+        );
+
+        Ok(aggregate)
+    }
+
+    pub fn record_oracle_failure(env: Env, _pair: Symbol) -> Result<(), TradingError> {
+        Self::require_not_terminated(&env)?;
+
+        let mut status = TradingStorage::get_oracle_status(&env);
+        status.consecutive_failures += 1;
+        TradingStorage::set_oracle_status(&env, &status);
+
+        env.events().publish(
+            (symbol_short!("orc_fail"),),
+            status.consecutive_failures,
+        );
+
+        Ok(())
+    }
+
+    pub fn get_oracle_status(env: Env) -> OptimizedOracleStatus {
+        TradingStorage::get_oracle_status(&env)
+    }
+
+    /// Pause the contract (admin, pauser or guardian)
+    pub fn pause(env: Env, pauser: Address) -> Result<(), TradingError> {
+        Self::set_pause_with_reason(env.clone(), pauser, true, Symbol::new(&env, ""))
+    }
+
+    /// Unpause the contract (admin or pauser)
+    pub fn unpause(env: Env, pauser: Address) -> Result<(), TradingError> {
+        Self::set_pause_with_reason(env.clone(), pauser, false, Symbol::new(&env, ""))
+    }
+
+    /// Pause or unpause the contract, recording a reason (admin or pauser;
+    /// a guardian may only pause)
+    ///
+    /// The reason and ledger timestamp are overwritten on every change, so an
+    /// unpause replaces the incident reason with its own. Pausing is instant;
+    /// unpausing waits out `unpause_delay` ledgers.
+    pub fn set_pause_with_reason(
+        env: Env,
+        pauser: Address,
+        paused: bool,
+        reason: Symbol,
+    ) -> Result<(), TradingError> {
+        if paused {
+            Self::require_pause_role(&env, &pauser)?;
+        } else {
+            Self::require_role(&env, &pauser, TradingRole::Pauser)?;
+        }
+
+        if paused {
+            Self::update_pause_flags(&env, pauser, PAUSE_ALL, reason);
+        } else {
+            Self::resume_operations(&env, pauser, PAUSE_ALL, reason);
+        }
+
+        Ok(())
+    }
+
+    /// Make every unpause wait `delay` ledgers before operations resume
+    /// (admin only, timelocked)
+    ///
+    /// Gives users the same head start as the admin after an incident. 0
+    /// resumes immediately.
+    pub fn set_unpause_delay(env: Env, admin: Address, delay: u32) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
 
-        let aggregate =
-            fetch_aggregate_price(&env, &config.oracles, &pair, config.max_staleness, config.min_sources)
-                .map_err(|_| TradeError::OracleFailure)?;
+        Self::schedule_change(&env, ParamChange::UnpauseDelay(delay));
 
-        let mut status = TradingStorage::get_oracle_status(&env);
-        status.last_pair = aggregate.pair.clone();
-        status.last_price = aggregate.median_price;
-        status.last_updated_at = env.ledger().timestamp();
-        status.last_source_count = aggregate.source_count;
-        status.consecutive_failures = 0;
+        Ok(())
+    }
 
-        TradingStorage::set_oracle_status(&env, &status);
+    /// Whether any operation is paused, including during an unpause grace period
+    pub fn is_paused(env: Env) -> bool {
+        TradingStorage::is_paused(&env)
+    }
 
-        env.events().publish(
-            (symbol_short!("orc_upd"),),
-            (aggregate.pair.clone(), aggregate.median_price, aggregate.source_count),
-        );
+    /// Get the grace period, in ledgers, between an unpause and resumption
+    pub fn unpause_delay(env: Env) -> u32 {
+        TradingStorage::get_unpause_delay(&env)
+    }
 
-        Ok(aggregate)
+    /// Get `(flags, resume_ledger)` for an unpause still waiting out its grace
+    /// period, if any
+    pub fn pending_resume(env: Env) -> Option<(u32, u32)> {
+        TradingStorage::get_pending_resume(&env)
+            .filter(|(_, resume_ledger)| env.ledger().sequence() < *resume_ledger)
     }
 
-    pub fn record_oracle_failure(env: Env, _pair: Symbol) {
-        let mut status = TradingStorage::get_oracle_status(&env);
-        status.consecutive_failures += 1;
-        TradingStorage::set_oracle_status(&env, &status);
+    /// Pause everything through `until_ledger`, after which the pause lapses
+    /// on its own (admin, pauser or guardian)
+    ///
+    /// Any later pause or unpause call replaces the expiry with an indefinite
+    /// state. Fails with `InvalidWindow` if `until_ledger` has already passed.
+    /// Ending an active pause sooner than it would have ended is an unpause,
+    /// so guardians get `Unauthorized` for it and it fails with
+    /// `InvalidWindow` unless `until_ledger` leaves `unpause_delay` ledgers.
+    pub fn set_pause_until(env: Env, pauser: Address, until_ledger: u32) -> Result<(), TradingError> {
+        Self::require_pause_role(&env, &pauser)?;
 
-        env.events().publish(
-            (symbol_short!("orc_fail"),),
-            status.consecutive_failures,
-        );
+        if until_ledger < env.ledger().sequence() {
+            return Err(TradingError::InvalidWindow);
+        }
+
+        let shortens = TradingStorage::is_paused(&env)
+            && TradingStorage::get_pause_until(&env).is_none_or(|until| until_ledger < until);
+        if shortens
+            && !Self::has_admin_role(&env, &pauser)
+            && !RoleManager::has_role(&env, &pauser, TradingRole::Pauser)
+        {
+            return Err(TradingError::Unauthorized);
+        }
+        let earliest_resume = env.ledger().sequence().saturating_add(TradingStorage::get_unpause_delay(&env));
+        if shortens && until_ledger < earliest_resume {
+            return Err(TradingError::InvalidWindow);
+        }
+
+        Self::update_pause_flags(&env, pauser, PAUSE_ALL, Symbol::new(&env, ""));
+        TradingStorage::set_pause_until(&env, until_ledger);
+
+        Ok(())
     }
 
-    pub fn get_oracle_status(env: Env) -> OptimizedOracleStatus {
-        TradingStorage::get_oracle_status(&env)
+    /// Get the last ledger of an active self-expiring pause, if any
+    pub fn pause_until(env: Env) -> Option<u32> {
+        if !TradingStorage::is_paused(&env) {
+            return None;
+        }
+        TradingStorage::get_pause_until(&env)
     }
 
-    /// Pause the contract (admin only)
-    pub fn pause(env: Env, admin: Address) -> Result<(), TradeError> {
-        admin.require_auth();
+    /// Pause a single operation, e.g. `PAUSE_REWARD`, leaving others running (admin, pauser or guardian)
+    pub fn pause_operation(env: Env, pauser: Address, flag: u32) -> Result<(), TradingError> {
+        Self::require_pause_role(&env, &pauser)?;
+
+        Self::validate_pause_flag(flag)?;
+
+        let flags = TradingStorage::get_pause_flags(&env) | flag;
+        Self::update_pause_flags(&env, pauser, flags, Symbol::new(&env, ""));
+
+        Ok(())
+    }
+
+    /// Resume a single paused operation (admin or pauser)
+    pub fn unpause_operation(env: Env, pauser: Address, flag: u32) -> Result<(), TradingError> {
+        Self::require_role(&env, &pauser, TradingRole::Pauser)?;
+
+        Self::validate_pause_flag(flag)?;
+
+        Self::resume_operations(&env, pauser, flag, Symbol::new(&env, ""));
+
+        Ok(())
+    }
+
+    /// Block trades, deposits, escrows and scheduled trades that move `token`,
+    /// leaving other tokens trading (admin, pauser or guardian)
+    pub fn pause_token(env: Env, pauser: Address, token: Address) -> Result<(), TradingError> {
+        Self::require_pause_role(&env, &pauser)?;
+
+        TradingStorage::set_token_paused(&env, &token, true);
+        Self::log_admin_action(&env, &pauser, "pause_token", 1);
+
+        env.events().publish((Symbol::new(&env, "token_paused"), token), (pauser, true));
+
+        Ok(())
+    }
+
+    /// Resume trades in a paused token (admin or pauser)
+    pub fn unpause_token(env: Env, pauser: Address, token: Address) -> Result<(), TradingError> {
+        Self::require_role(&env, &pauser, TradingRole::Pauser)?;
+
+        TradingStorage::set_token_paused(&env, &token, false);
+        Self::log_admin_action(&env, &pauser, "pause_token", 0);
+
+        env.events().publish((Symbol::new(&env, "token_paused"), token), (pauser, false));
+
+        Ok(())
+    }
+
+    /// Check whether trades in `token` are paused by `pause_token`
+    pub fn is_token_paused(env: Env, token: Address) -> bool {
+        TradingStorage::is_token_paused(&env, &token)
+    }
+
+    /// Get the bitmask of currently paused operations
+    pub fn pause_flags(env: Env) -> u32 {
+        TradingStorage::get_pause_flags(&env)
+    }
+
+    /// Helper: Reject empty or unknown pause flags
+    fn validate_pause_flag(flag: u32) -> Result<(), TradingError> {
+        if flag == 0 || flag & !PAUSE_ALL != 0 {
+            return Err(TradingError::InvalidPauseFlag);
+        }
+        Ok(())
+    }
+
+    /// Helper: Store new pause flags and record the change
+    fn update_pause_flags(env: &Env, pauser: Address, flags: u32, reason: Symbol) {
+        Self::extend_instance_ttl(env);
+        TradingStorage::set_pause_flags(env, flags);
+
+        let timestamp = env.ledger().timestamp();
+        TradingStorage::set_pause_info(env, &pauser, &reason, timestamp);
+        Self::log_admin_action(env, &pauser, "set_pause", flags as i128);
+
+        if flags != 0 {
+            EventEmitter::contract_paused(env, ContractPausedEvent {
+                paused_by: pauser,
+                flags,
+                reason,
+                timestamp,
+            });
+        } else {
+            EventEmitter::contract_unpaused(env, ContractUnpausedEvent {
+                unpaused_by: pauser,
+                timestamp,
+            });
+        }
+    }
+
+    /// Helper: Unpause `flags`, after the unpause delay if one is set
+    ///
+    /// During the grace period the operations stay paused and a
+    /// `("resume",)` event announces `(pauser, flags, resume_ledger)`. Any
+    /// later pause change replaces the scheduled resumption.
+    fn resume_operations(env: &Env, pauser: Address, flags: u32, reason: Symbol) {
+        let current = TradingStorage::get_pause_flags(env);
+        let delay = TradingStorage::get_unpause_delay(env);
+        if delay == 0 || current & flags == 0 {
+            Self::update_pause_flags(env, pauser, current & !flags, reason);
+            return;
+        }
+
+        Self::extend_instance_ttl(env);
+        let resume_ledger = env.ledger().sequence().saturating_add(delay);
+        TradingStorage::set_pause_flags(env, current);
+        TradingStorage::set_pending_resume(env, flags, resume_ledger);
+        TradingStorage::set_pause_info(env, &pauser, &reason, env.ledger().timestamp());
+        Self::log_admin_action(env, &pauser, "resume", flags as i128);
+
+        env.events().publish((symbol_short!("resume"),), (pauser, flags, resume_ledger));
+    }
 
-        // Verify admin role using optimized storage
-        let role = TradingStorage::get_role(&env, &admin)
-            .ok_or(TradeError::Unauthorized)?;
+    /// Get `(paused, reason, timestamp)` for the last pause change
+    pub fn pause_info(env: Env) -> (bool, Symbol, u64) {
+        (
+            TradingStorage::is_paused(&env),
+            TradingStorage::get_pause_reason(&env),
+            TradingStorage::get_pause_changed_at(&env),
+        )
+    }
+
+    /// Page through the admin log, oldest first
+    ///
+    /// `start` counts forward from the oldest entry still held (0 = oldest).
+    /// Pause changes, fee and allowlist updates, role changes, resets and
+    /// termination are recorded; only the last `MAX_ADMIN_LOG` (256) entries
+    /// are retained and `limit` is capped to that.
+    pub fn admin_log(env: Env, start: u32, limit: u32) -> Vec<AdminLogEntry> {
+        let end = start
+            .saturating_add(limit.min(TradingStorage::MAX_ADMIN_LOG))
+            .min(TradingStorage::get_admin_log_count(&env));
+        let mut entries = Vec::new(&env);
+        for index in start..end {
+            if let Some(entry) = TradingStorage::get_admin_log(&env, index) {
+                entries.push_back(entry);
+            }
+        }
+        entries
+    }
+
+    /// Helper: Append a privileged action to the admin log
+    fn log_admin_action(env: &Env, actor: &Address, action: &str, detail: i128) {
+        TradingStorage::push_admin_log(env, &AdminLogEntry {
+            actor: actor.clone(),
+            action: Symbol::new(env, action),
+            ledger: env.ledger().sequence(),
+            detail,
+        });
+    }
+
+    /// Get the address behind the last pause change, if any
+    ///
+    /// Automatic pauses, such as a tripped circuit breaker, record the
+    /// contract's own address.
+    pub fn last_pauser(env: Env) -> Option<Address> {
+        TradingStorage::get_last_pauser(&env)
+    }
+
+    /// Hand `set_fee_bps` and `upgrade` over to `threshold`-of-`signers`
+    /// approval (admin only, once)
+    ///
+    /// After this the single admin can no longer call those entrypoints
+    /// directly, nor reconfigure the signers.
+    pub fn set_admin_signers(env: Env, admin: Address, signers: Vec<Address>, threshold: u32) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+        Self::require_no_multisig(&env)?;
 
-        if role != GovernanceRole::Admin {
-            return Err(TradeError::Unauthorized);
+        if threshold == 0 || threshold > signers.len() {
+            return Err(TradingError::InvalidThreshold);
+        }
+        for (index, signer) in signers.iter().enumerate() {
+            if signers.first_index_of(&signer) != Some(index as u32) {
+                return Err(TradingError::InvalidThreshold);
+            }
         }
 
-        TradingStorage::set_paused(&env, true);
+        MultisigManager::set_signers(&env, &signers, threshold);
 
         Ok(())
     }
 
-    /// Unpause the contract (admin only)
-    pub fn unpause(env: Env, admin: Address) -> Result<(), TradeError> {
-        admin.require_auth();
+    /// Get the admin signers
+    pub fn signers(env: Env) -> Vec<Address> {
+        MultisigManager::signers(&env)
+    }
+
+    /// Get the number of signer approvals an admin action needs (0 = multisig off)
+    pub fn threshold(env: Env) -> u32 {
+        MultisigManager::threshold(&env)
+    }
+
+    /// Propose an admin action, counting as its first approval (signers only)
+    ///
+    /// The action runs as soon as it reaches the threshold, which may be on
+    /// this call. Returns the action id.
+    pub fn propose_action(env: Env, signer: Address, action: AdminAction) -> Result<u64, TradingError> {
+        Self::require_signer(&env, &signer)?;
 
-        let role = TradingStorage::get_role(&env, &admin)
-            .ok_or(TradeError::Unauthorized)?;
+        if let AdminAction::SetFeeBps(bps) = &action {
+            if *bps > BPS_DENOMINATOR {
+                return Err(TradingError::InvalidFeeBps);
+            }
+        }
+
+        let action_id = MultisigManager::next_action_id(&env);
+        let mut approvals = Vec::new(&env);
+        approvals.push_back(signer.clone());
+        let mut pending = PendingAction { action, approvals, executed: false };
+        Self::execute_action_if_approved(&env, &signer, &mut pending)?;
+        MultisigManager::set_action(&env, action_id, &pending);
+
+        Ok(action_id)
+    }
+
+    /// Approve a proposed admin action (signers only)
+    ///
+    /// Each signer approves an action at most once. Returns whether this
+    /// approval executed it.
+    pub fn approve_action(env: Env, signer: Address, action_id: u64) -> Result<bool, TradingError> {
+        Self::require_signer(&env, &signer)?;
+
+        let mut pending = MultisigManager::get_action(&env, action_id)
+            .ok_or(TradingError::ActionNotFound)?;
+        if pending.executed {
+            return Err(TradingError::ActionAlreadyExecuted);
+        }
+        if pending.approvals.contains(&signer) {
+            return Err(TradingError::AlreadyApproved);
+        }
+
+        pending.approvals.push_back(signer.clone());
+        Self::execute_action_if_approved(&env, &signer, &mut pending)?;
+        MultisigManager::set_action(&env, action_id, &pending);
+
+        Ok(pending.executed)
+    }
+
+    /// Get a proposed admin action and its approvals
+    pub fn admin_action(env: Env, action_id: u64) -> Option<PendingAction> {
+        MultisigManager::get_action(&env, action_id)
+    }
+
+    /// Helper: Run `pending` once it has enough approvals, logging `signer`
+    /// as the approver who completed it
+    fn execute_action_if_approved(env: &Env, signer: &Address, pending: &mut PendingAction) -> Result<(), TradingError> {
+        if pending.approvals.len() < MultisigManager::threshold(env) {
+            return Ok(());
+        }
+
+        Self::run_admin_action(env, signer, &pending.action)?;
+        pending.executed = true;
+
+        Ok(())
+    }
+
+    /// Helper: Carry out an admin action that has been authorized and record
+    /// it in the admin log under `actor`
+    fn run_admin_action(env: &Env, actor: &Address, action: &AdminAction) -> Result<(), TradingError> {
+        match action {
+            AdminAction::SetFeeBps(bps) => {
+                if *bps > BPS_DENOMINATOR {
+                    return Err(TradingError::InvalidFeeBps);
+                }
+
+                Self::schedule_change(env, ParamChange::FeeBps(*bps));
+                Self::log_admin_action(env, actor, "set_fee_bps", *bps as i128);
+            }
+            AdminAction::Upgrade(new_wasm_hash) => {
+                // Only upgrade from a quiesced state
+                if !TradingStorage::is_paused(env) {
+                    return Err(TradingError::NotPaused);
+                }
+
+                Self::log_admin_action(env, actor, "upgrade", 0);
+                env.deployer().update_current_contract_wasm(new_wasm_hash.clone());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Helper: Require `signer`'s auth and membership in the admin signer set
+    fn require_signer(env: &Env, signer: &Address) -> Result<(), TradingError> {
+        Self::require_not_terminated(env)?;
+        signer.require_auth();
+
+        if !MultisigManager::is_signer(env, signer) {
+            return Err(TradingError::NotSigner);
+        }
+        Ok(())
+    }
 
-        if role != GovernanceRole::Admin {
-            return Err(TradeError::Unauthorized);
+    /// Helper: Reject single-admin calls to actions owned by the signer set
+    fn require_no_multisig(env: &Env) -> Result<(), TradingError> {
+        if MultisigManager::threshold(env) > 0 {
+            return Err(TradingError::MultisigRequired);
         }
+        Ok(())
+    }
+
+    /// Grant an operational role to an address (admin only)
+    pub fn grant_role(env: Env, admin: Address, address: Address, role: TradingRole) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        RoleManager::grant_role(&env, &address, role);
+        Self::log_admin_action(&env, &admin, "grant_role", role as i128);
+
+        Ok(())
+    }
 
-        TradingStorage::set_paused(&env, false);
+    /// Revoke an operational role from an address (admin only)
+    pub fn revoke_role(env: Env, admin: Address, address: Address, role: TradingRole) -> Result<(), TradingError> {
+        Self::require_admin(&env, &admin)?;
+
+        RoleManager::revoke_role(&env, &address, role);
+        Self::log_admin_action(&env, &admin, "revoke_role", role as i128);
 
         Ok(())
     }
 
-    pub fn pause_upgrade_governance(env: Env, admin: Address) -> Result<(), TradeError> {
+    /// Check whether an address holds an operational role
+    pub fn has_role(env: Env, address: Address, role: TradingRole) -> bool {
+        RoleManager::has_role(&env, &address, role)
+    }
+
+    pub fn pause_upgrade_governance(env: Env, admin: Address) -> Result<(), TradingError> {
+        Self::require_not_terminated(&env)?;
         admin.require_auth();
 
         GovernanceManager::pause_governance(&env, admin)
-            .map_err(|_| TradeError::Unauthorized)
+            .map_err(|_| TradingError::Unauthorized)
     }
 
-    pub fn resume_upgrade_governance(env: Env, admin: Address) -> Result<(), TradeError> {
+    pub fn resume_upgrade_governance(env: Env, admin: Address) -> Result<(), TradingError> {
+        Self::require_not_terminated(&env)?;
         admin.require_auth();
 
         GovernanceManager::resume_governance(&env, admin)
-            .map_err(|_| TradeError::Unauthorized)
+            .map_err(|_| TradingError::Unauthorized)
     }
 
     /// Helper: Check if address is admin
-    fn is_admin(env: &Env, address: &Address) -> bool {
+    fn has_admin_role(env: &Env, address: &Address) -> bool {
         TradingStorage::get_role(env, address) == Some(GovernanceRole::Admin)
     }
 
+    /// Helper: Add `fee` to `trader`'s lifetime volume and recompute their
+    /// tier, emitting `("tier_up", trader)` when it rises
+    fn record_tier_volume(env: &Env, trader: &Address, fee: i128) -> Result<(), TradingError> {
+        let volume = TradingStorage::add_trader_fee_volume(env, trader, fee)?;
+        let reached = TradingStorage::get_tier_thresholds(env)
+            .iter()
+            .filter(|threshold| volume >= *threshold)
+            .count();
+        let tier = match reached {
+            0 => Tier::None,
+            1 => Tier::Bronze,
+            2 => Tier::Silver,
+            _ => Tier::Gold,
+        };
+
+        let previous = TradingStorage::get_trader_tier(env, trader);
+        if tier == previous {
+            return Ok(());
+        }
+
+        TradingStorage::set_trader_tier(env, trader, tier);
+        if tier > previous {
+            env.events().publish((symbol_short!("tier_up"), trader.clone()), tier);
+        }
+
+        Ok(())
+    }
+
+    /// Helper: Enforce the per-trader cooldown and start a new one
+    fn consume_cooldown(env: &Env, trader: &Address) -> Result<(), TradingError> {
+        if TradingStorage::get_cooldown(env) == 0 {
+            return Ok(());
+        }
+
+        let now = env.ledger().timestamp();
+        if Self::next_trade_at(env, trader) > now {
+            return Err(TradingError::RateLimited);
+        }
+
+        TradingStorage::set_last_trade_at(env, trader, now);
+
+        Ok(())
+    }
+
+    /// Helper: Earliest timestamp `trader` may trade again, 0 when unrestricted
+    fn next_trade_at(env: &Env, trader: &Address) -> u64 {
+        let cooldown = TradingStorage::get_cooldown(env);
+        if cooldown == 0 {
+            return 0;
+        }
+
+        TradingStorage::get_last_trade_at(env, trader)
+            .map(|last| last.saturating_add(cooldown))
+            .unwrap_or(0)
+    }
+
+    /// Helper: Apply the self-trade, daily-limit and cooldown checks to a fee payment
+    fn consume_trade_limits(
+        env: &Env,
+        trader: &Address,
+        fee_recipient: &Address,
+        fee_amount: i128,
+    ) -> Result<(), TradingError> {
+        Self::check_self_trade(env, trader, fee_recipient)?;
+        Self::consume_daily_limit(env, trader, fee_amount)?;
+        Self::consume_cooldown(env, trader)
+    }
+
+    /// Helper: Reject a fee routed back to the trader, a no-op that only inflates volume
+    fn check_self_trade(env: &Env, trader: &Address, fee_recipient: &Address) -> Result<(), TradingError> {
+        if fee_recipient == trader && !TradingStorage::is_self_trade_allowed(env) {
+            return Err(TradingError::SelfTrade);
+        }
+        Ok(())
+    }
+
+    /// Helper: Give back the daily-limit usage of a `refunded` fee and restore
+    /// the cooldown to `last_trade_at`, its value before the trade
+    fn release_trade_limits(env: &Env, trader: &Address, refunded: i128, last_trade_at: Option<u64>) {
+        if TradingStorage::get_daily_limit(env) != 0 {
+            let day = env.ledger().timestamp() / RATE_LIMIT_WINDOW;
+            let volume = TradingStorage::get_daily_volume(env, trader, day);
+            TradingStorage::set_daily_volume(env, trader, day, (volume - refunded).max(0));
+        }
+
+        if TradingStorage::get_cooldown(env) != 0 {
+            match last_trade_at {
+                Some(timestamp) => TradingStorage::set_last_trade_at(env, trader, timestamp),
+                None => TradingStorage::clear_last_trade_at(env, trader),
+            }
+        }
+    }
+
+    /// Helper: Count a `fee_amount` fee paid by `trader` towards fee and tier
+    /// volume, and the `routed` share towards `recipient`'s received totals
+    fn record_fee_paid(
+        env: &Env,
+        trader: &Address,
+        fee_token: &Address,
+        fee_amount: i128,
+        recipient: &Address,
+        routed: i128,
+    ) -> Result<(), TradingError> {
+        TradingStorage::add_fee_volume(env, fee_token, fee_amount)?;
+        Self::record_tier_volume(env, trader, fee_amount)?;
+        TradingStorage::add_received(env, recipient, routed, 1)?;
+        if *recipient == env.current_contract_address() {
+            TradingStorage::add_accrued_fees(env, fee_token, routed)?;
+        }
+        Ok(())
+    }
+
+    /// Helper: Charge `fee_amount` against the trader's volume for the current day bucket
+    fn consume_daily_limit(env: &Env, trader: &Address, fee_amount: i128) -> Result<(), TradingError> {
+        if let Some((day, volume)) = Self::check_daily_limit(env, trader, fee_amount)? {
+            TradingStorage::set_daily_volume(env, trader, day, volume);
+        }
+
+        Ok(())
+    }
+
+    /// Helper: Day bucket and volume after adding `fee_amount`, or `None` when
+    /// rate limiting is off; fails if the limit would be exceeded
+    fn check_daily_limit(env: &Env, trader: &Address, fee_amount: i128) -> Result<Option<(u64, i128)>, TradingError> {
+        let limit = TradingStorage::get_daily_limit(env);
+        if limit == 0 {
+            return Ok(None);
+        }
+
+        let day = env.ledger().timestamp() / RATE_LIMIT_WINDOW;
+        let volume = TradingStorage::get_daily_volume(env, trader, day)
+            .checked_add(fee_amount)
+            .ok_or(TradingError::ArithmeticOverflow)?;
+        if volume > limit {
+            return Err(TradingError::RateLimited);
+        }
+
+        Ok(Some((day, volume)))
+    }
+
+    /// Helper: Keep instance storage (admin, roles, pause state) from expiring
+    ///
+    /// Extends once fewer than `INSTANCE_TTL_THRESHOLD` ledgers remain, and
+    /// records the resulting expiry for `health()`.
+    fn extend_instance_ttl(env: &Env) {
+        let ledger = env.ledger().sequence();
+        if TradingStorage::get_instance_live_until(env).saturating_sub(ledger) >= INSTANCE_TTL_THRESHOLD {
+            return;
+        }
+
+        // Extending to the full target even if the real TTL is already past
+        // the threshold keeps the recorded expiry a lower bound
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_TTL_EXTEND_TO, INSTANCE_TTL_EXTEND_TO);
+        TradingStorage::set_instance_live_until(env, ledger.saturating_add(INSTANCE_TTL_EXTEND_TO));
+    }
+
+    /// Extend the contract instance TTL; callable by anyone to keep the contract alive
+    pub fn bump_ttl(env: Env) {
+        Self::extend_instance_ttl(&env);
+    }
+
+    /// Helper: Reject calls made while trade_and_reward is mid cross-call, so a
+    /// reward contract cannot call back into any entrypoint that moves funds
+    fn check_not_reentrant(env: &Env) -> Result<(), TradingError> {
+        if TradingStorage::is_locked(env) {
+            return Err(TradingError::Reentrant);
+        }
+        Ok(())
+    }
+
+    /// Helper: Require `admin`'s auth and the admin role
+    fn require_admin(env: &Env, admin: &Address) -> Result<(), TradingError> {
+        Self::require_not_terminated(env)?;
+        admin.require_auth();
+
+        if !Self::has_admin_role(env, admin) {
+            return Err(TradingError::NotAdmin);
+        }
+        Ok(())
+    }
+
+    /// Helper: Require `address`'s auth and `role`; the admin holds every role
+    fn require_role(env: &Env, address: &Address, role: TradingRole) -> Result<(), TradingError> {
+        Self::require_not_terminated(env)?;
+        address.require_auth();
+
+        if !Self::has_admin_role(env, address) && !RoleManager::has_role(env, address, role) {
+            return Err(TradingError::Unauthorized);
+        }
+        Ok(())
+    }
+
+    /// Helper: Require `address`'s auth and a role that may pause, i.e. the
+    /// admin, a pauser or a guardian
+    fn require_pause_role(env: &Env, address: &Address) -> Result<(), TradingError> {
+        Self::require_not_terminated(env)?;
+        address.require_auth();
+
+        if !Self::has_admin_role(env, address)
+            && !RoleManager::has_role(env, address, TradingRole::Pauser)
+            && !RoleManager::has_role(env, address, TradingRole::Guardian)
+        {
+            return Err(TradingError::Unauthorized);
+        }
+        Ok(())
+    }
+
+    /// Helper: Reject the call while any of the operations in `flags` is paused
+    ///
+    /// Every mutating trade entrypoint must go through this check.
+    fn require_not_paused(env: &Env, flags: u32) -> Result<(), TradingError> {
+        Self::require_not_terminated(env)?;
+        if TradingStorage::is_operation_paused(env, flags) {
+            return Err(TradingError::Paused);
+        }
+        Ok(())
+    }
+
+    /// Helper: Reject a call that moves `token` while it is paused by `pause_token`
+    ///
+    /// Every entrypoint that takes fees or deposits in a token runs this
+    /// alongside `require_not_paused`.
+    fn require_token_not_paused(env: &Env, token: &Address) -> Result<(), TradingError> {
+        if TradingStorage::is_token_paused(env, token) {
+            return Err(TradingError::Paused);
+        }
+        Ok(())
+    }
+
+    /// Helper: Reject every mutating call once the contract has been terminated
+    ///
+    /// `require_admin`, `require_role` and `require_not_paused` run this
+    /// first; entrypoints gated by none of them call it directly.
+    fn require_not_terminated(env: &Env) -> Result<(), TradingError> {
+        if TradingStorage::is_terminated(env) {
+            return Err(TradingError::Terminated);
+        }
+        Ok(())
+    }
+
+    /// Helper: Persist roles for both the contract and the governance module
+    fn store_roles(env: &Env, roles: &soroban_sdk::Map<Address, GovernanceRole>) {
+        TradingStorage::set_roles(env, roles);
+
+        let roles_key = symbol_short!("roles");
+        env.storage().persistent().set(&roles_key, roles);
+    }
+
+    /// Propose handing the admin role over to `new_admin` (admin only)
+    ///
+    /// The current admin keeps full control until `new_admin` calls `accept_admin`.
+    pub fn propose_admin(env: Env, current_admin: Address, new_admin: Address) -> Result<(), TradingError> {
+        Self::require_admin(&env, &current_admin)?;
+
+        TradingStorage::set_pending_admin(&env, &PendingAdmin {
+            proposer: current_admin,
+            new_admin,
+        });
+
+        Ok(())
+    }
+
+    /// Accept a pending admin proposal; must be called by the proposed address
+    pub fn accept_admin(env: Env, new_admin: Address) -> Result<(), TradingError> {
+        Self::require_not_terminated(&env)?;
+        new_admin.require_auth();
+
+        let pending = TradingStorage::get_pending_admin(&env)
+            .ok_or(TradingError::NoPendingAdmin)?;
+
+        if pending.new_admin != new_admin {
+            return Err(TradingError::Unauthorized);
+        }
+
+        let mut roles = TradingStorage::get_roles(&env)
+            .ok_or(TradingError::NotInitialized)?;
+        roles.remove(pending.proposer);
+        roles.set(new_admin, GovernanceRole::Admin);
+        Self::store_roles(&env, &roles);
+
+        TradingStorage::clear_pending_admin(&env);
+
+        Ok(())
+    }
+
+    /// Cancel a pending admin proposal (admin only)
+    pub fn cancel_admin_proposal(env: Env, current_admin: Address) -> Result<(), TradingError> {
+        Self::require_admin(&env, &current_admin)?;
+
+        if TradingStorage::get_pending_admin(&env).is_none() {
+            return Err(TradingError::NoPendingAdmin);
+        }
+
+        TradingStorage::clear_pending_admin(&env);
+
+        Ok(())
+    }
+
+    /// Get the address awaiting acceptance as the new admin, if any
+    pub fn pending_admin(env: Env) -> Option<Address> {
+        TradingStorage::get_pending_admin(&env).map(|pending| pending.new_admin)
+    }
+
     /// Propose an upgrade via governance
     pub fn propose_upgrade(
         env: Env,
@@ -488,7 +4587,8 @@ impl UpgradeableTradingContract {
         approvers: soroban_sdk::Vec<Address>,
         approval_threshold: u32,
         timelock_delay: u64,
-    ) -> Result<u64, TradeError> {
+    ) -> Result<u64, TradingError> {
+        Self::require_not_terminated(&env)?;
         admin.require_auth();
 
         let proposal_result = GovernanceManager::propose_upgrade(
@@ -504,7 +4604,7 @@ impl UpgradeableTradingContract {
 
         match proposal_result {
             Ok(id) => Ok(id),
-            Err(_) => Err(TradeError::Unauthorized),
+            Err(_) => Err(TradingError::Unauthorized),
         }
     }
 
@@ -513,11 +4613,12 @@ impl UpgradeableTradingContract {
         env: Env,
         proposal_id: u64,
         approver: Address,
-    ) -> Result<(), TradeError> {
+    ) -> Result<(), TradingError> {
+        Self::require_not_terminated(&env)?;
         approver.require_auth();
 
         GovernanceManager::approve_proposal(&env, proposal_id, approver)
-            .map_err(|_| TradeError::Unauthorized)
+            .map_err(|_| TradingError::Unauthorized)
     }
 
     /// Execute an approved upgrade proposal
@@ -525,17 +4626,18 @@ impl UpgradeableTradingContract {
         env: Env,
         proposal_id: u64,
         executor: Address,
-    ) -> Result<(), TradeError> {
+    ) -> Result<(), TradingError> {
+        Self::require_not_terminated(&env)?;
         executor.require_auth();
 
         GovernanceManager::execute_proposal(&env, proposal_id, executor)
-            .map_err(|_| TradeError::Unauthorized)
+            .map_err(|_| TradingError::Unauthorized)
     }
 
     /// Get upgrade proposal details
-    pub fn get_upgrade_proposal(env: Env, proposal_id: u64) -> Result<UpgradeProposal, TradeError> {
+    pub fn get_upgrade_proposal(env: Env, proposal_id: u64) -> Result<UpgradeProposal, TradingError> {
         GovernanceManager::get_proposal(&env, proposal_id)
-            .map_err(|_| TradeError::Unauthorized)
+            .map_err(|_| TradingError::Unauthorized)
     }
 
     /// Reject an upgrade proposal
@@ -543,11 +4645,12 @@ impl UpgradeableTradingContract {
         env: Env,
         proposal_id: u64,
         rejector: Address,
-    ) -> Result<(), TradeError> {
+    ) -> Result<(), TradingError> {
+        Self::require_not_terminated(&env)?;
         rejector.require_auth();
 
         GovernanceManager::reject_proposal(&env, proposal_id, rejector)
-            .map_err(|_| TradeError::Unauthorized)
+            .map_err(|_| TradingError::Unauthorized)
     }
 
     /// Cancel an upgrade proposal (admin only)
@@ -555,11 +4658,12 @@ impl UpgradeableTradingContract {
         env: Env,
         proposal_id: u64,
         admin: Address,
-    ) -> Result<(), TradeError> {
+    ) -> Result<(), TradingError> {
+        Self::require_not_terminated(&env)?;
         admin.require_auth();
 
         GovernanceManager::cancel_proposal(&env, proposal_id, admin)
-            .map_err(|_| TradeError::Unauthorized)
+            .map_err(|_| TradingError::Unauthorized)
     }
 }
 
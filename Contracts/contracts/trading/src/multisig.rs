@@ -0,0 +1,67 @@
+//! M-of-N admin signers for the trading contract
+//!
+//! Once signers are configured, sensitive admin actions are proposed by one
+//! signer and run only after `threshold` distinct signers have approved,
+//! so no single key can change fees or swap the contract code.
+
+use soroban_sdk::{contracttype, Address, BytesN, Env, Vec};
+
+use crate::storage::TradingDataKey;
+
+/// Admin action that requires signer approval
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum AdminAction {
+    SetFeeBps(u32),
+    Upgrade(BytesN<32>),
+}
+
+/// Proposed admin action and the signers that approved it
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingAction {
+    pub action: AdminAction,
+    pub approvals: Vec<Address>,
+    pub executed: bool,
+}
+
+/// Signer set and action queue backed by instance and persistent storage
+pub struct MultisigManager;
+
+impl MultisigManager {
+    pub fn signers(env: &Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&TradingDataKey::AdminSigners)
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    /// Approvals required to run an action; 0 while no signers are configured
+    pub fn threshold(env: &Env) -> u32 {
+        env.storage().instance().get(&TradingDataKey::AdminThreshold).unwrap_or(0)
+    }
+
+    pub fn set_signers(env: &Env, signers: &Vec<Address>, threshold: u32) {
+        env.storage().instance().set(&TradingDataKey::AdminSigners, signers);
+        env.storage().instance().set(&TradingDataKey::AdminThreshold, &threshold);
+    }
+
+    pub fn is_signer(env: &Env, address: &Address) -> bool {
+        Self::signers(env).contains(address)
+    }
+
+    pub fn get_action(env: &Env, action_id: u64) -> Option<PendingAction> {
+        env.storage().persistent().get(&TradingDataKey::AdminAction(action_id))
+    }
+
+    pub fn set_action(env: &Env, action_id: u64, action: &PendingAction) {
+        env.storage().persistent().set(&TradingDataKey::AdminAction(action_id), action);
+    }
+
+    /// Allocate the next action id
+    pub fn next_action_id(env: &Env) -> u64 {
+        let id: u64 = env.storage().instance().get(&TradingDataKey::NextAdminActionId).unwrap_or(1);
+        env.storage().instance().set(&TradingDataKey::NextAdminActionId, &(id + 1));
+        id
+    }
+}
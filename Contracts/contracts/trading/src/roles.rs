@@ -0,0 +1,50 @@
+//! Operational roles for the trading contract
+//!
+//! Governance roles (admin, approver, executor) control upgrades. The roles
+//! here grant narrower operational powers, such as pausing, so those keys do
+//! not have to be shared with the admin.
+
+use soroban_sdk::{contracttype, Address, Env, Vec};
+
+use crate::storage::TradingDataKey;
+
+/// Operational role that can be granted by the admin
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum TradingRole {
+    Pauser = 0,       // Can pause and unpause trading
+    Guardian = 1,     // Can pause but never unpause, for automated monitors
+}
+
+/// Role membership manager backed by instance storage
+pub struct RoleManager;
+
+impl RoleManager {
+    fn members(env: &Env, role: TradingRole) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&TradingDataKey::RoleMembers(role))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    pub fn has_role(env: &Env, address: &Address, role: TradingRole) -> bool {
+        Self::members(env, role).contains(address)
+    }
+
+    pub fn grant_role(env: &Env, address: &Address, role: TradingRole) {
+        let mut members = Self::members(env, role);
+        if !members.contains(address) {
+            members.push_back(address.clone());
+            env.storage().instance().set(&TradingDataKey::RoleMembers(role), &members);
+        }
+    }
+
+    pub fn revoke_role(env: &Env, address: &Address, role: TradingRole) {
+        let mut members = Self::members(env, role);
+        if let Some(index) = members.first_index_of(address) {
+            members.remove(index);
+            env.storage().instance().set(&TradingDataKey::RoleMembers(role), &members);
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! Trades committed now and executed at a future ledger
+//!
+//! `schedule_trade` records the intent and, if asked, escrows the fee in the
+//! contract. Anyone can execute it once `execute_at` is reached, settling it
+//! like a regular trade; until then the trader can cancel it and get any
+//! escrowed fee back.
+
+use soroban_sdk::{contracttype, Address, Env, Symbol};
+
+use crate::storage::TradingConfigKey;
+
+/// Lifecycle of a scheduled trade
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScheduleStatus {
+    Pending,   // Waiting for execute_scheduled or cancel_scheduled
+    Executed,  // Fee was paid to the recipient
+    Cancelled, // Cancelled by the trader; any escrowed fee was returned
+}
+
+/// Trade intent recorded by `schedule_trade`
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScheduledTrade {
+    pub trader: Address,
+    pub pair: Symbol,
+    pub amount: i128,
+    pub price: i128,
+    pub is_buy: bool,
+    pub fee_token: Address,
+    pub fee: i128,
+    pub recipient: Address,
+    /// First ledger sequence at which the trade can be executed
+    pub execute_at: u32,
+    /// Whether the fee is already held by the contract; otherwise it is
+    /// pulled from the trader's allowance on execution
+    pub escrowed: bool,
+    pub status: ScheduleStatus,
+}
+
+/// Scheduled trade records
+pub struct ScheduleManager;
+
+impl ScheduleManager {
+    pub fn get(env: &Env, schedule_id: u64) -> Option<ScheduledTrade> {
+        env.storage().persistent().get(&TradingConfigKey::ScheduledTrade(schedule_id))
+    }
+
+    pub fn set(env: &Env, schedule_id: u64, record: &ScheduledTrade) {
+        env.storage().persistent().set(&TradingConfigKey::ScheduledTrade(schedule_id), record);
+    }
+
+    /// Allocate the next scheduled trade id
+    pub fn next_id(env: &Env) -> u64 {
+        let id: u64 = env.storage().instance().get(&TradingConfigKey::NextScheduleId).unwrap_or(1);
+        env.storage().instance().set(&TradingConfigKey::NextScheduleId, &(id + 1));
+        id
+    }
+}
@@ -8,6 +8,9 @@
 
 use soroban_sdk::{contracttype, Address, Env, Symbol, Vec, symbol_short};
 
+use crate::roles::TradingRole;
+use crate::{TradingError, PAUSE_ALL};
+
 /// Contract version for migration tracking
 #[allow(dead_code)]
 const CONTRACT_VERSION: u32 = 2;
@@ -66,19 +69,204 @@ pub struct OptimizedTrade {
     pub is_buy: bool,
 }
 
+/// Privileged action recorded in the admin log
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AdminLogEntry {
+    pub actor: Address,
+    pub action: Symbol,
+    pub ledger: u32,
+    pub detail: i128,     // Action-specific value, e.g. the new fee bps; 0 if none
+}
+
+/// Trade summary kept in the recent-trades ring buffer
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TradeRecord {
+    pub trade_id: u64,
+    pub trader: Address,
+    pub pair: Symbol,
+    pub amount: i128,
+    pub price: i128,
+    pub is_buy: bool,
+    pub fee_token: Address,
+    pub fee_paid: i128,
+    pub settle_token: Address,
+    pub ledger: u32,
+    pub timestamp: u64,
+}
+
+/// Admin handover awaiting acceptance by the proposed address
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingAdmin {
+    pub proposer: Address,
+    pub new_admin: Address,
+}
+
+/// Admin parameter change that can be held back by the timelock
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParamChange {
+    FeeBps(u32),
+    FeeBounds(i128, i128),
+    AllowFeeToken(Address),
+    AllowRewardContract(Address),
+    TimelockDelay(u32),
+    NativeToken(Address),
+    TokenFeeBps(Address, u32),
+    MinNonzeroFee(i128),
+    BurnBps(u32),
+    UnpauseDelay(u32),
+    RebateBps(u32),
+    MinFeeMode(MinFeeMode),
+}
+
+/// Where single-trade fees are sent
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeeSinkMode {
+    Direct, // Transferred straight to the trade's fee recipient
+    Accrue, // Held by the contract until withdrawn by the admin
+}
+
+/// Where the minimum nonzero fee comes from
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MinFeeMode {
+    Fixed,               // The stored min_nonzero_fee, in every token
+    DecimalDerived(u32), // 10^(decimals - n) of the fee token
+}
+
+/// Badge earned by a trader's lifetime fee volume
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Tier {
+    None,   // Below the first threshold, or tiers are not configured
+    Bronze, // Reached the first threshold
+    Silver, // Reached the second threshold
+    Gold,   // Reached the third threshold
+}
+
+/// How much the per-trade events carry
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventMode {
+    Full,    // Topic and full event data
+    Minimal, // Topic only, with empty data
+    Off,     // No per-trade events
+}
+
+/// Queued parameter change and the ledger it may be executed from
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingParamChange {
+    pub change: ParamChange,
+    pub execute_after: u32,
+}
+
 /// Governance roles storage key
 #[contracttype]
 #[derive(Clone, Debug)]
 pub enum TradingDataKey {
     Init,
-    Paused,
+    Paused,               // Legacy single pause flag, superseded by PauseFlags
     Stats,
     OracleConfig,
     OracleStatus,
     Roles,
     Trade(u64),           // Individual trade by ID
     TradeIdsByTrader(Address), // List of trade IDs for a trader
-    RecentTrades,         // Recent trade IDs (circular buffer)
+    RecentTrade(u32),     // Ring buffer slot holding a TradeRecord
+    RecentTradesWritten,  // Total records ever written to the ring buffer
+    FeeBps,               // Fee rate applied to trade amounts, in basis points
+    TokenFeeBps(Address), // Per-fee-token override of FeeBps
+    FeeBounds,            // (min_fee, max_fee) clamp applied after the bps computation
+    MinNonzeroFee,        // Fee charged when a nonzero bps fee rounds down to zero (0 = disabled)
+    FeeHoliday,           // (start_ledger, end_ledger) window in which trades are fee-free
+    RewardMultiplier,     // (multiplier_bps, start_ledger, end_ledger) reward boost campaign
+    ReferralBps,          // Share of a trader's reward granted to their referrer, in basis points
+    DiscountTiers,        // (min_reward_balance, discount_bps) fee discount tiers
+    DiscountSource,       // Reward contract whose balances drive fee discounts
+    TimelockDelay,        // Ledgers a queued parameter change must wait before execution
+    PendingChange,        // Queued parameter change awaiting its timelock
+    PendingAdmin,         // Proposed admin awaiting acceptance
+    RoleMembers(TradingRole), // Addresses holding an operational role
+    AdminSigners,         // Signers for M-of-N admin actions
+    AdminThreshold,       // Approvals an admin action needs (0 = multisig off)
+    AdminAction(u64),     // Proposed admin action by ID
+    NextAdminActionId,    // Next admin action ID to allocate
+    ReentrancyLock,       // Set while trade_and_reward is mid cross-call (temporary storage)
+    RewardContract(Address), // Allowlisted reward contract for trade_and_reward
+    RewardContractCount,  // Number of allowlisted reward contracts
+    DefaultRewardContract, // Reward contract credited by trade_and_reward_default
+    FeeToken(Address),    // Allowlisted fee token
+    TokenDecimals(Address), // Decimals reported by a token, cached on first lookup
+    NativeToken,          // Stellar Asset Contract wrapping native XLM
+    FeeVolume(Address),   // Cumulative fees collected per fee token
+    SettleVolume(Address), // Cumulative trade amount per settle token
+    TagVolume(Symbol),    // Cumulative fees per trade attribution tag
+    FeeSink,              // FeeSinkMode for single-trade fees
+    AllowSelfTrade,       // Set to let trades route their fee back to the trader
+    AccruedFees(Address), // Fees held by the contract per token, awaiting withdrawal
+    DailyLimit,           // Max fee volume per trader per day bucket (0 = unlimited)
+    CircuitBreaker,       // Single-trade fee that auto-pauses trading (0 = disabled)
+    DailyVolume(Address, u64), // Fee volume routed by a trader within a day bucket
+    PauseReason,          // Reason given for the last pause change
+    PauseChangedAt,       // Ledger timestamp of the last pause change
+    PauseFlags,           // Bitmask of paused operations (PAUSE_TRADE, PAUSE_REWARD)
+    PauseUntil,           // Last ledger sequence covered by a self-expiring pause
+    Terminated,           // Set once by terminate(); never cleared
+    InstanceLiveUntil,    // Lower bound on the instance expiry ledger, kept by extend_instance_ttl
+    Nonce(Address),       // Next trade_with_nonce nonce expected from a trader
+}
+
+/// Storage keys added once `TradingDataKey` reached the 50-variant limit of
+/// a contract type; existing keys stay put so stored data keeps its encoding
+#[contracttype]
+#[derive(Clone, Debug)]
+pub enum TradingConfigKey {
+    FeeOracle,            // Contract queried for the live fee rate, overriding the stored bps
+    LastPauser,           // Address behind the last pause change
+    BurnBps,              // Share of each single-trade fee burned, in basis points
+    Escrow(u64),          // EscrowRecord by ID
+    NextEscrowId,         // Next escrow ID to allocate
+    EscrowHeld(Address),  // Fees per token currently held in escrow
+    Received(Address),    // (total, count) of trade fees delivered to a recipient
+    Deposit(Address, Address), // Pre-funded balance per (owner, token)
+    DepositsHeld(Address), // Sum of all pre-funded balances per token
+    UnpauseDelay,         // Ledgers an unpause waits before operations resume
+    PendingResume,        // (flags, resume_ledger) cleared from PauseFlags once the grace period ends
+    MaxRewardFailures,    // Consecutive committed failures that auto-disable a reward contract (0 = never)
+    RewardFailures(Address), // Consecutive committed add_reward failures per reward contract
+    EventMode,            // EventMode for per-trade events
+    RebateBps,            // Share of each single-trade fee paid into the rebate pool, in basis points
+    RebatePool(Address),  // Unclaimed rebates per fee token
+    RebateVolume(Address, Address), // Fees per (trader, token) that funded the pool since the trader's last claim
+    RebateVolumeTotal(Address), // Sum of all unclaimed rebate volume per token
+    RewardRequired,       // Whether a failed add_reward reverts trade_and_reward (default true)
+    MinTradeAmount,       // Smallest accepted trade amount (0 = no minimum)
+    Operator(Address, Address), // Whether (trader, operator) may trade_as the trader
+    MaxFeePct,            // Largest fee as bps of the payer's fee-token balance (0 = no cap)
+    Cooldown,             // Seconds a trader must wait between trades (0 = off)
+    LastTradeAt(Address), // Ledger timestamp of a trader's last trade while a cooldown is set
+    MinRewardVersion,     // Lowest reward_version() accepted by add_reward_contract (default 1)
+    FeeTokenList,         // Allowlisted fee tokens, indexed so the allowlist can be cleared
+    RewardContractList,   // Allowlisted reward contracts, indexed so the allowlist can be cleared
+    TokenPaused(Address), // Set while trades in a token are paused, independent of the global pause
+    PausedTokenList,      // Tokens paused by pause_token, indexed so reset_config can unpause them
+    AdminLog(u32),        // Ring buffer slot holding an AdminLogEntry
+    AdminLogWritten,      // Total entries ever written to the admin log
+    FallbackRecipient,    // Paid single-trade fees when the token rejects the transfer to the recipient
+    VerboseErrors,        // Whether trade_and_reward_verbose returns raw reward contract errors
+    MinRewardRatio,       // Smallest trade_and_reward reward as bps of the fee (0 = no minimum)
+    MinFeeMode,           // MinFeeMode for the minimum nonzero fee
+    ScheduledTrade(u64),  // ScheduledTrade by ID
+    NextScheduleId,       // Next scheduled trade ID to allocate
+    TierThresholds,       // Ascending lifetime fee volumes for Bronze, Silver and Gold
+    TraderFeeVolume(Address), // Lifetime fees paid by a trader across all fee tokens
+    TraderTier(Address),  // Tier last computed for a trader
+    Relayer(Address, Address), // Whether (trader, relayer) may trade_with_allowance for the trader
 }
 
 /// Storage manager for trading contract
@@ -138,12 +326,391 @@ impl TradingStorage {
     
     // ============ Pause State ============
     
+    /// True if any operation is paused
     pub fn is_paused(env: &Env) -> bool {
-        env.storage().instance().get(&TradingDataKey::Paused).unwrap_or(false)
+        Self::get_pause_flags(env) != 0
     }
     
     pub fn set_paused(env: &Env, paused: bool) {
-        env.storage().instance().set(&TradingDataKey::Paused, &paused);
+        Self::set_pause_flags(env, if paused { PAUSE_ALL } else { 0 });
+    }
+    
+    pub fn is_terminated(env: &Env) -> bool {
+        env.storage().instance().has(&TradingDataKey::Terminated)
+    }
+    
+    pub fn set_terminated(env: &Env) {
+        env.storage().instance().set(&TradingDataKey::Terminated, &true);
+    }
+    
+    pub fn is_token_paused(env: &Env, token: &Address) -> bool {
+        env.storage().persistent().has(&TradingConfigKey::TokenPaused(token.clone()))
+    }
+    
+    pub fn set_token_paused(env: &Env, token: &Address, paused: bool) {
+        let key = TradingConfigKey::TokenPaused(token.clone());
+        if paused {
+            env.storage().persistent().set(&key, &true);
+            Self::add_to_list(env, &TradingConfigKey::PausedTokenList, token);
+        } else {
+            env.storage().persistent().remove(&key);
+            Self::remove_from_list(env, &TradingConfigKey::PausedTokenList, token);
+        }
+    }
+    
+    pub fn get_instance_live_until(env: &Env) -> u32 {
+        env.storage().instance().get(&TradingDataKey::InstanceLiveUntil).unwrap_or(0)
+    }
+    
+    pub fn set_instance_live_until(env: &Env, ledger: u32) {
+        env.storage().instance().set(&TradingDataKey::InstanceLiveUntil, &ledger);
+    }
+    
+    pub fn is_operation_paused(env: &Env, flag: u32) -> bool {
+        Self::get_pause_flags(env) & flag != 0
+    }
+    
+    pub fn get_pause_flags(env: &Env) -> u32 {
+        if let Some(until) = Self::get_pause_until(env) {
+            if env.ledger().sequence() > until {
+                return 0;
+            }
+        }
+        if let Some(flags) = env.storage().instance().get::<_, u32>(&TradingDataKey::PauseFlags) {
+            return match Self::get_pending_resume(env) {
+                Some((resumed, resume_ledger)) if env.ledger().sequence() >= resume_ledger => flags & !resumed,
+                _ => flags,
+            };
+        }
+        // Fall back to the legacy boolean written before per-operation flags
+        let legacy: bool = env.storage().instance().get(&TradingDataKey::Paused).unwrap_or(false);
+        if legacy { PAUSE_ALL } else { 0 }
+    }
+    
+    /// Any explicit flag change makes the pause indefinite again
+    pub fn set_pause_flags(env: &Env, flags: u32) {
+        env.storage().instance().set(&TradingDataKey::PauseFlags, &flags);
+        env.storage().instance().remove(&TradingDataKey::Paused);
+        env.storage().instance().remove(&TradingDataKey::PauseUntil);
+        env.storage().instance().remove(&TradingConfigKey::PendingResume);
+    }
+    
+    pub fn get_pending_resume(env: &Env) -> Option<(u32, u32)> {
+        env.storage().instance().get(&TradingConfigKey::PendingResume)
+    }
+    
+    pub fn set_pending_resume(env: &Env, flags: u32, resume_ledger: u32) {
+        env.storage().instance().set(&TradingConfigKey::PendingResume, &(flags, resume_ledger));
+    }
+    
+    pub fn get_unpause_delay(env: &Env) -> u32 {
+        env.storage().instance().get(&TradingConfigKey::UnpauseDelay).unwrap_or(0)
+    }
+    
+    pub fn set_unpause_delay(env: &Env, delay: u32) {
+        env.storage().instance().set(&TradingConfigKey::UnpauseDelay, &delay);
+    }
+    
+    pub fn get_pause_until(env: &Env) -> Option<u32> {
+        env.storage().instance().get(&TradingDataKey::PauseUntil)
+    }
+    
+    pub fn set_pause_until(env: &Env, until_ledger: u32) {
+        env.storage().instance().set(&TradingDataKey::PauseUntil, &until_ledger);
+    }
+    
+    pub fn get_pause_reason(env: &Env) -> Symbol {
+        env.storage().instance()
+            .get(&TradingDataKey::PauseReason)
+            .unwrap_or_else(|| Symbol::new(env, ""))
+    }
+    
+    pub fn get_pause_changed_at(env: &Env) -> u64 {
+        env.storage().instance().get(&TradingDataKey::PauseChangedAt).unwrap_or(0)
+    }
+    
+    pub fn get_last_pauser(env: &Env) -> Option<Address> {
+        env.storage().instance().get(&TradingConfigKey::LastPauser)
+    }
+    
+    pub fn set_pause_info(env: &Env, pauser: &Address, reason: &Symbol, changed_at: u64) {
+        env.storage().instance().set(&TradingConfigKey::LastPauser, pauser);
+        env.storage().instance().set(&TradingDataKey::PauseReason, reason);
+        env.storage().instance().set(&TradingDataKey::PauseChangedAt, &changed_at);
+    }
+    
+    // ============ Fee Configuration ============
+    
+    pub fn get_fee_bps(env: &Env) -> u32 {
+        env.storage().instance().get(&TradingDataKey::FeeBps).unwrap_or(0)
+    }
+    
+    pub fn set_fee_bps(env: &Env, bps: u32) {
+        env.storage().instance().set(&TradingDataKey::FeeBps, &bps);
+    }
+    
+    /// Fee rate for `token`: its override if one is set, else the global rate
+    pub fn get_token_fee_bps(env: &Env, token: &Address) -> u32 {
+        env.storage().persistent()
+            .get(&TradingDataKey::TokenFeeBps(token.clone()))
+            .unwrap_or_else(|| Self::get_fee_bps(env))
+    }
+    
+    pub fn set_token_fee_bps(env: &Env, token: &Address, bps: u32) {
+        env.storage().persistent().set(&TradingDataKey::TokenFeeBps(token.clone()), &bps);
+    }
+    
+    /// Fee clamp as `(min_fee, max_fee)`; unbounded by default
+    pub fn get_fee_bounds(env: &Env) -> (i128, i128) {
+        env.storage().instance()
+            .get(&TradingDataKey::FeeBounds)
+            .unwrap_or((0, i128::MAX))
+    }
+    
+    pub fn set_fee_bounds(env: &Env, min_fee: i128, max_fee: i128) {
+        env.storage().instance().set(&TradingDataKey::FeeBounds, &(min_fee, max_fee));
+    }
+    
+    pub fn get_min_nonzero_fee(env: &Env) -> i128 {
+        env.storage().instance().get(&TradingDataKey::MinNonzeroFee).unwrap_or(0)
+    }
+    
+    pub fn set_min_nonzero_fee(env: &Env, fee: i128) {
+        env.storage().instance().set(&TradingDataKey::MinNonzeroFee, &fee);
+    }
+    
+    pub fn get_min_fee_mode(env: &Env) -> MinFeeMode {
+        env.storage().instance().get(&TradingConfigKey::MinFeeMode).unwrap_or(MinFeeMode::Fixed)
+    }
+    
+    pub fn set_min_fee_mode(env: &Env, mode: MinFeeMode) {
+        env.storage().instance().set(&TradingConfigKey::MinFeeMode, &mode);
+    }
+    
+    pub fn get_fee_holiday(env: &Env) -> Option<(u32, u32)> {
+        env.storage().instance().get(&TradingDataKey::FeeHoliday)
+    }
+    
+    pub fn set_fee_holiday(env: &Env, start_ledger: u32, end_ledger: u32) {
+        env.storage().instance().set(&TradingDataKey::FeeHoliday, &(start_ledger, end_ledger));
+    }
+    
+    pub fn get_reward_multiplier(env: &Env) -> Option<(u32, u32, u32)> {
+        env.storage().instance().get(&TradingDataKey::RewardMultiplier)
+    }
+    
+    pub fn set_reward_multiplier(env: &Env, multiplier_bps: u32, start_ledger: u32, end_ledger: u32) {
+        env.storage().instance().set(&TradingDataKey::RewardMultiplier, &(multiplier_bps, start_ledger, end_ledger));
+    }
+    
+    pub fn get_referral_bps(env: &Env) -> u32 {
+        env.storage().instance().get(&TradingDataKey::ReferralBps).unwrap_or(0)
+    }
+    
+    pub fn set_referral_bps(env: &Env, bps: u32) {
+        env.storage().instance().set(&TradingDataKey::ReferralBps, &bps);
+    }
+    
+    pub fn get_discount_tiers(env: &Env) -> Vec<(i128, u32)> {
+        env.storage().instance()
+            .get(&TradingDataKey::DiscountTiers)
+            .unwrap_or_else(|| Vec::new(env))
+    }
+    
+    pub fn set_discount_tiers(env: &Env, tiers: &Vec<(i128, u32)>) {
+        env.storage().instance().set(&TradingDataKey::DiscountTiers, tiers);
+    }
+    
+    pub fn get_discount_source(env: &Env) -> Option<Address> {
+        env.storage().instance().get(&TradingDataKey::DiscountSource)
+    }
+    
+    pub fn set_discount_source(env: &Env, source: &Option<Address>) {
+        match source {
+            Some(reward_id) => env.storage().instance().set(&TradingDataKey::DiscountSource, reward_id),
+            None => env.storage().instance().remove(&TradingDataKey::DiscountSource),
+        }
+    }
+    
+    pub fn get_burn_bps(env: &Env) -> u32 {
+        env.storage().instance().get(&TradingConfigKey::BurnBps).unwrap_or(0)
+    }
+    
+    pub fn set_burn_bps(env: &Env, bps: u32) {
+        env.storage().instance().set(&TradingConfigKey::BurnBps, &bps);
+    }
+    
+    pub fn get_cooldown(env: &Env) -> u64 {
+        env.storage().instance().get(&TradingConfigKey::Cooldown).unwrap_or(0)
+    }
+    
+    pub fn set_cooldown(env: &Env, seconds: u64) {
+        env.storage().instance().set(&TradingConfigKey::Cooldown, &seconds);
+    }
+    
+    pub fn get_last_trade_at(env: &Env, trader: &Address) -> Option<u64> {
+        env.storage().persistent().get(&TradingConfigKey::LastTradeAt(trader.clone()))
+    }
+    
+    pub fn set_last_trade_at(env: &Env, trader: &Address, timestamp: u64) {
+        env.storage().persistent().set(&TradingConfigKey::LastTradeAt(trader.clone()), &timestamp);
+    }
+    
+    pub fn clear_last_trade_at(env: &Env, trader: &Address) {
+        env.storage().persistent().remove(&TradingConfigKey::LastTradeAt(trader.clone()));
+    }
+    
+    pub fn get_max_fee_pct(env: &Env) -> u32 {
+        env.storage().instance().get(&TradingConfigKey::MaxFeePct).unwrap_or(0)
+    }
+    
+    pub fn set_max_fee_pct(env: &Env, bps: u32) {
+        env.storage().instance().set(&TradingConfigKey::MaxFeePct, &bps);
+    }
+    
+    pub fn get_min_trade_amount(env: &Env) -> i128 {
+        env.storage().instance().get(&TradingConfigKey::MinTradeAmount).unwrap_or(0)
+    }
+    
+    pub fn set_min_trade_amount(env: &Env, amount: i128) {
+        env.storage().instance().set(&TradingConfigKey::MinTradeAmount, &amount);
+    }
+    
+    pub fn get_min_reward_version(env: &Env) -> u32 {
+        env.storage().instance().get(&TradingConfigKey::MinRewardVersion).unwrap_or(1)
+    }
+    
+    pub fn set_min_reward_version(env: &Env, version: u32) {
+        env.storage().instance().set(&TradingConfigKey::MinRewardVersion, &version);
+    }
+    
+    pub fn get_tier_thresholds(env: &Env) -> Vec<i128> {
+        env.storage().instance()
+            .get(&TradingConfigKey::TierThresholds)
+            .unwrap_or_else(|| Vec::new(env))
+    }
+    
+    pub fn set_tier_thresholds(env: &Env, thresholds: &Vec<i128>) {
+        env.storage().instance().set(&TradingConfigKey::TierThresholds, thresholds);
+    }
+    
+    pub fn get_trader_fee_volume(env: &Env, trader: &Address) -> i128 {
+        env.storage().persistent()
+            .get(&TradingConfigKey::TraderFeeVolume(trader.clone()))
+            .unwrap_or(0)
+    }
+    
+    pub fn add_trader_fee_volume(env: &Env, trader: &Address, amount: i128) -> Result<i128, TradingError> {
+        let total = Self::get_trader_fee_volume(env, trader)
+            .checked_add(amount)
+            .ok_or(TradingError::ArithmeticOverflow)?;
+        env.storage().persistent().set(&TradingConfigKey::TraderFeeVolume(trader.clone()), &total);
+        Ok(total)
+    }
+    
+    pub fn get_trader_tier(env: &Env, trader: &Address) -> Tier {
+        env.storage().persistent()
+            .get(&TradingConfigKey::TraderTier(trader.clone()))
+            .unwrap_or(Tier::None)
+    }
+    
+    pub fn set_trader_tier(env: &Env, trader: &Address, tier: Tier) {
+        env.storage().persistent().set(&TradingConfigKey::TraderTier(trader.clone()), &tier);
+    }
+    
+    pub fn get_min_reward_ratio(env: &Env) -> u32 {
+        env.storage().instance().get(&TradingConfigKey::MinRewardRatio).unwrap_or(0)
+    }
+    
+    pub fn set_min_reward_ratio(env: &Env, bps: u32) {
+        env.storage().instance().set(&TradingConfigKey::MinRewardRatio, &bps);
+    }
+    
+    pub fn is_verbose_errors(env: &Env) -> bool {
+        env.storage().instance().get(&TradingConfigKey::VerboseErrors).unwrap_or(false)
+    }
+    
+    pub fn set_verbose_errors(env: &Env, verbose: bool) {
+        env.storage().instance().set(&TradingConfigKey::VerboseErrors, &verbose);
+    }
+    
+    pub fn is_reward_required(env: &Env) -> bool {
+        env.storage().instance().get(&TradingConfigKey::RewardRequired).unwrap_or(true)
+    }
+    
+    pub fn set_reward_required(env: &Env, required: bool) {
+        env.storage().instance().set(&TradingConfigKey::RewardRequired, &required);
+    }
+    
+    pub fn get_rebate_bps(env: &Env) -> u32 {
+        env.storage().instance().get(&TradingConfigKey::RebateBps).unwrap_or(0)
+    }
+    
+    pub fn set_rebate_bps(env: &Env, bps: u32) {
+        env.storage().instance().set(&TradingConfigKey::RebateBps, &bps);
+    }
+    
+    pub fn get_fee_oracle(env: &Env) -> Option<Address> {
+        env.storage().instance().get(&TradingConfigKey::FeeOracle)
+    }
+    
+    pub fn set_fee_oracle(env: &Env, oracle: &Option<Address>) {
+        match oracle {
+            Some(oracle) => env.storage().instance().set(&TradingConfigKey::FeeOracle, oracle),
+            None => env.storage().instance().remove(&TradingConfigKey::FeeOracle),
+        }
+    }
+    
+    pub fn get_fallback_recipient(env: &Env) -> Option<Address> {
+        env.storage().instance().get(&TradingConfigKey::FallbackRecipient)
+    }
+    
+    pub fn set_fallback_recipient(env: &Env, recipient: &Option<Address>) {
+        match recipient {
+            Some(recipient) => env.storage().instance().set(&TradingConfigKey::FallbackRecipient, recipient),
+            None => env.storage().instance().remove(&TradingConfigKey::FallbackRecipient),
+        }
+    }
+    
+    pub fn get_default_reward_contract(env: &Env) -> Option<Address> {
+        env.storage().instance().get(&TradingDataKey::DefaultRewardContract)
+    }
+    
+    pub fn set_default_reward_contract(env: &Env, reward_id: &Option<Address>) {
+        match reward_id {
+            Some(reward_id) => env.storage().instance().set(&TradingDataKey::DefaultRewardContract, reward_id),
+            None => env.storage().instance().remove(&TradingDataKey::DefaultRewardContract),
+        }
+    }
+    
+    pub fn get_circuit_breaker(env: &Env) -> i128 {
+        env.storage().instance().get(&TradingDataKey::CircuitBreaker).unwrap_or(0)
+    }
+    
+    pub fn set_circuit_breaker(env: &Env, threshold: i128) {
+        env.storage().instance().set(&TradingDataKey::CircuitBreaker, &threshold);
+    }
+    
+    pub fn get_daily_limit(env: &Env) -> i128 {
+        env.storage().instance().get(&TradingDataKey::DailyLimit).unwrap_or(0)
+    }
+    
+    pub fn set_daily_limit(env: &Env, limit: i128) {
+        env.storage().instance().set(&TradingDataKey::DailyLimit, &limit);
+    }
+    
+    // ============ Reentrancy Lock (Temporary) ============
+    
+    pub fn is_locked(env: &Env) -> bool {
+        env.storage().temporary().has(&TradingDataKey::ReentrancyLock)
+    }
+    
+    pub fn set_locked(env: &Env, locked: bool) {
+        if locked {
+            env.storage().temporary().set(&TradingDataKey::ReentrancyLock, &true);
+        } else {
+            env.storage().temporary().remove(&TradingDataKey::ReentrancyLock);
+        }
     }
     
     // ============ Statistics ============
@@ -160,13 +727,245 @@ impl TradingStorage {
         env.storage().instance().set(&TradingDataKey::Stats, stats);
     }
     
-    pub fn increment_trade_stats(env: &Env, amount: i128) -> u64 {
+    pub fn increment_trade_stats(env: &Env, amount: i128) -> Result<u64, TradingError> {
         let mut stats = Self::get_stats(env);
+        stats.total_volume = stats.total_volume
+            .checked_add(amount)
+            .ok_or(TradingError::ArithmeticOverflow)?;
         stats.last_trade_id += 1;
         stats.total_trades += 1;
-        stats.total_volume += amount;
         Self::set_stats(env, &stats);
-        stats.last_trade_id
+        Ok(stats.last_trade_id)
+    }
+    
+    pub fn get_fee_volume(env: &Env, token: &Address) -> i128 {
+        env.storage().persistent()
+            .get(&TradingDataKey::FeeVolume(token.clone()))
+            .unwrap_or(0)
+    }
+    
+    pub fn add_fee_volume(env: &Env, token: &Address, amount: i128) -> Result<(), TradingError> {
+        let total = Self::get_fee_volume(env, token)
+            .checked_add(amount)
+            .ok_or(TradingError::ArithmeticOverflow)?;
+        env.storage().persistent().set(&TradingDataKey::FeeVolume(token.clone()), &total);
+        Ok(())
+    }
+    
+    pub fn get_tag_volume(env: &Env, tag: &Symbol) -> i128 {
+        env.storage().persistent()
+            .get(&TradingDataKey::TagVolume(tag.clone()))
+            .unwrap_or(0)
+    }
+    
+    pub fn add_tag_volume(env: &Env, tag: &Symbol, amount: i128) -> Result<(), TradingError> {
+        let total = Self::get_tag_volume(env, tag)
+            .checked_add(amount)
+            .ok_or(TradingError::ArithmeticOverflow)?;
+        env.storage().persistent().set(&TradingDataKey::TagVolume(tag.clone()), &total);
+        Ok(())
+    }
+    
+    pub fn get_received(env: &Env, recipient: &Address) -> (i128, u64) {
+        env.storage().persistent()
+            .get(&TradingConfigKey::Received(recipient.clone()))
+            .unwrap_or((0, 0))
+    }
+    
+    /// Add `amount` over `count` trades to `recipient`'s totals; negative
+    /// values undo a refunded trade
+    pub fn add_received(env: &Env, recipient: &Address, amount: i128, count: i64) -> Result<(), TradingError> {
+        let (total, trades) = Self::get_received(env, recipient);
+        let total = total.checked_add(amount).ok_or(TradingError::ArithmeticOverflow)?;
+        let trades = trades.checked_add_signed(count).ok_or(TradingError::ArithmeticOverflow)?;
+        env.storage().persistent().set(&TradingConfigKey::Received(recipient.clone()), &(total, trades));
+        Ok(())
+    }
+    
+    // ============ Deposits (Persistent) ============
+    
+    pub fn get_deposit(env: &Env, owner: &Address, token: &Address) -> i128 {
+        env.storage().persistent()
+            .get(&TradingConfigKey::Deposit(owner.clone(), token.clone()))
+            .unwrap_or(0)
+    }
+    
+    /// Adjust `owner`'s pre-funded `token` balance by `delta`
+    ///
+    /// Only the per-owner balance changes; `add_deposits_held` tracks the
+    /// tokens that actually entered or left the contract.
+    pub fn add_deposit(env: &Env, owner: &Address, token: &Address, delta: i128) -> Result<i128, TradingError> {
+        let balance = Self::get_deposit(env, owner, token)
+            .checked_add(delta)
+            .ok_or(TradingError::ArithmeticOverflow)?;
+        if balance < 0 {
+            return Err(TradingError::InsufficientBalance);
+        }
+        env.storage().persistent().set(&TradingConfigKey::Deposit(owner.clone(), token.clone()), &balance);
+        Ok(balance)
+    }
+    
+    pub fn get_deposits_held(env: &Env, token: &Address) -> i128 {
+        env.storage().persistent()
+            .get(&TradingConfigKey::DepositsHeld(token.clone()))
+            .unwrap_or(0)
+    }
+    
+    pub fn add_deposits_held(env: &Env, token: &Address, delta: i128) -> Result<(), TradingError> {
+        let total = Self::get_deposits_held(env, token)
+            .checked_add(delta)
+            .ok_or(TradingError::ArithmeticOverflow)?;
+        env.storage().persistent().set(&TradingConfigKey::DepositsHeld(token.clone()), &total);
+        Ok(())
+    }
+    
+    // ============ Operators (Persistent) ============
+    
+    pub fn is_operator(env: &Env, trader: &Address, operator: &Address) -> bool {
+        env.storage().persistent()
+            .has(&TradingConfigKey::Operator(trader.clone(), operator.clone()))
+    }
+    
+    pub fn set_operator(env: &Env, trader: &Address, operator: &Address, allowed: bool) {
+        let key = TradingConfigKey::Operator(trader.clone(), operator.clone());
+        if allowed {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+    }
+    
+    pub fn is_relayer(env: &Env, trader: &Address, relayer: &Address) -> bool {
+        env.storage().persistent()
+            .has(&TradingConfigKey::Relayer(trader.clone(), relayer.clone()))
+    }
+    
+    pub fn set_relayer(env: &Env, trader: &Address, relayer: &Address, allowed: bool) {
+        let key = TradingConfigKey::Relayer(trader.clone(), relayer.clone());
+        if allowed {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+    }
+    
+    // ============ Rebate Pool (Persistent) ============
+    
+    pub fn get_rebate_pool(env: &Env, token: &Address) -> i128 {
+        env.storage().persistent()
+            .get(&TradingConfigKey::RebatePool(token.clone()))
+            .unwrap_or(0)
+    }
+    
+    pub fn add_rebate_pool(env: &Env, token: &Address, delta: i128) -> Result<(), TradingError> {
+        let total = Self::get_rebate_pool(env, token)
+            .checked_add(delta)
+            .ok_or(TradingError::ArithmeticOverflow)?;
+        env.storage().persistent().set(&TradingConfigKey::RebatePool(token.clone()), &total);
+        Ok(())
+    }
+    
+    /// Get `(trader_volume, total_volume)` of unclaimed rebate volume for `token`
+    pub fn get_rebate_volume(env: &Env, trader: &Address, token: &Address) -> (i128, i128) {
+        let volume = env.storage().persistent()
+            .get(&TradingConfigKey::RebateVolume(trader.clone(), token.clone()))
+            .unwrap_or(0);
+        let total = env.storage().persistent()
+            .get(&TradingConfigKey::RebateVolumeTotal(token.clone()))
+            .unwrap_or(0);
+        (volume, total)
+    }
+    
+    /// Add `delta` to both `trader`'s and the total rebate volume for `token`
+    pub fn add_rebate_volume(env: &Env, trader: &Address, token: &Address, delta: i128) -> Result<(), TradingError> {
+        let (volume, total) = Self::get_rebate_volume(env, trader, token);
+        let volume = volume.checked_add(delta).ok_or(TradingError::ArithmeticOverflow)?;
+        let total = total.checked_add(delta).ok_or(TradingError::ArithmeticOverflow)?;
+        env.storage().persistent().set(&TradingConfigKey::RebateVolume(trader.clone(), token.clone()), &volume);
+        env.storage().persistent().set(&TradingConfigKey::RebateVolumeTotal(token.clone()), &total);
+        Ok(())
+    }
+    
+    pub fn get_settle_volume(env: &Env, token: &Address) -> i128 {
+        env.storage().persistent()
+            .get(&TradingDataKey::SettleVolume(token.clone()))
+            .unwrap_or(0)
+    }
+    
+    pub fn add_settle_volume(env: &Env, token: &Address, amount: i128) -> Result<(), TradingError> {
+        let total = Self::get_settle_volume(env, token)
+            .checked_add(amount)
+            .ok_or(TradingError::ArithmeticOverflow)?;
+        env.storage().persistent().set(&TradingDataKey::SettleVolume(token.clone()), &total);
+        Ok(())
+    }
+    
+    pub fn get_token_decimals(env: &Env, token: &Address) -> Option<u32> {
+        env.storage().persistent().get(&TradingDataKey::TokenDecimals(token.clone()))
+    }
+    
+    pub fn set_token_decimals(env: &Env, token: &Address, decimals: u32) {
+        env.storage().persistent().set(&TradingDataKey::TokenDecimals(token.clone()), &decimals);
+    }
+    
+    pub fn get_fee_sink(env: &Env) -> FeeSinkMode {
+        env.storage().instance().get(&TradingDataKey::FeeSink).unwrap_or(FeeSinkMode::Direct)
+    }
+    
+    pub fn set_fee_sink(env: &Env, mode: FeeSinkMode) {
+        env.storage().instance().set(&TradingDataKey::FeeSink, &mode);
+    }
+    
+    pub fn is_self_trade_allowed(env: &Env) -> bool {
+        env.storage().instance().get(&TradingDataKey::AllowSelfTrade).unwrap_or(false)
+    }
+    
+    pub fn set_self_trade_allowed(env: &Env, allowed: bool) {
+        env.storage().instance().set(&TradingDataKey::AllowSelfTrade, &allowed);
+    }
+    
+    pub fn get_event_mode(env: &Env) -> EventMode {
+        env.storage().instance().get(&TradingConfigKey::EventMode).unwrap_or(EventMode::Full)
+    }
+    
+    pub fn set_event_mode(env: &Env, mode: EventMode) {
+        env.storage().instance().set(&TradingConfigKey::EventMode, &mode);
+    }
+    
+    pub fn get_accrued_fees(env: &Env, token: &Address) -> i128 {
+        env.storage().persistent()
+            .get(&TradingDataKey::AccruedFees(token.clone()))
+            .unwrap_or(0)
+    }
+    
+    pub fn add_accrued_fees(env: &Env, token: &Address, amount: i128) -> Result<(), TradingError> {
+        let total = Self::get_accrued_fees(env, token)
+            .checked_add(amount)
+            .ok_or(TradingError::ArithmeticOverflow)?;
+        env.storage().persistent().set(&TradingDataKey::AccruedFees(token.clone()), &total);
+        Ok(())
+    }
+    
+    // ============ Rate Limiting (Persistent) ============
+    
+    pub fn get_daily_volume(env: &Env, trader: &Address, day: u64) -> i128 {
+        env.storage().persistent()
+            .get(&TradingDataKey::DailyVolume(trader.clone(), day))
+            .unwrap_or(0)
+    }
+    
+    pub fn set_daily_volume(env: &Env, trader: &Address, day: u64, volume: i128) {
+        env.storage().persistent().set(&TradingDataKey::DailyVolume(trader.clone(), day), &volume);
+    }
+    
+    pub fn get_nonce(env: &Env, trader: &Address) -> u64 {
+        env.storage().persistent()
+            .get(&TradingDataKey::Nonce(trader.clone()))
+            .unwrap_or(0)
+    }
+    
+    pub fn set_nonce(env: &Env, trader: &Address, nonce: u64) {
+        env.storage().persistent().set(&TradingDataKey::Nonce(trader.clone()), &nonce);
     }
     
     // ============ Oracle Configuration ============
@@ -211,6 +1010,219 @@ impl TradingStorage {
         Self::get_roles(env)?.get(address.clone())
     }
     
+    // ============ Admin Transfer ============
+    
+    pub fn get_pending_admin(env: &Env) -> Option<PendingAdmin> {
+        env.storage().instance().get(&TradingDataKey::PendingAdmin)
+    }
+    
+    pub fn set_pending_admin(env: &Env, pending: &PendingAdmin) {
+        env.storage().instance().set(&TradingDataKey::PendingAdmin, pending);
+    }
+    
+    pub fn clear_pending_admin(env: &Env) {
+        env.storage().instance().remove(&TradingDataKey::PendingAdmin);
+    }
+    
+    // ============ Parameter Timelock ============
+    
+    pub fn get_timelock_delay(env: &Env) -> u32 {
+        env.storage().instance().get(&TradingDataKey::TimelockDelay).unwrap_or(0)
+    }
+    
+    pub fn set_timelock_delay(env: &Env, delay: u32) {
+        env.storage().instance().set(&TradingDataKey::TimelockDelay, &delay);
+    }
+    
+    pub fn get_pending_change(env: &Env) -> Option<PendingParamChange> {
+        env.storage().instance().get(&TradingDataKey::PendingChange)
+    }
+    
+    pub fn set_pending_change(env: &Env, pending: &PendingParamChange) {
+        env.storage().instance().set(&TradingDataKey::PendingChange, pending);
+    }
+    
+    pub fn clear_pending_change(env: &Env) {
+        env.storage().instance().remove(&TradingDataKey::PendingChange);
+    }
+    
+    // ============ Reward Contract Allowlist (Persistent) ============
+    
+    pub fn is_reward_contract_allowed(env: &Env, reward_id: &Address) -> bool {
+        env.storage().persistent().has(&TradingDataKey::RewardContract(reward_id.clone()))
+    }
+    
+    pub fn allow_reward_contract(env: &Env, reward_id: &Address) {
+        if Self::is_reward_contract_allowed(env, reward_id) {
+            return;
+        }
+        env.storage().persistent().set(&TradingDataKey::RewardContract(reward_id.clone()), &true);
+        Self::set_reward_contract_count(env, Self::get_reward_contract_count(env) + 1);
+        Self::add_to_list(env, &TradingConfigKey::RewardContractList, reward_id);
+    }
+    
+    pub fn disallow_reward_contract(env: &Env, reward_id: &Address) {
+        if !Self::is_reward_contract_allowed(env, reward_id) {
+            return;
+        }
+        env.storage().persistent().remove(&TradingDataKey::RewardContract(reward_id.clone()));
+        Self::set_reward_contract_count(env, Self::get_reward_contract_count(env) - 1);
+        Self::remove_from_list(env, &TradingConfigKey::RewardContractList, reward_id);
+    }
+    
+    pub fn get_max_reward_failures(env: &Env) -> u32 {
+        env.storage().instance().get(&TradingConfigKey::MaxRewardFailures).unwrap_or(0)
+    }
+    
+    pub fn set_max_reward_failures(env: &Env, max: u32) {
+        env.storage().instance().set(&TradingConfigKey::MaxRewardFailures, &max);
+    }
+    
+    pub fn get_reward_failures(env: &Env, reward_id: &Address) -> u32 {
+        env.storage().persistent()
+            .get(&TradingConfigKey::RewardFailures(reward_id.clone()))
+            .unwrap_or(0)
+    }
+    
+    pub fn set_reward_failures(env: &Env, reward_id: &Address, failures: u32) {
+        let key = TradingConfigKey::RewardFailures(reward_id.clone());
+        if failures == 0 {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, &failures);
+        }
+    }
+    
+    pub fn get_reward_contract_count(env: &Env) -> u32 {
+        env.storage().instance().get(&TradingDataKey::RewardContractCount).unwrap_or(0)
+    }
+    
+    fn set_reward_contract_count(env: &Env, count: u32) {
+        env.storage().instance().set(&TradingDataKey::RewardContractCount, &count);
+    }
+    
+    // ============ Fee Token Allowlist (Persistent) ============
+    
+    pub fn is_fee_token_allowed(env: &Env, token: &Address) -> bool {
+        env.storage().persistent().has(&TradingDataKey::FeeToken(token.clone()))
+    }
+    
+    pub fn get_native_token(env: &Env) -> Option<Address> {
+        env.storage().instance().get(&TradingDataKey::NativeToken)
+    }
+    
+    pub fn set_native_token(env: &Env, token: &Address) {
+        env.storage().instance().set(&TradingDataKey::NativeToken, token);
+    }
+    
+    pub fn allow_fee_token(env: &Env, token: &Address) {
+        env.storage().persistent().set(&TradingDataKey::FeeToken(token.clone()), &true);
+        Self::add_to_list(env, &TradingConfigKey::FeeTokenList, token);
+    }
+    
+    pub fn disallow_fee_token(env: &Env, token: &Address) {
+        env.storage().persistent().remove(&TradingDataKey::FeeToken(token.clone()));
+        Self::remove_from_list(env, &TradingConfigKey::FeeTokenList, token);
+    }
+    
+    pub fn get_fee_token_list(env: &Env) -> Vec<Address> {
+        Self::get_list(env, &TradingConfigKey::FeeTokenList)
+    }
+    
+    pub fn get_reward_contract_list(env: &Env) -> Vec<Address> {
+        Self::get_list(env, &TradingConfigKey::RewardContractList)
+    }
+    
+    /// Allowlist entries in insertion order
+    ///
+    /// Entries allowlisted before the index was introduced are not listed.
+    pub fn get_list(env: &Env, key: &TradingConfigKey) -> Vec<Address> {
+        env.storage().instance().get(key).unwrap_or_else(|| Vec::new(env))
+    }
+    
+    fn add_to_list(env: &Env, key: &TradingConfigKey, address: &Address) {
+        let mut list = Self::get_list(env, key);
+        if !list.contains(address) {
+            list.push_back(address.clone());
+            env.storage().instance().set(key, &list);
+        }
+    }
+    
+    fn remove_from_list(env: &Env, key: &TradingConfigKey, address: &Address) {
+        let mut list = Self::get_list(env, key);
+        if let Some(index) = list.first_index_of(address) {
+            list.remove(index);
+            env.storage().instance().set(key, &list);
+        }
+    }
+    
+    // ============ Config Reset ============
+    
+    /// Restore fee, limit and pause parameters to their defaults and empty
+    /// both allowlists
+    ///
+    /// Balances (accrued fees, deposits, escrows, rebate pools), trade
+    /// history, governance roles and the timelock delay are left untouched.
+    pub fn reset_config(env: &Env) {
+        let instance = env.storage().instance();
+        for key in [
+            TradingDataKey::FeeBps,
+            TradingDataKey::FeeBounds,
+            TradingDataKey::MinNonzeroFee,
+            TradingDataKey::FeeHoliday,
+            TradingDataKey::RewardMultiplier,
+            TradingDataKey::ReferralBps,
+            TradingDataKey::DiscountTiers,
+            TradingDataKey::DiscountSource,
+            TradingDataKey::DailyLimit,
+            TradingDataKey::CircuitBreaker,
+            TradingDataKey::PendingChange,
+            TradingDataKey::DefaultRewardContract,
+            TradingDataKey::NativeToken,
+            TradingDataKey::RewardContractCount,
+            TradingDataKey::FeeSink,
+            TradingDataKey::AllowSelfTrade,
+        ] {
+            instance.remove(&key);
+        }
+        for key in [
+            TradingConfigKey::FeeOracle,
+            TradingConfigKey::BurnBps,
+            TradingConfigKey::RebateBps,
+            TradingConfigKey::UnpauseDelay,
+            TradingConfigKey::RewardRequired,
+            TradingConfigKey::EventMode,
+            TradingConfigKey::MaxRewardFailures,
+            TradingConfigKey::MinTradeAmount,
+            TradingConfigKey::MaxFeePct,
+            TradingConfigKey::Cooldown,
+            TradingConfigKey::MinRewardVersion,
+            TradingConfigKey::FallbackRecipient,
+            TradingConfigKey::VerboseErrors,
+            TradingConfigKey::MinRewardRatio,
+            TradingConfigKey::MinFeeMode,
+            TradingConfigKey::TierThresholds,
+        ] {
+            instance.remove(&key);
+        }
+        Self::set_pause_flags(env, 0);
+        
+        for token in Self::get_list(env, &TradingConfigKey::FeeTokenList).iter() {
+            env.storage().persistent().remove(&TradingDataKey::FeeToken(token.clone()));
+            env.storage().persistent().remove(&TradingDataKey::TokenFeeBps(token));
+        }
+        for reward_id in Self::get_list(env, &TradingConfigKey::RewardContractList).iter() {
+            env.storage().persistent().remove(&TradingDataKey::RewardContract(reward_id.clone()));
+            env.storage().persistent().remove(&TradingConfigKey::RewardFailures(reward_id));
+        }
+        for token in Self::get_list(env, &TradingConfigKey::PausedTokenList).iter() {
+            env.storage().persistent().remove(&TradingConfigKey::TokenPaused(token));
+        }
+        instance.remove(&TradingConfigKey::FeeTokenList);
+        instance.remove(&TradingConfigKey::RewardContractList);
+        instance.remove(&TradingConfigKey::PausedTokenList);
+    }
+    
     // ============ Trade Storage (Persistent) ============
     
     /// Store individual trade - optimized for direct access by ID
@@ -220,9 +1232,6 @@ impl TradingStorage {
         
         // Update trader's trade list
         Self::add_trade_to_trader_index(env, &trade.trader, trade.id);
-        
-        // Update recent trades buffer
-        Self::add_to_recent_trades(env, trade.id);
     }
     
     /// Get trade by ID
@@ -268,41 +1277,66 @@ impl TradingStorage {
     
     // ============ Recent Trades Buffer ============
     
-    const MAX_RECENT_TRADES: u32 = 100;
+    /// Capacity of the ring buffer; the oldest record is overwritten once it is full
+    pub const MAX_RECENT_TRADES: u32 = 256;
     
-    fn add_to_recent_trades(env: &Env, trade_id: u64) {
-        let mut recent: Vec<u64> = env
-            .storage()
-            .persistent()
-            .get(&TradingDataKey::RecentTrades)
-            .unwrap_or_else(|| Vec::new(env));
-
-        recent.push_back(trade_id);
-
-        if recent.len() > Self::MAX_RECENT_TRADES {
-            let len = recent.len();
-            let keep = Self::MAX_RECENT_TRADES;
-            let start = len.saturating_sub(keep);
-            let mut trimmed = Vec::new(env);
-
-            for (i, id) in recent.iter().enumerate() {
-                if (i as u32) >= start {
-                    trimmed.push_back(id);
-                }
-            }
-
-            recent = trimmed;
+    /// Write `record` into the next ring buffer slot
+    pub fn push_recent_trade(env: &Env, record: &TradeRecord) {
+        let written = Self::get_recent_trades_written(env);
+        let slot = (written % Self::MAX_RECENT_TRADES as u64) as u32;
+        env.storage().persistent().set(&TradingDataKey::RecentTrade(slot), record);
+        env.storage().persistent().set(&TradingDataKey::RecentTradesWritten, &(written + 1));
+    }
+    
+    /// Get the record `age` trades before the newest one (0 = newest)
+    pub fn get_recent_trade(env: &Env, age: u32) -> Option<TradeRecord> {
+        let written = Self::get_recent_trades_written(env);
+        if age as u64 >= written || age >= Self::MAX_RECENT_TRADES {
+            return None;
         }
-
-        env.storage()
-            .persistent()
-            .set(&TradingDataKey::RecentTrades, &recent);
+        let slot = ((written - 1 - age as u64) % Self::MAX_RECENT_TRADES as u64) as u32;
+        env.storage().persistent().get(&TradingDataKey::RecentTrade(slot))
     }
     
-    pub fn get_recent_trade_ids(env: &Env) -> Vec<u64> {
-        env.storage().persistent()
-            .get(&TradingDataKey::RecentTrades)
-            .unwrap_or_else(|| Vec::new(env))
+    /// Number of records currently held in the ring buffer
+    pub fn get_recent_trade_count(env: &Env) -> u32 {
+        Self::get_recent_trades_written(env).min(Self::MAX_RECENT_TRADES as u64) as u32
+    }
+    
+    fn get_recent_trades_written(env: &Env) -> u64 {
+        env.storage().persistent().get(&TradingDataKey::RecentTradesWritten).unwrap_or(0)
+    }
+    
+    // ============ Admin Log ============
+    
+    /// Capacity of the admin log; the oldest entry is overwritten once it is full
+    pub const MAX_ADMIN_LOG: u32 = 256;
+    
+    pub fn push_admin_log(env: &Env, entry: &AdminLogEntry) {
+        let written = Self::get_admin_log_written(env);
+        let slot = (written % Self::MAX_ADMIN_LOG as u64) as u32;
+        env.storage().persistent().set(&TradingConfigKey::AdminLog(slot), entry);
+        env.storage().persistent().set(&TradingConfigKey::AdminLogWritten, &(written + 1));
+    }
+    
+    /// Get the entry `index` places after the oldest one still held (0 = oldest)
+    pub fn get_admin_log(env: &Env, index: u32) -> Option<AdminLogEntry> {
+        if index >= Self::get_admin_log_count(env) {
+            return None;
+        }
+        let written = Self::get_admin_log_written(env);
+        let oldest = written - Self::get_admin_log_count(env) as u64;
+        let slot = ((oldest + index as u64) % Self::MAX_ADMIN_LOG as u64) as u32;
+        env.storage().persistent().get(&TradingConfigKey::AdminLog(slot))
+    }
+    
+    /// Number of entries currently held in the admin log
+    pub fn get_admin_log_count(env: &Env) -> u32 {
+        Self::get_admin_log_written(env).min(Self::MAX_ADMIN_LOG as u64) as u32
+    }
+    
+    fn get_admin_log_written(env: &Env) -> u64 {
+        env.storage().persistent().get(&TradingConfigKey::AdminLogWritten).unwrap_or(0)
     }
     
     // ============ Batch Operations ============
@@ -326,7 +1360,7 @@ impl TradingStorage {
     /// Get storage statistics for monitoring
     pub fn get_storage_stats(env: &Env) -> (u64, u64) {
         let stats = Self::get_stats(env);
-        let recent_count = Self::get_recent_trade_ids(env).len() as u64;
+        let recent_count = Self::get_recent_trade_count(env) as u64;
         (stats.total_trades, recent_count)
     }
 }
@@ -3,9 +3,8 @@
 extern crate std;
 
 use super::*;
-use soroban_sdk::{testutils::Address as _, testutils::Ledger as _, testutils::Events as _, token, Address, Env, Symbol, Vec, IntoVal};
+use soroban_sdk::{testutils::Address as _, testutils::Ledger as _, testutils::Events as _, testutils::MockAuth, testutils::MockAuthInvoke, token, Address, Env, FromVal, Symbol, Val, Vec, IntoVal};
 use shared::governance::ProposalStatus;
-use shared::fees::FeeError;
 // Temporarily disable serial lock to fix CI
 
 fn setup_env() -> (Env, Address, Address, Address, Address) {
@@ -22,8 +21,17 @@ fn setup_env() -> (Env, Address, Address, Address, Address) {
     (env, admin, approver, executor, contract_id)
 }
 
+/// Authorize only `signer`'s direct call to `fn_name`, so any other
+/// `require_auth` in that call fails as it would on-chain
+fn mock_only_auth(env: &Env, signer: &Address, contract: &Address, fn_name: &str, args: Vec<Val>) {
+    env.mock_auths(&[MockAuth {
+        address: signer,
+        invoke: &MockAuthInvoke { contract, fn_name, args, sub_invokes: &[] },
+    }]);
+}
+
 fn init_contract(client: &UpgradeableTradingContractClient, admin: &Address, approvers: Vec<Address>, executor: &Address) {
-    client.init(admin, &approvers, executor);
+    client.init(admin, &approvers, executor, &None);
 }
 
 fn setup_fee_token(env: &Env) -> (Address, token::Client<'_>, token::StellarAssetClient<'_>) {
@@ -34,6 +42,12 @@ fn setup_fee_token(env: &Env) -> (Address, token::Client<'_>, token::StellarAsse
     (token_id, token_client, token_admin)
 }
 
+fn advance_ledger(env: &Env, ledgers: u32) {
+    let mut ledger_info = env.ledger().get();
+    ledger_info.sequence_number += ledgers;
+    env.ledger().set(ledger_info);
+}
+
 fn set_timestamp(env: &Env, timestamp: u64) {
     let mut ledger_info = env.ledger().get();
     ledger_info.timestamp = timestamp;
@@ -50,6 +64,394 @@ impl TestOracle {
     }
 }
 
+mod mock_reward {
+    use soroban_sdk::{contract, contracterror, contractimpl, symbol_short, Address, Env};
+
+    #[contracterror]
+    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+    #[repr(u32)]
+    pub enum MockRewardError {
+        InvalidAmount = 1,
+        Blocked = 2,
+    }
+
+    #[contract]
+    pub struct MockRewardContract;
+
+    #[contractimpl]
+    impl MockRewardContract {
+        pub fn reward_version(_env: Env) -> u32 {
+            1
+        }
+
+        pub fn add_reward(env: Env, user: Address, amount: i128) -> Result<i128, MockRewardError> {
+            if amount <= 0 {
+                return Err(MockRewardError::InvalidAmount);
+            }
+            if env.storage().instance().has(&(symbol_short!("blocked"), user.clone())) {
+                return Err(MockRewardError::Blocked);
+            }
+            let total: i128 = env.storage().instance().get(&user).unwrap_or(0) + amount;
+            env.storage().instance().set(&user, &total);
+            Ok(total)
+        }
+
+        pub fn reward_of(env: Env, user: Address) -> i128 {
+            env.storage().instance().get(&user).unwrap_or(0)
+        }
+
+        /// Balance lookup used for fee discounts; fails for blocked users
+        pub fn reward_balance(env: Env, user: Address) -> Result<i128, MockRewardError> {
+            if env.storage().instance().has(&(symbol_short!("blocked"), user.clone())) {
+                return Err(MockRewardError::Blocked);
+            }
+            Ok(env.storage().instance().get(&user).unwrap_or(0))
+        }
+
+        /// Make every future reward for `user` fail
+        pub fn block(env: Env, user: Address) {
+            env.storage().instance().set(&(symbol_short!("blocked"), user), &true);
+        }
+    }
+}
+
+mod reentrant_reward {
+    use soroban_sdk::{contract, contractimpl, symbol_short, Address, Env, IntoVal, Symbol};
+
+    /// Reward contract that tries to call back into `trade` while being rewarded
+    #[contract]
+    pub struct ReentrantRewardContract;
+
+    #[contractimpl]
+    impl ReentrantRewardContract {
+        pub fn reward_version(_env: Env) -> u32 {
+            1
+        }
+
+        pub fn set_target(env: Env, trading: Address, fee_token: Address, fee_recipient: Address) {
+            env.storage().instance().set(&symbol_short!("target"), &(trading, fee_token, fee_recipient));
+        }
+
+        pub fn add_reward(env: Env, user: Address, amount: i128) {
+            let (trading, fee_token, fee_recipient): (Address, Address, Address) =
+                env.storage().instance().get(&symbol_short!("target")).unwrap();
+
+            let args = soroban_sdk::vec![
+                &env,
+                user.into_val(&env),
+                Symbol::new(&env, "XLMUSDC").into_val(&env),
+                amount.into_val(&env),
+                10i128.into_val(&env),
+                true.into_val(&env),
+                fee_token.into_val(&env),
+                fee_recipient.into_val(&env),
+                Option::<Address>::None.into_val(&env),
+                Option::<Symbol>::None.into_val(&env),
+            ];
+            let result = env.try_invoke_contract::<u64, soroban_sdk::Error>(
+                &trading,
+                &Symbol::new(&env, "trade"),
+                args,
+            );
+
+            let code = match result {
+                Err(Ok(error)) => error.get_code(),
+                _ => 0,
+            };
+            env.storage().instance().set(&symbol_short!("reentry"), &code);
+        }
+
+        pub fn reentry_error(env: Env) -> u32 {
+            env.storage().instance().get(&symbol_short!("reentry")).unwrap_or(0)
+        }
+    }
+}
+
+mod counting_token {
+    use soroban_sdk::{contract, contractimpl, symbol_short, Env};
+
+    /// Token stub that counts how often its `decimals` is queried
+    #[contract]
+    pub struct CountingDecimalsToken;
+
+    #[contractimpl]
+    impl CountingDecimalsToken {
+        pub fn decimals(env: Env) -> u32 {
+            let calls: u32 = env.storage().instance().get(&symbol_short!("calls")).unwrap_or(0);
+            env.storage().instance().set(&symbol_short!("calls"), &(calls + 1));
+            6
+        }
+
+        pub fn decimals_calls(env: Env) -> u32 {
+            env.storage().instance().get(&symbol_short!("calls")).unwrap_or(0)
+        }
+    }
+}
+
+mod decimals_token {
+    use soroban_sdk::{contract, contractimpl, symbol_short, Env};
+
+    /// Token stub reporting configurable decimals
+    #[contract]
+    pub struct DecimalsToken;
+
+    #[contractimpl]
+    impl DecimalsToken {
+        pub fn set_decimals(env: Env, decimals: u32) {
+            env.storage().instance().set(&symbol_short!("decimals"), &decimals);
+        }
+
+        pub fn decimals(env: Env) -> u32 {
+            env.storage().instance().get(&symbol_short!("decimals")).unwrap_or(7)
+        }
+    }
+}
+
+mod mock_fee_oracle {
+    use soroban_sdk::{contract, contracterror, contractimpl, symbol_short, Address, Env};
+
+    #[contracterror]
+    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+    #[repr(u32)]
+    pub enum MockFeeOracleError {
+        Unavailable = 1,
+    }
+
+    /// Fee oracle reporting a configurable rate; fails until a rate is set
+    #[contract]
+    pub struct MockFeeOracle;
+
+    #[contractimpl]
+    impl MockFeeOracle {
+        pub fn set_bps(env: Env, bps: u32) {
+            env.storage().instance().set(&symbol_short!("bps"), &bps);
+        }
+
+        pub fn fee_bps(env: Env, _fee_token: Address) -> Result<u32, MockFeeOracleError> {
+            env.storage()
+                .instance()
+                .get(&symbol_short!("bps"))
+                .ok_or(MockFeeOracleError::Unavailable)
+        }
+    }
+}
+
+mod permit_token {
+    use soroban_sdk::{contract, contracterror, contractimpl, Address, BytesN, Env};
+
+    #[contracterror]
+    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+    #[repr(u32)]
+    pub enum PermitTokenError {
+        Expired = 1,
+        BadSignature = 2,
+        InsufficientAllowance = 3,
+        InsufficientBalance = 4,
+    }
+
+    /// Minimal token whose allowances are granted by signed permits; an
+    /// all-zero signature stands in for an invalid one
+    #[contract]
+    pub struct PermitToken;
+
+    #[contractimpl]
+    impl PermitToken {
+        pub fn mint(env: Env, to: Address, amount: i128) {
+            let balance = Self::balance(env.clone(), to.clone());
+            env.storage().instance().set(&to, &(balance + amount));
+        }
+
+        pub fn balance(env: Env, id: Address) -> i128 {
+            env.storage().instance().get(&id).unwrap_or(0)
+        }
+
+        pub fn permit(
+            env: Env,
+            owner: Address,
+            spender: Address,
+            amount: i128,
+            deadline: u32,
+            signature: BytesN<64>,
+        ) -> Result<(), PermitTokenError> {
+            if env.ledger().sequence() > deadline {
+                return Err(PermitTokenError::Expired);
+            }
+            if signature == BytesN::from_array(&env, &[0u8; 64]) {
+                return Err(PermitTokenError::BadSignature);
+            }
+            env.storage().instance().set(&(owner, spender), &amount);
+            Ok(())
+        }
+
+        pub fn transfer_from(
+            env: Env,
+            spender: Address,
+            from: Address,
+            to: Address,
+            amount: i128,
+        ) -> Result<(), PermitTokenError> {
+            spender.require_auth();
+            let key = (from.clone(), spender);
+            let allowance: i128 = env.storage().instance().get(&key).unwrap_or(0);
+            if allowance < amount {
+                return Err(PermitTokenError::InsufficientAllowance);
+            }
+            env.storage().instance().set(&key, &(allowance - amount));
+            Self::move_balance(&env, from, to, amount)
+        }
+
+        pub fn transfer(env: Env, from: Address, to: Address, amount: i128) -> Result<(), PermitTokenError> {
+            from.require_auth();
+            Self::move_balance(&env, from, to, amount)
+        }
+
+        fn move_balance(env: &Env, from: Address, to: Address, amount: i128) -> Result<(), PermitTokenError> {
+            let from_balance = Self::balance(env.clone(), from.clone());
+            if from_balance < amount {
+                return Err(PermitTokenError::InsufficientBalance);
+            }
+            env.storage().instance().set(&from, &(from_balance - amount));
+            let to_balance = Self::balance(env.clone(), to.clone());
+            env.storage().instance().set(&to, &(to_balance + amount));
+            Ok(())
+        }
+    }
+}
+
+mod lossy_token {
+    use soroban_sdk::{contract, contractimpl, Address, Env};
+
+    /// Fee-on-transfer token that keeps 1% of every transfer
+    #[contract]
+    pub struct LossyToken;
+
+    #[contractimpl]
+    impl LossyToken {
+        pub fn mint(env: Env, to: Address, amount: i128) {
+            let balance = Self::balance(env.clone(), to.clone());
+            env.storage().instance().set(&to, &(balance + amount));
+        }
+
+        pub fn balance(env: Env, id: Address) -> i128 {
+            env.storage().instance().get(&id).unwrap_or(0)
+        }
+
+        pub fn transfer(env: Env, from: Address, to: Address, amount: i128) {
+            from.require_auth();
+            let from_balance = Self::balance(env.clone(), from.clone());
+            env.storage().instance().set(&from, &(from_balance - amount));
+            let to_balance = Self::balance(env.clone(), to.clone());
+            env.storage().instance().set(&to, &(to_balance + amount - amount / 100));
+        }
+    }
+}
+
+mod versioned_reward {
+    use soroban_sdk::{contract, contractimpl, symbol_short, Env};
+
+    /// Reward contract stub that only reports a configurable interface version
+    #[contract]
+    pub struct VersionedReward;
+
+    #[contractimpl]
+    impl VersionedReward {
+        pub fn set_version(env: Env, version: u32) {
+            env.storage().instance().set(&symbol_short!("version"), &version);
+        }
+
+        pub fn reward_version(env: Env) -> u32 {
+            env.storage().instance().get(&symbol_short!("version")).unwrap_or(1)
+        }
+    }
+}
+
+mod rejecting_token {
+    use soroban_sdk::{contract, contracterror, contractimpl, symbol_short, Address, Env};
+
+    #[contracterror]
+    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+    #[repr(u32)]
+    pub enum RejectError {
+        Rejected = 1,
+    }
+
+    /// Token that refuses transfers to blocked recipients
+    #[contract]
+    pub struct RejectingToken;
+
+    #[contractimpl]
+    impl RejectingToken {
+        pub fn mint(env: Env, to: Address, amount: i128) {
+            let balance = Self::balance(env.clone(), to.clone());
+            env.storage().instance().set(&to, &(balance + amount));
+        }
+
+        pub fn balance(env: Env, id: Address) -> i128 {
+            env.storage().instance().get(&id).unwrap_or(0)
+        }
+
+        pub fn block(env: Env, to: Address) {
+            env.storage().instance().set(&(symbol_short!("blocked"), to), &true);
+        }
+
+        pub fn transfer(env: Env, from: Address, to: Address, amount: i128) -> Result<(), RejectError> {
+            from.require_auth();
+            if env.storage().instance().has(&(symbol_short!("blocked"), to.clone())) {
+                return Err(RejectError::Rejected);
+            }
+            let from_balance = Self::balance(env.clone(), from.clone());
+            env.storage().instance().set(&from, &(from_balance - amount));
+            let to_balance = Self::balance(env.clone(), to.clone());
+            env.storage().instance().set(&to, &(to_balance + amount));
+            Ok(())
+        }
+    }
+}
+
+/// A reward contract whose `add_reward` traps, as a Rust panic or overflow
+/// does once compiled to Wasm; `reward_version` returns 1
+///
+/// Native test contracts cannot panic without aborting the test binary, so
+/// this is assembled by hand from:
+///
+/// ```text
+/// (module
+///   (func (export "add_reward") (param i64 i64) (result i64) unreachable)
+///   (func (export "reward_version") (result i64) i64.const 0x100000004))
+/// ```
+fn trapping_reward_wasm() -> std::vec::Vec<u8> {
+    let mut wasm = std::vec![0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+    // contractenvmetav0: interface version for protocol 20
+    let mut meta = std::vec![17];
+    meta.extend_from_slice(b"contractenvmetav0");
+    meta.extend_from_slice(&[0, 0, 0, 0]);
+    meta.extend_from_slice(&(20u64 << 32).to_be_bytes());
+    wasm.extend_from_slice(&[0x00, meta.len() as u8]);
+    wasm.extend(meta);
+    // Types: (i64, i64) -> i64 and () -> i64
+    wasm.extend_from_slice(&[0x01, 0x0b, 0x02, 0x60, 0x02, 0x7e, 0x7e, 0x01, 0x7e, 0x60, 0x00, 0x01, 0x7e]);
+    wasm.extend_from_slice(&[0x03, 0x03, 0x02, 0x00, 0x01]);
+    wasm.extend_from_slice(&[0x07, 0x1f, 0x02, 0x0a]);
+    wasm.extend_from_slice(b"add_reward");
+    wasm.extend_from_slice(&[0x00, 0x00, 0x0e]);
+    wasm.extend_from_slice(b"reward_version");
+    wasm.extend_from_slice(&[0x00, 0x01]);
+    // Bodies: `unreachable`, and the U32Val 1
+    wasm.extend_from_slice(&[0x0a, 0x0e, 0x02, 0x03, 0x00, 0x00, 0x0b]);
+    wasm.extend_from_slice(&[0x08, 0x00, 0x42, 0x84, 0x80, 0x80, 0x80, 0x10, 0x0b]);
+    wasm
+}
+
+use mock_reward::{MockRewardContract, MockRewardContractClient};
+use rejecting_token::{RejectingToken, RejectingTokenClient};
+use versioned_reward::{VersionedReward, VersionedRewardClient};
+use reentrant_reward::{ReentrantRewardContract, ReentrantRewardContractClient};
+use counting_token::{CountingDecimalsToken, CountingDecimalsTokenClient};
+use decimals_token::{DecimalsToken, DecimalsTokenClient};
+use mock_fee_oracle::{MockFeeOracle, MockFeeOracleClient};
+use permit_token::{PermitToken, PermitTokenClient};
+use lossy_token::{LossyToken, LossyTokenClient};
+
 #[test]
 fn test_init_and_getters() {
     let _guard = (); // serial_lock disabled
@@ -80,8 +482,39 @@ fn test_init_twice_fails() {
 
     init_contract(&client, &admin, approvers.clone(), &executor);
 
-    let result = client.try_init(&admin, &approvers, &executor);
-    assert_eq!(result, Err(Ok(TradeError::Unauthorized)));
+    let result = client.try_init(&admin, &approvers, &executor, &None);
+    assert_eq!(result, Err(Ok(TradingError::AlreadyInitialized)));
+}
+
+#[test]
+fn test_reinit_preserves_original_admin() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers.clone(), &executor);
+
+    let attacker = Address::generate(&env);
+    let result = client.try_init(&attacker, &approvers, &executor, &None);
+    assert_eq!(result, Err(Ok(TradingError::AlreadyInitialized)));
+
+    // The attacker gained no admin rights; the original admin still has them
+    assert_eq!(client.try_set_fee_bps(&attacker, &100), Err(Ok(TradingError::NotAdmin)));
+    client.set_fee_bps(&admin, &100);
+    assert_eq!(client.fee_bps(), 100);
+}
+
+#[test]
+fn test_init_rejects_contract_as_admin() {
+    let _guard = ();
+    let (env, _admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+
+    let result = client.try_init(&contract_id, &approvers, &executor, &None);
+    assert_eq!(result, Err(Ok(TradingError::InvalidAdmin)));
 }
 
 #[test]
@@ -94,12 +527,13 @@ fn test_trade_happy_path_updates_stats_and_transfers_fee() {
     init_contract(&client, &admin, approvers, &executor);
 
     let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
     let trader = Address::generate(&env);
     let fee_recipient = Address::generate(&env);
 
     token_admin.mint(&trader, &1000);
 
-    let trade_id = client.trade(
+    let trade_id = client.trade_fixed_fee(
         &trader,
         &Symbol::new(&env, "XLMUSDC"),
         &250,
@@ -130,11 +564,12 @@ fn test_trade_invalid_fee_amount_fails() {
     init_contract(&client, &admin, approvers, &executor);
 
     let (token_id, _token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
     let trader = Address::generate(&env);
     let fee_recipient = Address::generate(&env);
     token_admin.mint(&trader, &1000);
 
-    let result = client.try_trade(
+    let result = client.try_trade_fixed_fee(
         &trader,
         &Symbol::new(&env, "XLMUSDC"),
         &100,
@@ -145,7 +580,7 @@ fn test_trade_invalid_fee_amount_fails() {
         &fee_recipient,
     );
 
-    assert_eq!(result, Err(Ok(FeeError::InvalidAmount)));
+    assert_eq!(result, Err(Ok(TradingError::InvalidAmount)));
 }
 
 #[test]
@@ -158,11 +593,12 @@ fn test_trade_insufficient_balance_fails() {
     init_contract(&client, &admin, approvers, &executor);
 
     let (token_id, _token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
     let trader = Address::generate(&env);
     let fee_recipient = Address::generate(&env);
     token_admin.mint(&trader, &50);
 
-    let result = client.try_trade(
+    let result = client.try_trade_fixed_fee(
         &trader,
         &Symbol::new(&env, "XLMUSDC"),
         &100,
@@ -173,357 +609,412 @@ fn test_trade_insufficient_balance_fails() {
         &fee_recipient,
     );
 
-    assert_eq!(result, Err(Ok(FeeError::InsufficientBalance)));
+    assert_eq!(result, Err(Ok(TradingError::InsufficientBalance)));
 }
 
 #[test]
-fn test_pause_sets_flag() {
-    let _guard = (); // serial_lock disabled
+fn test_trade_charges_bps_fee_of_amount() {
+    let _guard = ();
     let (env, admin, approver, executor, contract_id) = setup_env();
     let client = UpgradeableTradingContractClient::new(&env, &contract_id);
     let mut approvers = Vec::new(&env);
     approvers.push_back(approver);
     init_contract(&client, &admin, approvers, &executor);
 
-    // Test that pause and unpause can be called successfully
-    // Note: The actual pause state is stored in instance storage with TradingDataKey::Paused
-    client.pause(&admin);
-    client.unpause(&admin);
-    
-    // If we get here, pause/unpause worked correctly
-    // The actual pause state verification is done in test_batch_trade_when_paused
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+
+    assert_eq!(client.fee_bps(), 0);
+    client.set_fee_bps(&admin, &30);
+    assert_eq!(client.fee_bps(), 30);
+
+    // 30 bps of 10_000 is exactly 30
+    let trade_id = client.trade(
+        &trader,
+        &Symbol::new(&env, "XLMUSDC"),
+        &10_000,
+        &10,
+        &true,
+        &token_id,
+        &fee_recipient,
+        &None,
+        &None,
+    );
+
+    assert_eq!(trade_id, 1);
+    assert_eq!(token_client.balance(&trader), 970);
+    assert_eq!(token_client.balance(&fee_recipient), 30);
+
+    let stats = client.get_stats();
+    assert_eq!(stats.total_trades, 1);
+    assert_eq!(stats.total_volume, 10_000);
 }
 
 #[test]
-fn test_pause_unpause_authorization() {
-    let _guard = (); // serial_lock disabled
+fn test_trade_bps_fee_rounds_down() {
+    let _guard = ();
     let (env, admin, approver, executor, contract_id) = setup_env();
     let client = UpgradeableTradingContractClient::new(&env, &contract_id);
     let mut approvers = Vec::new(&env);
     approvers.push_back(approver);
     init_contract(&client, &admin, approvers, &executor);
 
-    let non_admin = Address::generate(&env);
-    let result = client.try_pause(&non_admin);
-    assert_eq!(result, Err(Ok(TradeError::Unauthorized)));
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
 
-    client.pause(&admin);
-    let result = client.try_unpause(&non_admin);
-    assert_eq!(result, Err(Ok(TradeError::Unauthorized)));
+    client.set_fee_bps(&admin, &30);
 
-    client.unpause(&admin);
+    // 30 bps of 3_333 is 9.999, which rounds down to 9
+    client.trade(
+        &trader,
+        &Symbol::new(&env, "XLMUSDC"),
+        &3_333,
+        &10,
+        &true,
+        &token_id,
+        &fee_recipient,
+        &None,
+        &None,
+    );
+    assert_eq!(token_client.balance(&fee_recipient), 9);
+
+    // 30 bps of 333 is 0.999, which rounds down to a zero fee
+    client.trade(
+        &trader,
+        &Symbol::new(&env, "XLMUSDC"),
+        &333,
+        &10,
+        &true,
+        &token_id,
+        &fee_recipient,
+        &None,
+        &None,
+    );
+    assert_eq!(token_client.balance(&fee_recipient), 9);
+    assert_eq!(token_client.balance(&trader), 991);
+    assert_eq!(client.get_stats().total_trades, 2);
 }
 
 #[test]
-fn test_upgrade_proposal_flow_and_errors() {
-    let _guard = (); // serial_lock disabled
+fn test_trade_checked_enforces_max_fee() {
+    let _guard = ();
     let (env, admin, approver, executor, contract_id) = setup_env();
     let client = UpgradeableTradingContractClient::new(&env, &contract_id);
     let mut approvers = Vec::new(&env);
-    approvers.push_back(approver.clone());
-    init_contract(&client, &admin, approvers.clone(), &executor);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
 
-    // Invalid threshold -> mapped to Unauthorized
-    let invalid = client.try_propose_upgrade(
-        &admin,
-        &symbol_short!("v2hash"),
-        &symbol_short!("Upgrade"),
-        &approvers,
-        &0,
-        &3600,
-    );
-    assert_eq!(invalid, Err(Ok(TradeError::Unauthorized)));
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    let pair = Symbol::new(&env, "XLMUSDC");
 
-    // Valid proposal
-    let proposal_id = client.propose_upgrade(
-        &admin,
-        &symbol_short!("v2hash"),
-        &symbol_short!("Upgrade"),
-        &approvers,
-        &1,
-        &3600,
-    );
+    // 100 bps of 5_000 is 50: one unit beyond max_fee is rejected before any transfer
+    let result = client.try_trade_checked(&trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &49);
+    assert_eq!(result, Err(Ok(TradingError::FeeTooHigh)));
+    assert_eq!(token_client.balance(&trader), 1000);
+    assert_eq!(client.total_trades(), 0);
 
-    let proposal = client.get_upgrade_proposal(&proposal_id);
-    assert_eq!(proposal.status, ProposalStatus::Pending);
+    // A fee exactly at max_fee is accepted
+    client.trade_checked(&trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &50);
+    assert_eq!(token_client.balance(&trader), 950);
+    assert_eq!(token_client.balance(&fee_recipient), 50);
+}
 
-    client.approve_upgrade(&proposal_id, &approver);
-    let duplicate = client.try_approve_upgrade(&proposal_id, &approver);
-    assert_eq!(duplicate, Err(Ok(TradeError::Unauthorized)));
-    let proposal = client.get_upgrade_proposal(&proposal_id);
-    assert_eq!(proposal.status, ProposalStatus::Approved);
+#[test]
+fn test_daily_limit_resets_across_day_boundary() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+    client.set_daily_limit(&admin, &100);
+    assert_eq!(client.daily_limit(), 100);
 
-    // Execute too early
-    let execute_err = client.try_execute_upgrade(&proposal_id, &executor);
-    assert_eq!(execute_err, Err(Ok(TradeError::Unauthorized)));
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let other_trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    token_admin.mint(&other_trader, &1000);
+    let pair = Symbol::new(&env, "XLMUSDC");
 
-    set_timestamp(&env, 1000 + 3601);
-    client.execute_upgrade(&proposal_id, &executor);
+    set_timestamp(&env, 86_400 * 10);
 
-    let proposal = client.get_upgrade_proposal(&proposal_id);
-    assert_eq!(proposal.status, ProposalStatus::Executed);
+    // Two 50-unit fees use up the day's allowance exactly
+    client.trade(&trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    client.trade(&trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &None, &None);
 
-    // Cancelling executed proposal should fail
-    let cancel_err = client.try_cancel_upgrade(&proposal_id, &admin);
-    assert_eq!(cancel_err, Err(Ok(TradeError::Unauthorized)));
+    let result = client.try_trade(&trader, &pair, &100, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(result, Err(Ok(TradingError::RateLimited)));
+    assert_eq!(token_client.balance(&trader), 900);
+
+    // Limits are tracked per trader
+    client.trade(&other_trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &None, &None);
+
+    // Still the same bucket one second before midnight
+    set_timestamp(&env, 86_400 * 11 - 1);
+    let result = client.try_trade(&trader, &pair, &100, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(result, Err(Ok(TradingError::RateLimited)));
+
+    // The counter resets once the bucket rolls over
+    set_timestamp(&env, 86_400 * 11);
+    client.trade(&trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(token_client.balance(&trader), 850);
 }
 
 #[test]
-fn test_reject_and_get_proposal_errors() {
-    let _guard = (); // serial_lock disabled
+fn test_set_daily_limit_requires_admin() {
+    let _guard = ();
     let (env, admin, approver, executor, contract_id) = setup_env();
     let client = UpgradeableTradingContractClient::new(&env, &contract_id);
     let mut approvers = Vec::new(&env);
     approvers.push_back(approver.clone());
-    init_contract(&client, &admin, approvers.clone(), &executor);
+    init_contract(&client, &admin, approvers, &executor);
 
-    let proposal_id = client.propose_upgrade(
-        &admin,
-        &symbol_short!("v2hash"),
-        &symbol_short!("Upgrade"),
-        &approvers,
-        &1,
-        &3600,
-    );
-
-    client.reject_upgrade(&proposal_id, &approver);
-    let proposal = client.get_upgrade_proposal(&proposal_id);
-    assert_eq!(proposal.status, ProposalStatus::Rejected);
+    let result = client.try_set_daily_limit(&approver, &100);
+    assert_eq!(result, Err(Ok(TradingError::NotAdmin)));
 
-    let missing = client.try_get_upgrade_proposal(&999);
-    assert_eq!(missing, Err(Ok(TradeError::Unauthorized)));
+    let result = client.try_set_daily_limit(&admin, &-1);
+    assert_eq!(result, Err(Ok(TradingError::InvalidAmount)));
+    assert_eq!(client.daily_limit(), 0);
 }
 
 #[test]
-fn test_upgrade_governance_pause_and_timelock_validation() {
+fn test_quote_fee_matches_trade_balance_delta() {
     let _guard = ();
     let (env, admin, approver, executor, contract_id) = setup_env();
     let client = UpgradeableTradingContractClient::new(&env, &contract_id);
     let mut approvers = Vec::new(&env);
-    approvers.push_back(approver.clone());
-    init_contract(&client, &admin, approvers.clone(), &executor);
-
-    let invalid_timelock = client.try_propose_upgrade(
-        &admin,
-        &symbol_short!("v2hash"),
-        &symbol_short!("Upgrade"),
-        &approvers,
-        &1,
-        &3599,
-    );
-    assert_eq!(invalid_timelock, Err(Ok(TradeError::Unauthorized)));
-
-    client.pause_upgrade_governance(&admin);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &33);
 
-    let paused_proposal = client.try_propose_upgrade(
-        &admin,
-        &symbol_short!("v2hash"),
-        &symbol_short!("Upgrade"),
-        &approvers,
-        &1,
-        &3600,
-    );
-    assert_eq!(paused_proposal, Err(Ok(TradeError::Unauthorized)));
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &10_000);
+    let pair = Symbol::new(&env, "XLMUSDC");
 
-    client.resume_upgrade_governance(&admin);
+    // 33 bps of 12_345 is 40.7385, so both the quote and the charge round down to 40
+    let quote = client.quote_fee(&12_345, &token_id);
+    assert_eq!(quote, 40);
 
-    let proposal_id = client.propose_upgrade(
-        &admin,
-        &symbol_short!("v2hash"),
-        &symbol_short!("Upgrade"),
-        &approvers,
-        &1,
-        &3600,
-    );
+    let before = token_client.balance(&trader);
+    client.trade(&trader, &pair, &12_345, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(before - token_client.balance(&trader), quote);
 
-    let proposal = client.get_upgrade_proposal(&proposal_id);
-    assert_eq!(proposal.status, ProposalStatus::Pending);
+    assert_eq!(client.try_quote_fee(&-1, &token_id), Err(Ok(TradingError::InvalidAmount)));
 }
 
 #[test]
-fn test_oracle_refresh_updates_status() {
-    let _guard = (); // serial_lock disabled
+fn test_fee_bounds_clamp_tiny_and_huge_trades() {
+    let _guard = ();
     let (env, admin, approver, executor, contract_id) = setup_env();
     let client = UpgradeableTradingContractClient::new(&env, &contract_id);
     let mut approvers = Vec::new(&env);
     approvers.push_back(approver);
     init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+    assert_eq!(client.fee_bounds(), (0, i128::MAX));
+    client.set_fee_bounds(&admin, &5, &500);
 
-    let oracle_id = env.register_contract(None, TestOracle);
-    let mut oracles = Vec::new(&env);
-    oracles.push_back(oracle_id);
-
-    client.set_oracle_config(&admin, &oracles, &100, &1);
-
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &10_000);
     let pair = Symbol::new(&env, "XLMUSDC");
-    let aggregate = client.refresh_oracle_price(&pair);
 
-    assert_eq!(aggregate.median_price, 100);
-    assert_eq!(aggregate.source_count, 1);
+    // 1% of 100 is 1, raised to the floor of 5; 1% of 1_000_000 is 10_000,
+    // capped at the ceiling of 500. Quote, charge and the shared formula agree
+    for (amount, expected) in [(100, 5), (1_000_000, 500), (20_000, 200)] {
+        let quote = client.quote_fee(&amount, &token_id);
+        assert_eq!(quote, expected);
+        assert_eq!(FeeManager::compute_fee(amount, 100, 0, 0, 5, 500), Ok(quote));
 
-    let status = client.get_oracle_status();
-    assert_eq!(status.last_price, 100);
-    assert_eq!(status.last_source_count, 1);
-    assert_eq!(status.consecutive_failures, 0);
+        let before = token_client.balance(&fee_recipient);
+        client.trade(&trader, &pair, &amount, &10, &true, &token_id, &fee_recipient, &None, &None);
+        assert_eq!(token_client.balance(&fee_recipient) - before, quote);
+    }
+
+    // The floor and the discount are applied before the bounds
+    client.set_min_nonzero_fee(&admin, &3);
+    assert_eq!(client.quote_fee(&10, &token_id), 5);
+    assert_eq!(FeeManager::compute_fee(10, 100, 3, 0, 5, 500), Ok(5));
+    let before = token_client.balance(&fee_recipient);
+    client.trade(&trader, &pair, &10, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(token_client.balance(&fee_recipient) - before, 5);
 }
 
-// =============================================================================
-// Batch Operations Tests
-// =============================================================================
+#[test]
+fn test_set_fee_bounds_validation() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver.clone());
+    init_contract(&client, &admin, approvers, &executor);
+
+    assert_eq!(client.try_set_fee_bounds(&admin, &10, &5), Err(Ok(TradingError::InvalidFeeBounds)));
+    assert_eq!(client.try_set_fee_bounds(&admin, &-1, &5), Err(Ok(TradingError::InvalidFeeBounds)));
+    assert_eq!(client.try_set_fee_bounds(&approver, &0, &5), Err(Ok(TradingError::NotAdmin)));
+
+    client.set_fee_bounds(&admin, &7, &7);
+    assert_eq!(client.fee_bounds(), (7, 7));
+}
 
 #[test]
-fn test_batch_trade_happy_path() {
-    let _guard = (); // serial_lock disabled
+fn test_fee_token_allowlist_gates_trades() {
+    let _guard = ();
     let (env, admin, approver, executor, contract_id) = setup_env();
     let client = UpgradeableTradingContractClient::new(&env, &contract_id);
     let mut approvers = Vec::new(&env);
-    approvers.push_back(approver);
+    approvers.push_back(approver.clone());
     init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
 
-    let (token_id, token_client, token_admin) = setup_fee_token(&env);
-    let trader1 = Address::generate(&env);
-    let trader2 = Address::generate(&env);
+    let (allowed_token, allowed_client, allowed_admin) = setup_fee_token(&env);
+    let (other_token, other_client, other_admin) = setup_fee_token(&env);
+    let trader = Address::generate(&env);
     let fee_recipient = Address::generate(&env);
+    allowed_admin.mint(&trader, &1000);
+    other_admin.mint(&trader, &1000);
+    let pair = Symbol::new(&env, "XLMUSDC");
 
-    // Mint tokens for traders
-    token_admin.mint(&trader1, &1000);
-    token_admin.mint(&trader2, &1000);
-
-    // Create batch requests
-    let mut requests = Vec::new(&env);
-    requests.push_back(BatchTradeRequest {
-        trader: trader1.clone(),
-        pair: Symbol::new(&env, "XLMUSDC"),
-        amount: 250,
-        price: 10,
-        is_buy: true,
-        fee_token: token_id.clone(),
-        fee_amount: 100,
-        fee_recipient: fee_recipient.clone(),
-    });
-    requests.push_back(BatchTradeRequest {
-        trader: trader2.clone(),
-        pair: Symbol::new(&env, "XLMUSDC"),
-        amount: 150,
-        price: 12,
-        is_buy: false,
-        fee_token: token_id.clone(),
-        fee_amount: 50,
-        fee_recipient: fee_recipient.clone(),
-    });
+    assert_eq!(client.try_allow_fee_token(&approver, &allowed_token), Err(Ok(TradingError::NotAdmin)));
+    client.allow_fee_token(&admin, &allowed_token);
+    assert!(client.is_fee_token_allowed(&allowed_token));
+    assert!(!client.is_fee_token_allowed(&other_token));
 
-    let result = client.batch_trade(&requests);
+    client.trade(&trader, &pair, &5000, &10, &true, &allowed_token, &fee_recipient, &None, &None);
+    assert_eq!(allowed_client.balance(&fee_recipient), 50);
 
-    assert_eq!(result.successful_trades.len(), 2);
-    assert_eq!(result.failed_trades.len(), 2); // All results are stored in failed_trades
-    assert_eq!(result.total_fees_collected, 150);
-    assert!(result.gas_saved > 0);
+    // Unlisted tokens are refused before any transfer
+    let result = client.try_trade(&trader, &pair, &5000, &10, &true, &other_token, &fee_recipient, &None, &None);
+    assert_eq!(result, Err(Ok(TradingError::TokenNotAllowed)));
+    assert_eq!(other_client.balance(&trader), 1000);
 
-    // Check token balances
-    assert_eq!(token_client.balance(&trader1), 900);
-    assert_eq!(token_client.balance(&trader2), 950);
-    assert_eq!(token_client.balance(&fee_recipient), 150);
+    let result = client.try_batch_trade_atomic(
+        &trader,
+        &other_token,
+        &soroban_sdk::vec![&env, 10i128],
+        &soroban_sdk::vec![&env, fee_recipient.clone()],
+    );
+    assert_eq!(result, Err(Ok(TradingError::TokenNotAllowed)));
 
-    // Check stats
-    let stats = client.get_stats();
-    assert_eq!(stats.total_trades, 2);
-    assert_eq!(stats.total_volume, 400);
-    assert_eq!(stats.last_trade_id, 2);
+    // Delisting takes effect immediately
+    client.disallow_fee_token(&admin, &allowed_token);
+    let result = client.try_trade(&trader, &pair, &5000, &10, &true, &allowed_token, &fee_recipient, &None, &None);
+    assert_eq!(result, Err(Ok(TradingError::TokenNotAllowed)));
+    assert_eq!(allowed_client.balance(&trader), 950);
 }
 
 #[test]
-fn test_batch_trade_size_limit() {
-    let _guard = (); // serial_lock disabled
+fn test_timelocked_fee_change_waits_for_delay() {
+    let _guard = ();
     let (env, admin, approver, executor, contract_id) = setup_env();
     let client = UpgradeableTradingContractClient::new(&env, &contract_id);
     let mut approvers = Vec::new(&env);
-    approvers.push_back(approver);
+    approvers.push_back(approver.clone());
     init_contract(&client, &admin, approvers, &executor);
 
-    let (token_id, _token_client, token_admin) = setup_fee_token(&env);
-    let trader = Address::generate(&env);
-    let fee_recipient = Address::generate(&env);
+    // With no delay configured the delay change itself applies at once
+    client.set_timelock_delay(&admin, &100);
+    assert_eq!(client.timelock_delay(), 100);
+    assert_eq!(client.pending_change(), None);
 
-    token_admin.mint(&trader, &50000); // Enough for many trades
+    let queued_at = env.ledger().sequence();
+    client.set_fee_bps(&admin, &250);
+    assert_eq!(client.fee_bps(), 0);
+    assert_eq!(
+        client.pending_change(),
+        Some(PendingParamChange {
+            change: ParamChange::FeeBps(250),
+            execute_after: queued_at + 100,
+        })
+    );
 
-    // Create batch with more than MAX_BATCH_SIZE (50) requests
-    let mut requests = Vec::new(&env);
-    for _ in 0..51 {
-        requests.push_back(BatchTradeRequest {
-            trader: trader.clone(),
-            pair: Symbol::new(&env, "XLMUSDC"),
-            amount: 10,
-            price: 10,
-            is_buy: true,
-            fee_token: token_id.clone(),
-            fee_amount: 1,
-            fee_recipient: fee_recipient.clone(),
-        });
-    }
-    let result = client.try_batch_trade(&requests);
-    assert!(result.is_err());
-    // The batch size limit is enforced, but exact error type may vary
+    advance_ledger(&env, 99);
+    assert_eq!(client.try_execute_pending_change(), Err(Ok(TradingError::TimelockNotElapsed)));
+    assert_eq!(client.fee_bps(), 0);
+
+    advance_ledger(&env, 1);
+    assert_eq!(client.execute_pending_change(), ParamChange::FeeBps(250));
+    assert_eq!(client.fee_bps(), 250);
+    assert_eq!(client.pending_change(), None);
+    assert_eq!(client.try_execute_pending_change(), Err(Ok(TradingError::NoPendingChange)));
 }
 
 #[test]
-fn test_batch_trade_partial_failures() {
-    let _guard = (); // serial_lock disabled
+fn test_timelock_covers_allowlists_but_not_removals() {
+    let _guard = ();
     let (env, admin, approver, executor, contract_id) = setup_env();
     let client = UpgradeableTradingContractClient::new(&env, &contract_id);
     let mut approvers = Vec::new(&env);
-    approvers.push_back(approver);
+    approvers.push_back(approver.clone());
     init_contract(&client, &admin, approvers, &executor);
 
-    let (token_id, token_client, token_admin) = setup_fee_token(&env);
-    let trader1 = Address::generate(&env);
-    let trader2 = Address::generate(&env);
-    let fee_recipient = Address::generate(&env);
+    let (token_id, _token_client, _token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    client.set_timelock_delay(&admin, &50);
 
-    // Only mint tokens for trader1
-    token_admin.mint(&trader1, &1000);
+    let (new_token, _new_client, _new_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &new_token);
+    assert!(!client.is_fee_token_allowed(&new_token));
 
-    let mut requests = Vec::new(&env);
-    // Valid trade
-    requests.push_back(BatchTradeRequest {
-        trader: trader1.clone(),
-        pair: Symbol::new(&env, "XLMUSDC"),
-        amount: 250,
-        price: 10,
-        is_buy: true,
-        fee_token: token_id.clone(),
-        fee_amount: 100,
-        fee_recipient: fee_recipient.clone(),
-    });
-    // Invalid trade (insufficient balance)
-    requests.push_back(BatchTradeRequest {
-        trader: trader2.clone(),
-        pair: Symbol::new(&env, "XLMUSDC"),
-        amount: 150,
-        price: 12,
-        is_buy: false,
-        fee_token: token_id.clone(),
-        fee_amount: 50,
-        fee_recipient: fee_recipient.clone(),
-    });
+    // Removing a token stays immediate so a bad token can be pulled during an incident
+    client.disallow_fee_token(&admin, &token_id);
+    assert!(!client.is_fee_token_allowed(&token_id));
 
-    let result = client.batch_trade(&requests);
+    advance_ledger(&env, 50);
+    client.execute_pending_change();
+    assert!(client.is_fee_token_allowed(&new_token));
 
-    assert_eq!(result.successful_trades.len(), 1);
-    assert_eq!(result.failed_trades.len(), 2);
-    assert_eq!(result.total_fees_collected, 100);
+    // Lowering the delay waits behind the current one
+    client.set_timelock_delay(&admin, &0);
+    assert_eq!(client.timelock_delay(), 50);
+    assert_eq!(client.try_set_timelock_delay(&approver, &0), Err(Ok(TradingError::NotAdmin)));
+}
 
-    // Check that only the successful trade was processed
-    assert_eq!(token_client.balance(&trader1), 900);
-    assert_eq!(token_client.balance(&trader2), 0);
-    assert_eq!(token_client.balance(&fee_recipient), 100);
+#[test]
+fn test_set_fee_bps_validation_and_authorization() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
 
-    let stats = client.get_stats();
-    assert_eq!(stats.total_trades, 1);
-    assert_eq!(stats.total_volume, 250);
+    let result = client.try_set_fee_bps(&admin, &10_001);
+    assert_eq!(result, Err(Ok(TradingError::InvalidFeeBps)));
+    assert_eq!(client.fee_bps(), 0);
+
+    // 100% is the largest accepted rate
+    client.set_fee_bps(&admin, &10_000);
+    assert_eq!(client.fee_bps(), 10_000);
+
+    let non_admin = Address::generate(&env);
+    let result = client.try_set_fee_bps(&non_admin, &50);
+    assert_eq!(result, Err(Ok(TradingError::NotAdmin)));
+    assert_eq!(client.fee_bps(), 10_000);
 }
 
 #[test]
-fn test_batch_trade_when_paused() {
+fn test_pause_sets_flag() {
     let _guard = (); // serial_lock disabled
     let (env, admin, approver, executor, contract_id) = setup_env();
     let client = UpgradeableTradingContractClient::new(&env, &contract_id);
@@ -532,77 +1023,5001 @@ fn test_batch_trade_when_paused() {
     init_contract(&client, &admin, approvers, &executor);
 
     let (token_id, _token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
     let trader = Address::generate(&env);
     let fee_recipient = Address::generate(&env);
-
     token_admin.mint(&trader, &1000);
+    let pair = Symbol::new(&env, "XLMUSDC");
 
-    // Pause contract
     client.pause(&admin);
+    let result = client.try_trade(&trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(result, Err(Ok(TradingError::Paused)));
 
-    let mut requests = Vec::new(&env);
-    requests.push_back(BatchTradeRequest {
-        trader: trader.clone(),
-        pair: Symbol::new(&env, "XLMUSDC"),
-        amount: 250,
-        price: 10,
-        is_buy: true,
-        fee_token: token_id.clone(),
-        fee_amount: 100,
-        fee_recipient: fee_recipient.clone(),
-    });
-
-    let result = client.try_batch_trade(&requests);
-    assert!(result.is_err());
-    // The batch size limit is enforced
+    client.unpause(&admin);
+    let trade_id = client.trade(&trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(trade_id, 1);
 }
 
 #[test]
-fn test_batch_trade_emits_events() {
-    let _guard = (); // serial_lock disabled
+fn test_config_snapshot_tracks_settings() {
+    let _guard = ();
     let (env, admin, approver, executor, contract_id) = setup_env();
     let client = UpgradeableTradingContractClient::new(&env, &contract_id);
     let mut approvers = Vec::new(&env);
     approvers.push_back(approver);
+    assert_eq!(client.try_config(), Err(Ok(TradingError::NotInitialized)));
     init_contract(&client, &admin, approvers, &executor);
 
-    let (token_id, _token_client, token_admin) = setup_fee_token(&env);
-    let trader = Address::generate(&env);
-    let fee_recipient = Address::generate(&env);
+    let mut expected = TradingConfig {
+        admin: admin.clone(),
+        paused: false,
+        fee_bps: 0,
+        min_fee: 0,
+        max_fee: i128::MAX,
+        version: client.get_version(),
+    };
+    assert_eq!(client.config(), expected);
 
-    token_admin.mint(&trader, &1000);
+    client.set_fee_bps(&admin, &250);
+    expected.fee_bps = 250;
+    assert_eq!(client.config(), expected);
 
-    let mut requests = Vec::new(&env);
-    requests.push_back(BatchTradeRequest {
-        trader: trader.clone(),
-        pair: Symbol::new(&env, "XLMUSDC"),
-        amount: 250,
-        price: 10,
-        is_buy: true,
-        fee_token: token_id,
-        fee_amount: 100,
-        fee_recipient: fee_recipient.clone(),
-    });
+    client.set_fee_bounds(&admin, &5, &500);
+    expected.min_fee = 5;
+    expected.max_fee = 500;
+    assert_eq!(client.config(), expected);
 
-    client.batch_trade(&requests);
+    client.pause(&admin);
+    expected.paused = true;
+    assert_eq!(client.config(), expected);
+
+    let new_admin = Address::generate(&env);
+    client.propose_admin(&admin, &new_admin);
+    client.accept_admin(&new_admin);
+    expected.admin = new_admin;
+    assert_eq!(client.config(), expected);
+}
+
+#[test]
+fn test_native_token_fee_trade_settles() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    // Stand-in for the native XLM wrapper: a plain Stellar Asset Contract
+    let (native_id, native_client, native_admin) = setup_fee_token(&env);
+    assert_eq!(client.native_token(), None);
+    client.set_native_token(&admin, &native_id);
+    assert_eq!(client.native_token(), Some(native_id.clone()));
+    assert!(client.is_fee_token_allowed(&native_id));
+
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    native_admin.mint(&trader, &1000);
+
+    let trade_id = client.trade(
+        &trader,
+        &Symbol::new(&env, "XLMUSDC"),
+        &10_000,
+        &10,
+        &true,
+        &native_id,
+        &fee_recipient,
+        &None,
+        &None,
+    );
+
+    assert_eq!(trade_id, 1);
+    assert_eq!(native_client.balance(&trader), 900);
+    assert_eq!(native_client.balance(&fee_recipient), 100);
+    assert_eq!(client.total_fees(&native_id), 100);
+}
+
+#[test]
+fn test_trade_with_nonce_rejects_replay() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    assert_eq!(client.current_nonce(&trader), 0);
+    client.trade_with_nonce(&trader, &0, &pair, &10_000, &10, &true, &token_id, &fee_recipient);
+    assert_eq!(client.current_nonce(&trader), 1);
+
+    // Replaying the same intent is refused and charges nothing
+    let result = client.try_trade_with_nonce(&trader, &0, &pair, &10_000, &10, &true, &token_id, &fee_recipient);
+    assert_eq!(result, Err(Ok(TradingError::InvalidNonce)));
+
+    // Nonces cannot skip ahead either
+    let result = client.try_trade_with_nonce(&trader, &5, &pair, &10_000, &10, &true, &token_id, &fee_recipient);
+    assert_eq!(result, Err(Ok(TradingError::InvalidNonce)));
+    assert_eq!(token_client.balance(&trader), 900);
+
+    client.trade_with_nonce(&trader, &1, &pair, &10_000, &10, &true, &token_id, &fee_recipient);
+    assert_eq!(client.current_nonce(&trader), 2);
+    assert_eq!(token_client.balance(&trader), 800);
+
+    // Nonces are tracked per trader
+    assert_eq!(client.current_nonce(&fee_recipient), 0);
+}
+
+#[test]
+fn test_fee_holiday_waives_fees_inside_window() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+    client.set_fee_bounds(&admin, &10, &1_000);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    let start = env.ledger().sequence() + 10;
+    assert_eq!(client.try_set_fee_holiday(&admin, &start, &start), Err(Ok(TradingError::InvalidWindow)));
+    client.set_fee_holiday(&admin, &start, &(start + 5));
+    assert_eq!(client.fee_holiday(), Some((start, start + 5)));
+
+    // Before the window: normal fee
+    assert_eq!(client.quote_fee(&10_000, &token_id), 100);
+    client.trade(&trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(token_client.balance(&trader), 900);
+
+    // Inside the window: no fee, not even the minimum
+    advance_ledger(&env, 10);
+    assert_eq!(client.quote_fee(&10_000, &token_id), 0);
+    client.trade(&trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(token_client.balance(&trader), 900);
+
+    // The end ledger is already back to normal
+    advance_ledger(&env, 5);
+    assert_eq!(client.quote_fee(&10_000, &token_id), 100);
+    client.trade(&trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(token_client.balance(&trader), 800);
+}
+
+#[test]
+fn test_min_nonzero_fee_closes_rounding_loophole() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &30);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    // 100 * 30 / 10_000 rounds down to a free trade
+    assert_eq!(client.quote_fee(&100, &token_id), 0);
+    client.trade(&trader, &pair, &100, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(token_client.balance(&trader), 1000);
+
+    assert_eq!(client.try_set_min_nonzero_fee(&admin, &-1), Err(Ok(TradingError::InvalidAmount)));
+    client.set_min_nonzero_fee(&admin, &1);
+    assert_eq!(client.min_nonzero_fee(), 1);
+
+    // The same tiny trade now pays the floor
+    assert_eq!(client.quote_fee(&100, &token_id), 1);
+    client.trade(&trader, &pair, &100, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(token_client.balance(&trader), 999);
+    assert_eq!(token_client.balance(&fee_recipient), 1);
+
+    // Fees that do not round to zero are unaffected, and a 0 bps rate stays free
+    assert_eq!(client.quote_fee(&10_000, &token_id), 30);
+    client.set_fee_bps(&admin, &0);
+    assert_eq!(client.quote_fee(&100, &token_id), 0);
+}
+
+#[test]
+fn test_fee_sink_accrues_and_withdraws() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    assert_eq!(client.fee_sink(), FeeSinkMode::Direct);
+    client.set_fee_sink(&admin, &FeeSinkMode::Accrue);
+
+    client.trade(&trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    client.trade(&trader, &pair, &5_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    client.trade(&trader, &pair, &20_000, &10, &false, &token_id, &fee_recipient, &None, &None);
+
+    // Fees stay in the contract instead of reaching the recipient
+    assert_eq!(client.accrued_fees(&token_id), 350);
+    assert_eq!(token_client.balance(&contract_id), 350);
+    assert_eq!(token_client.balance(&fee_recipient), 0);
+
+    let outsider = Address::generate(&env);
+    assert_eq!(
+        client.try_withdraw_fees(&outsider, &token_id, &treasury, &350),
+        Err(Ok(TradingError::NotAdmin))
+    );
+    assert_eq!(
+        client.try_withdraw_fees(&admin, &token_id, &treasury, &351),
+        Err(Ok(TradingError::InsufficientBalance))
+    );
+
+    // Accrued fees cannot be swept as stray tokens
+    client.pause(&admin);
+    assert_eq!(
+        client.try_sweep(&admin, &token_id, &treasury, &1),
+        Err(Ok(TradingError::InsufficientBalance))
+    );
+    client.unpause(&admin);
+
+    client.withdraw_fees(&admin, &token_id, &treasury, &350);
+    assert_eq!(client.accrued_fees(&token_id), 0);
+    assert_eq!(token_client.balance(&treasury), 350);
+    assert_eq!(token_client.balance(&contract_id), 0);
+
+    // Switching back streams fees to the recipient again
+    client.set_fee_sink(&admin, &FeeSinkMode::Direct);
+    client.trade(&trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(token_client.balance(&fee_recipient), 100);
+    assert_eq!(client.accrued_fees(&token_id), 0);
+}
+
+#[test]
+fn test_circuit_breaker_pauses_on_fee_spike() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+    assert_eq!(client.try_set_circuit_breaker(&admin, &-1), Err(Ok(TradingError::InvalidAmount)));
+    client.set_circuit_breaker(&admin, &150);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    // At the threshold: trades normally
+    client.trade(&trader, &pair, &15_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(token_client.balance(&trader), 850);
+
+    // A misconfigured rate pushes the fee past the threshold
+    client.set_fee_bps(&admin, &10_000);
+    let trade_id = client.trade(&trader, &pair, &500, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(trade_id, BREAKER_TRIPPED);
+    assert_eq!(token_client.balance(&trader), 850);
+    assert_eq!(client.pause_flags(), PAUSE_TRADE);
+    assert_eq!(client.pause_info().1, Symbol::new(&env, "circuit_breaker"));
 
     let events = env.events().all();
-    // Should have fee and trade events
-    let has_trade_event = events.iter().any(|(_, topics, _): (_, _, _)| {
-        if let Some(first_topic) = topics.first() {
-            let topic_sym: Symbol = first_topic.clone().into_val(&env);
-            return topic_sym == symbol_short!("trade");
+    let (_, topics, data) = events.last().unwrap();
+    assert_eq!(topics, (Symbol::new(&env, "circuit_breaker"),).into_val(&env));
+    assert_eq!(i128::from_val(&env, &data), 500);
+
+    let result = client.try_trade(&trader, &pair, &10, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(result, Err(Ok(TradingError::Paused)));
+    assert_eq!(client.get_stats().total_trades, 1);
+}
+
+#[test]
+fn test_circuit_breaker_covers_every_fee_path() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    env.budget().reset_unlimited();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_circuit_breaker(&admin, &150);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    client.deposit(&trader, &token_id, &500);
+    let schedule_id = client.schedule_trade(&trader, &Symbol::new(&env, "XLMUSDC"), &10_000, &10, &true, &token_id, &200, &recipient, &0, &true);
+    let mut recipients = Vec::new(&env);
+    recipients.push_back(recipient.clone());
+    let mut weights = Vec::new(&env);
+    weights.push_back(10_000);
+    let mut fees = Vec::new(&env);
+    fees.push_back(200);
+    let tripped = |client: &UpgradeableTradingContractClient| {
+        assert_eq!(client.pause_flags(), PAUSE_TRADE);
+        assert_eq!(client.pause_info().1, Symbol::new(&env, "circuit_breaker"));
+        client.unpause(&admin);
+    };
+
+    // Each call succeeds so the pause sticks, but moves no fee
+    assert_eq!(client.trade_split(&trader, &token_id, &200, &recipients, &weights).len(), 0);
+    tripped(&client);
+    assert_eq!(client.batch_trade_atomic(&trader, &token_id, &fees, &recipients), 0);
+    tripped(&client);
+    client.trade_from_balance(&trader, &token_id, &200, &recipient);
+    assert_eq!(client.balance_of(&trader, &token_id), 500);
+    tripped(&client);
+    assert_eq!(client.trade_escrow(&trader, &token_id, &200, &recipient, &0), BREAKER_TRIPPED);
+    tripped(&client);
+    client.execute_scheduled(&schedule_id);
+    assert_eq!(client.scheduled_trade(&schedule_id).status, ScheduleStatus::Pending);
+    tripped(&client);
+
+    // A trip inside `batch_trade` fails that request and every later one
+    let mut requests = Vec::new(&env);
+    for (trader, fee_amount) in [(trader.clone(), 200), (Address::generate(&env), 10)] {
+        requests.push_back(BatchTradeRequest {
+            trader,
+            pair: Symbol::new(&env, "XLMUSDC"),
+            amount: 10_000,
+            price: 10,
+            is_buy: true,
+            fee_token: token_id.clone(),
+            fee_amount,
+            fee_recipient: recipient.clone(),
+        });
+    }
+    let result = client.batch_trade(&requests);
+    assert_eq!(result.successful_trades.len(), 0);
+    for entry in result.failed_trades.iter() {
+        assert_eq!(entry.error_code, Some(TradingError::Paused as u32));
+    }
+    tripped(&client);
+
+    assert_eq!(token_client.balance(&trader), 300);
+    assert_eq!(token_client.balance(&recipient), 0);
+    assert_eq!(client.total_fees(&token_id), 0);
+}
+
+#[test]
+fn test_can_trade_reports_first_failing_check() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    let trader = Address::generate(&env);
+    token_admin.mint(&trader, &100);
+
+    assert_eq!(client.can_trade(&trader, &token_id, &50, &10_000), TradeCheck::TokenNotAllowed);
+    client.allow_fee_token(&admin, &token_id);
+    assert_eq!(client.can_trade(&trader, &token_id, &50, &10_000), TradeCheck::Ok);
+
+    assert_eq!(client.can_trade(&trader, &token_id, &-1, &10_000), TradeCheck::InvalidFee);
+    assert_eq!(client.can_trade(&trader, &token_id, &101, &10_000), TradeCheck::InsufficientBalance);
+
+    client.set_fee_bounds(&admin, &10, &80);
+    assert_eq!(client.can_trade(&trader, &token_id, &5, &10_000), TradeCheck::FeeOutOfBounds);
+    assert_eq!(client.can_trade(&trader, &token_id, &90, &10_000), TradeCheck::FeeOutOfBounds);
+
+    client.set_daily_limit(&admin, &40);
+    assert_eq!(client.can_trade(&trader, &token_id, &50, &10_000), TradeCheck::RateLimited);
+    assert_eq!(client.can_trade(&trader, &token_id, &40, &10_000), TradeCheck::Ok);
+
+    client.pause_operation(&admin, &PAUSE_TRADE);
+    assert_eq!(client.can_trade(&trader, &token_id, &40, &10_000), TradeCheck::Paused);
+
+    // Nothing was transferred or recorded
+    assert_eq!(token_client.balance(&trader), 100);
+    assert_eq!(client.get_stats().total_trades, 0);
+}
+
+#[test]
+fn test_trade_with_deadline_rejects_stale_trades() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    set_timestamp(&env, 1_000);
+    client.trade_with_deadline(&trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &1_060);
+    // Exactly at the deadline is still accepted
+    client.trade_with_deadline(&trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &1_000);
+    assert_eq!(token_client.balance(&trader), 800);
+
+    set_timestamp(&env, 1_061);
+    let result =
+        client.try_trade_with_deadline(&trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &1_060);
+    assert_eq!(result, Err(Ok(TradingError::Expired)));
+    assert_eq!(token_client.balance(&trader), 800);
+    assert_eq!(client.get_stats().total_trades, 2);
+}
+
+#[test]
+fn test_events_decode_into_typed_structs() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (token_id, _token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    set_timestamp(&env, 12_345);
+
+    let pair = Symbol::new(&env, "XLMUSDC");
+    client.trade(&trader, &pair, &10_000, &7, &false, &token_id, &fee_recipient, &None, &None);
+
+    let (_, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(topics, (symbol_short!("trade"),).into_val(&env));
+    let event: TradeExecutedEvent = data.into_val(&env);
+    assert_eq!(
+        event,
+        TradeExecutedEvent {
+            trade_id: 1,
+            trader,
+            pair,
+            amount: 10_000,
+            price: 7,
+            is_buy: false,
+            fee_amount: 100,
+            fee_token: token_id.clone(),
+            settle_token: token_id,
+            tag: Symbol::new(&env, ""),
+            timestamp: 12_345,
         }
-        false
-    });
-    assert!(has_trade_event, "Trade event not found");
+    );
 
-    let has_fee_event = events.iter().any(|(_, topics, _): (_, _, _)| {
-        if let Some(first_topic) = topics.first() {
-            let topic_sym: Symbol = first_topic.clone().into_val(&env);
-            return topic_sym == symbol_short!("fee");
+    client.pause_operation(&admin, &PAUSE_REWARD);
+    let (_, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(topics, (symbol_short!("paused"),).into_val(&env));
+    let event: ContractPausedEvent = data.into_val(&env);
+    assert_eq!(
+        event,
+        ContractPausedEvent {
+            paused_by: admin,
+            flags: PAUSE_REWARD,
+            reason: Symbol::new(&env, ""),
+            timestamp: 12_345,
         }
-        false
-    });
-    assert!(has_fee_event, "Fee event not found");
+    );
+}
+
+#[test]
+fn test_reward_contract_allowlist_is_capped() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let first = env.register_contract(None, VersionedReward);
+    client.add_reward_contract(&admin, &first);
+    // Re-adding an entry does not count twice
+    client.add_reward_contract(&admin, &first);
+    assert_eq!(client.reward_contract_count(), 1);
+
+    for _ in 1..MAX_REWARD_CONTRACTS {
+        client.add_reward_contract(&admin, &env.register_contract(None, VersionedReward));
+    }
+    assert_eq!(client.reward_contract_count(), MAX_REWARD_CONTRACTS);
+
+    let extra = env.register_contract(None, VersionedReward);
+    assert_eq!(client.try_add_reward_contract(&admin, &extra), Err(Ok(TradingError::AllowlistFull)));
+    assert!(!client.is_reward_contract_allowed(&extra));
+    assert!(client.is_reward_contract_allowed(&first));
+    client.add_reward_contract(&admin, &first);
+
+    // Removing an entry frees a slot
+    client.remove_reward_contract(&admin, &first);
+    assert_eq!(client.reward_contract_count(), MAX_REWARD_CONTRACTS - 1);
+    client.add_reward_contract(&admin, &extra);
+    assert!(client.is_reward_contract_allowed(&extra));
+}
+
+#[test]
+fn test_token_fee_bps_overrides_global_rate() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (cheap_id, cheap_client, cheap_admin) = setup_fee_token(&env);
+    let (plain_id, plain_client, plain_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &cheap_id);
+    client.allow_fee_token(&admin, &plain_id);
+
+    assert_eq!(client.try_set_token_fee_bps(&admin, &cheap_id, &10_001), Err(Ok(TradingError::InvalidFeeBps)));
+    client.set_token_fee_bps(&admin, &cheap_id, &25);
+    assert_eq!(client.token_fee_bps(&cheap_id), 25);
+    assert_eq!(client.token_fee_bps(&plain_id), 100);
+    assert_eq!(client.quote_fee(&10_000, &cheap_id), 25);
+    assert_eq!(client.quote_fee(&10_000, &plain_id), 100);
+
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    cheap_admin.mint(&trader, &1000);
+    plain_admin.mint(&trader, &1000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    client.trade(&trader, &pair, &10_000, &10, &true, &cheap_id, &fee_recipient, &None, &None);
+    client.trade(&trader, &pair, &10_000, &10, &true, &plain_id, &fee_recipient, &None, &None);
+    assert_eq!(cheap_client.balance(&trader), 975);
+    assert_eq!(plain_client.balance(&trader), 900);
+
+    // Changing the global rate leaves the override alone
+    client.set_fee_bps(&admin, &200);
+    assert_eq!(client.token_fee_bps(&cheap_id), 25);
+    assert_eq!(client.token_fee_bps(&plain_id), 200);
+}
+
+#[test]
+fn test_new_trade_entrypoints_honor_pause() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    let pauser = Address::generate(&env);
+    client.grant_role(&admin, &pauser, &TradingRole::Pauser);
+    client.pause_operation(&pauser, &PAUSE_TRADE);
+
+    let result = client.try_trade_with_nonce(&trader, &0, &pair, &10_000, &10, &true, &token_id, &fee_recipient);
+    assert_eq!(result, Err(Ok(TradingError::Paused)));
+    let result =
+        client.try_trade_with_deadline(&trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &u64::MAX);
+    assert_eq!(result, Err(Ok(TradingError::Paused)));
+
+    // The rejected call consumed no nonce and moved no funds
+    assert_eq!(client.current_nonce(&trader), 0);
+    assert_eq!(token_client.balance(&trader), 1000);
+
+    client.unpause_operation(&pauser, &PAUSE_TRADE);
+    client.trade_with_nonce(&trader, &0, &pair, &10_000, &10, &true, &token_id, &fee_recipient);
+    assert_eq!(client.current_nonce(&trader), 1);
+}
+
+#[test]
+fn test_recent_trades_ring_buffer_wraps_and_paginates() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, _token_client, _token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    assert_eq!(client.recent_trades(&0, &10).len(), 0);
+
+    // Filling the buffer takes more calls than the default test budget allows
+    env.budget().reset_unlimited();
+    let capacity = TradingStorage::MAX_RECENT_TRADES as u64;
+    let total = capacity + 4;
+    for i in 1..=total {
+        client.trade(&trader, &pair, &(i as i128), &10, &true, &token_id, &fee_recipient, &None, &None);
+    }
+
+    // Newest first
+    let page = client.recent_trades(&0, &3);
+    assert_eq!(page.len(), 3);
+    assert_eq!(page.get(0).unwrap().trade_id, total);
+    assert_eq!(page.get(0).unwrap().amount, total as i128);
+    assert_eq!(page.get(2).unwrap().trade_id, total - 2);
+
+    // The buffer wrapped: only the last `capacity` trades are retained
+    let all = client.recent_trades(&0, &u32::MAX);
+    assert_eq!(all.len() as u64, capacity);
+    assert_eq!(all.get(all.len() - 1).unwrap().trade_id, total - capacity + 1);
+
+    // A page running past the oldest entry is truncated
+    let tail = client.recent_trades(&(capacity as u32 - 2), &10);
+    assert_eq!(tail.len(), 2);
+    assert_eq!(tail.get(0).unwrap().trade_id, total - capacity + 2);
+    assert_eq!(tail.get(1).unwrap().trade_id, total - capacity + 1);
+    assert_eq!(client.recent_trades(&(capacity as u32), &10).len(), 0);
+}
+
+#[test]
+fn test_terminate_disables_every_mutating_call() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver.clone());
+    init_contract(&client, &admin, approvers.clone(), &executor);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+    let reward_id = env.register_contract(None, MockRewardContract);
+    client.add_reward_contract(&admin, &reward_id);
+    let new_admin = Address::generate(&env);
+    client.propose_admin(&admin, &new_admin);
+    let pauser = Address::generate(&env);
+    client.grant_role(&admin, &pauser, &TradingRole::Pauser);
+
+    // Termination is only allowed from a paused state
+    assert_eq!(client.try_terminate(&admin), Err(Ok(TradingError::NotPaused)));
+    client.pause(&admin);
+    assert_eq!(client.try_terminate(&pauser), Err(Ok(TradingError::NotAdmin)));
+    client.terminate(&admin);
+    assert!(client.is_terminated());
+
+    // Unpausing does not bring the contract back
+    assert_eq!(client.try_unpause(&admin), Err(Ok(TradingError::Terminated)));
+    assert_eq!(client.try_unpause_operation(&pauser, &PAUSE_TRADE), Err(Ok(TradingError::Terminated)));
+    assert_eq!(client.try_set_pause_until(&admin, &env.ledger().sequence()), Err(Ok(TradingError::Terminated)));
+    assert_eq!(client.try_pause_operation(&pauser, &PAUSE_REWARD), Err(Ok(TradingError::Terminated)));
+    assert_eq!(client.try_terminate(&admin), Err(Ok(TradingError::Terminated)));
+
+    // Trades
+    assert_eq!(client.try_trade(&trader, &pair, &100, &10, &true, &token_id, &fee_recipient, &None, &None), Err(Ok(TradingError::Terminated)));
+    assert_eq!(
+        client.try_trade_with_nonce(&trader, &0, &pair, &100, &10, &true, &token_id, &fee_recipient),
+        Err(Ok(TradingError::Terminated))
+    );
+    assert_eq!(
+        client.try_trade_and_reward(
+            &trader, &pair, &100, &10, &true, &token_id, &fee_recipient, &reward_id, &25, &None,
+        ),
+        Err(Ok(TradingError::Terminated))
+    );
+    assert_eq!(client.try_batch_trade(&Vec::new(&env)), Err(Ok(TradingError::Terminated)));
+
+    // Admin configuration and funds
+    assert_eq!(client.try_set_fee_bps(&admin, &100), Err(Ok(TradingError::Terminated)));
+    assert_eq!(client.try_allow_fee_token(&admin, &token_id), Err(Ok(TradingError::Terminated)));
+    assert_eq!(client.try_remove_reward_contract(&admin, &reward_id), Err(Ok(TradingError::Terminated)));
+    assert_eq!(client.try_grant_role(&admin, &trader, &TradingRole::Pauser), Err(Ok(TradingError::Terminated)));
+    assert_eq!(client.try_withdraw_fees(&admin, &token_id, &admin, &1), Err(Ok(TradingError::Terminated)));
+    assert_eq!(client.try_sweep(&admin, &token_id, &admin, &1), Err(Ok(TradingError::Terminated)));
+    assert_eq!(client.try_execute_pending_change(), Err(Ok(TradingError::Terminated)));
+
+    // Admin handover, governance and oracle bookkeeping
+    assert_eq!(client.try_accept_admin(&new_admin), Err(Ok(TradingError::Terminated)));
+    assert_eq!(
+        client.try_propose_upgrade(&admin, &symbol_short!("v2hash"), &symbol_short!("Upgrade"), &approvers, &1, &3600),
+        Err(Ok(TradingError::Terminated))
+    );
+    assert_eq!(client.try_approve_upgrade(&1, &approver), Err(Ok(TradingError::Terminated)));
+    assert_eq!(client.try_pause_upgrade_governance(&admin), Err(Ok(TradingError::Terminated)));
+    assert_eq!(client.try_resume_upgrade_governance(&admin), Err(Ok(TradingError::Terminated)));
+    assert_eq!(client.try_remove_operator(&trader, &pauser), Err(Ok(TradingError::Terminated)));
+    assert_eq!(client.try_record_oracle_failure(&pair), Err(Ok(TradingError::Terminated)));
+    assert_eq!(client.try_refresh_oracle_price(&pair).err(), Some(Ok(TradingError::Terminated)));
+
+    // Read-only views still report the final state
+    assert!(client.is_fee_token_allowed(&token_id));
+    assert_eq!(client.pause_flags(), PAUSE_ALL);
+    assert_eq!(token_client.balance(&trader), 1000);
+    assert!(client.is_terminated());
+}
+
+#[test]
+fn test_trade_with_separate_settle_token_only_moves_fee_token() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (fee_token_id, fee_token_client, fee_token_admin) = setup_fee_token(&env);
+    let (settle_token_id, settle_token_client, settle_token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &fee_token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    fee_token_admin.mint(&trader, &1000);
+    settle_token_admin.mint(&trader, &1000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    // The settle token must be allowlisted too
+    let result = client.try_trade(
+        &trader, &pair, &10_000, &10, &true, &fee_token_id, &fee_recipient, &Some(settle_token_id.clone()),
+        &None,
+    );
+    assert_eq!(result, Err(Ok(TradingError::TokenNotAllowed)));
+
+    client.allow_fee_token(&admin, &settle_token_id);
+    let trade_id = client.trade(
+        &trader, &pair, &10_000, &10, &true, &fee_token_id, &fee_recipient, &Some(settle_token_id.clone()),
+        &None,
+    );
+
+    // Only the fee token moved
+    assert_eq!(fee_token_client.balance(&trader), 900);
+    assert_eq!(fee_token_client.balance(&fee_recipient), 100);
+    assert_eq!(settle_token_client.balance(&trader), 1000);
+    assert_eq!(settle_token_client.balance(&fee_recipient), 0);
+
+    // The settle token is carried in the event, recent trades and stats
+    let (_, _, data) = env.events().all().last().unwrap();
+    let event: TradeExecutedEvent = data.into_val(&env);
+    assert_eq!(event.fee_token, fee_token_id);
+    assert_eq!(event.settle_token, settle_token_id);
+    let record = client.recent_trades(&0, &1).get(0).unwrap();
+    assert_eq!(record.trade_id, trade_id);
+    assert_eq!(record.settle_token, settle_token_id);
+    assert_eq!(client.settle_volume(&settle_token_id), 10_000);
+    assert_eq!(client.settle_volume(&fee_token_id), 0);
+    assert_eq!(client.total_fees(&fee_token_id), 100);
+    assert_eq!(client.total_fees(&settle_token_id), 0);
+}
+
+#[test]
+fn test_huge_trade_amount_reports_overflow() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    // `amount * bps` overflows before the division
+    let huge = i128::MAX / 50;
+    assert_eq!(client.try_quote_fee(&huge, &token_id), Err(Ok(TradingError::ArithmeticOverflow)));
+    let result = client.try_trade(&trader, &pair, &huge, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(result, Err(Ok(TradingError::ArithmeticOverflow)));
+    assert_eq!(token_client.balance(&trader), 1000);
+    assert_eq!(client.total_trades(), 0);
+
+    // Fee-free trades still cannot overflow the cumulative volume
+    client.set_fee_bps(&admin, &0);
+    client.trade(&trader, &pair, &i128::MAX, &10, &true, &token_id, &fee_recipient, &None, &None);
+    let result = client.try_trade(&trader, &pair, &1, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(result, Err(Ok(TradingError::ArithmeticOverflow)));
+    assert_eq!(client.total_trades(), 1);
+}
+
+#[test]
+fn test_health_reflects_pause_termination_and_ttl() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    assert_eq!(
+        client.health(),
+        HealthStatus { paused: false, terminated: false, initialized: false, instance_ttl_remaining: 0 }
+    );
+
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    assert_eq!(
+        client.health(),
+        HealthStatus {
+            paused: false,
+            terminated: false,
+            initialized: true,
+            instance_ttl_remaining: INSTANCE_TTL_EXTEND_TO,
+        }
+    );
+
+    client.pause(&admin);
+    advance_ledger(&env, 1_000);
+    let health = client.health();
+    assert!(health.paused);
+    assert_eq!(health.instance_ttl_remaining, INSTANCE_TTL_EXTEND_TO - 1_000);
+
+    // Close to expiry, any extension restores the full TTL
+    advance_ledger(&env, INSTANCE_TTL_EXTEND_TO - INSTANCE_TTL_THRESHOLD);
+    assert_eq!(client.health().instance_ttl_remaining, INSTANCE_TTL_THRESHOLD - 1_000);
+    client.bump_ttl();
+    assert_eq!(client.health().instance_ttl_remaining, INSTANCE_TTL_EXTEND_TO);
+
+    client.terminate(&admin);
+    let health = client.health();
+    assert!(health.paused);
+    assert!(health.terminated);
+    assert!(health.initialized);
+}
+
+#[test]
+fn test_trade_sponsored_debits_sponsor_and_credits_trader() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let sponsor = Address::generate(&env);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&sponsor, &1000);
+    token_admin.mint(&trader, &1000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    let trade_id = client.trade_sponsored(&sponsor, &trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient);
+
+    // Both the sponsor and the trader authorized the call
+    let signers: std::vec::Vec<Address> = env.auths().into_iter().map(|(address, _)| address).collect();
+    assert!(signers.contains(&sponsor));
+    assert!(signers.contains(&trader));
+
+    // Only the sponsor paid
+    assert_eq!(token_client.balance(&sponsor), 900);
+    assert_eq!(token_client.balance(&trader), 1000);
+    assert_eq!(token_client.balance(&fee_recipient), 100);
+
+    // The trade belongs to the trader
+    let trades = client.get_trades_by_trader(&trader);
+    assert_eq!(trades.len(), 1);
+    assert_eq!(trades.get(0).unwrap().id, trade_id);
+    assert_eq!(client.get_trades_by_trader(&sponsor).len(), 0);
+    assert_eq!(client.total_trades(), 1);
+    let (_, _, data) = env.events().all().last().unwrap();
+    let event: TradeExecutedEvent = data.into_val(&env);
+    assert_eq!(event.trader, trader);
+}
+
+#[test]
+fn test_admin_multisig_executes_at_threshold() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let carol = Address::generate(&env);
+    let mut signers = Vec::new(&env);
+    signers.push_back(alice.clone());
+    signers.push_back(bob.clone());
+    signers.push_back(carol.clone());
+    assert_eq!(client.try_set_admin_signers(&admin, &signers, &4), Err(Ok(TradingError::InvalidThreshold)));
+    client.set_admin_signers(&admin, &signers, &2);
+    assert_eq!(client.signers(), signers);
+    assert_eq!(client.threshold(), 2);
+
+    // The single admin key no longer controls fees, upgrades or the signer set
+    assert_eq!(client.try_set_fee_bps(&admin, &100), Err(Ok(TradingError::MultisigRequired)));
+    assert_eq!(
+        client.try_upgrade(&admin, &BytesN::from_array(&env, &[0; 32])),
+        Err(Ok(TradingError::MultisigRequired))
+    );
+    assert_eq!(client.try_set_admin_signers(&admin, &signers, &1), Err(Ok(TradingError::MultisigRequired)));
+
+    let stranger = Address::generate(&env);
+    assert_eq!(
+        client.try_propose_action(&stranger, &AdminAction::SetFeeBps(100)),
+        Err(Ok(TradingError::NotSigner))
+    );
+
+    // One approval is below the threshold
+    let action_id = client.propose_action(&alice, &AdminAction::SetFeeBps(100));
+    assert_eq!(client.fee_bps(), 0);
+    let pending = client.admin_action(&action_id).unwrap();
+    assert!(!pending.executed);
+    assert_eq!(pending.approvals.len(), 1);
+
+    // The proposer cannot approve twice
+    assert_eq!(client.try_approve_action(&alice, &action_id), Err(Ok(TradingError::AlreadyApproved)));
+    assert_eq!(client.fee_bps(), 0);
+
+    // The second distinct signer reaches the threshold and runs it
+    assert!(client.approve_action(&bob, &action_id));
+    assert_eq!(client.fee_bps(), 100);
+    // Logged under the signer whose approval ran it
+    let log = client.admin_log(&0, &10);
+    let entry = log.last().unwrap();
+    assert_eq!((entry.actor, entry.action, entry.detail), (bob.clone(), Symbol::new(&env, "set_fee_bps"), 100));
+    assert!(client.admin_action(&action_id).unwrap().executed);
+    assert_eq!(client.try_approve_action(&carol, &action_id), Err(Ok(TradingError::ActionAlreadyExecuted)));
+    assert_eq!(client.try_approve_action(&carol, &99), Err(Ok(TradingError::ActionNotFound)));
+}
+
+#[test]
+fn test_admin_multisig_upgrade_needs_pause() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let mut signers = Vec::new(&env);
+    signers.push_back(alice.clone());
+    signers.push_back(bob.clone());
+    client.set_admin_signers(&admin, &signers, &2);
+
+    let action_id = client.propose_action(&alice, &AdminAction::Upgrade(BytesN::from_array(&env, &[7; 32])));
+
+    // Reaching the threshold runs the upgrade's own checks; the failed
+    // attempt leaves the action pending
+    assert_eq!(client.try_approve_action(&bob, &action_id), Err(Ok(TradingError::NotPaused)));
+    let pending = client.admin_action(&action_id).unwrap();
+    assert!(!pending.executed);
+    assert_eq!(pending.approvals.len(), 1);
+}
+
+#[test]
+fn test_trade_tags_accumulate_fee_volume() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (token_id, _token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &10_000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+    let launch = Symbol::new(&env, "launch");
+    let mobile = Symbol::new(&env, "mobile");
+
+    client.trade(&trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &None, &Some(launch.clone()));
+    let (_, _, data) = env.events().all().last().unwrap();
+    let event: TradeExecutedEvent = data.into_val(&env);
+    assert_eq!(event.tag, launch);
+
+    client.trade(&trader, &pair, &20_000, &10, &true, &token_id, &fee_recipient, &None, &Some(launch.clone()));
+    client.trade(&trader, &pair, &5_000, &10, &true, &token_id, &fee_recipient, &None, &Some(mobile.clone()));
+    // Untagged trades count towards no tag
+    client.trade(&trader, &pair, &40_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+
+    assert_eq!(client.tag_volume(&launch), 300);
+    assert_eq!(client.tag_volume(&mobile), 50);
+    assert_eq!(client.tag_volume(&Symbol::new(&env, "other")), 0);
+    assert_eq!(client.total_fees(&token_id), 750);
+}
+
+#[test]
+fn test_decimals_are_cached_per_token() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, token_client, _token_admin) = setup_fee_token(&env);
+    assert_eq!(client.decimals(&token_id), Some(token_client.decimals()));
+
+    let counting_id = env.register_contract(None, CountingDecimalsToken);
+    let counting = CountingDecimalsTokenClient::new(&env, &counting_id);
+    assert_eq!(client.decimals(&counting_id), Some(6));
+    assert_eq!(client.decimals(&counting_id), Some(6));
+    assert_eq!(counting.decimals_calls(), 1);
+
+    // Contracts without a decimals method fall back to None
+    let no_decimals = env.register_contract(None, TestOracle);
+    assert_eq!(client.decimals(&no_decimals), None);
+}
+
+#[test]
+fn test_self_trade_rejected_unless_allowed() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    token_admin.mint(&trader, &1_000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    let result = client.try_trade(&trader, &pair, &10_000, &10, &true, &token_id, &trader, &None, &None);
+    assert_eq!(result, Err(Ok(TradingError::SelfTrade)));
+    assert!(!client.allow_self_trade());
+
+    let outsider = Address::generate(&env);
+    assert_eq!(
+        client.try_set_allow_self_trade(&outsider, &true),
+        Err(Ok(TradingError::NotAdmin))
+    );
+
+    client.set_allow_self_trade(&admin, &true);
+    assert!(client.allow_self_trade());
+    client.trade(&trader, &pair, &10_000, &10, &true, &token_id, &trader, &None, &None);
+    assert_eq!(client.total_trades(), 1);
+    assert_eq!(token_client.balance(&trader), 1_000);
+}
+
+#[test]
+fn test_fee_oracle_sets_trade_fee_with_fallback() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &10_000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    let oracle_id = env.register_contract(None, MockFeeOracle);
+    let oracle = MockFeeOracleClient::new(&env, &oracle_id);
+    client.set_fee_oracle(&admin, &Some(oracle_id.clone()));
+    assert_eq!(client.fee_oracle(), Some(oracle_id));
+
+    // The oracle has no rate yet, so the stored 1% applies
+    assert_eq!(client.quote_fee(&10_000, &token_id), 100);
+    client.trade(&trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(token_client.balance(&fee_recipient), 100);
+
+    oracle.set_bps(&250);
+    assert_eq!(client.quote_fee(&10_000, &token_id), 250);
+    client.trade(&trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(token_client.balance(&fee_recipient), 350);
+
+    // Out-of-range rates are ignored
+    oracle.set_bps(&20_000);
+    assert_eq!(client.quote_fee(&10_000, &token_id), 100);
+
+    client.set_fee_oracle(&admin, &None);
+    assert_eq!(client.fee_oracle(), None);
+}
+
+#[test]
+fn test_withdraw_fees_split_across_treasuries() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+    client.set_fee_sink(&admin, &FeeSinkMode::Accrue);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+    client.trade(&trader, &pair, &50_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(client.accrued_fees(&token_id), 500);
+
+    let ops = Address::generate(&env);
+    let grants = Address::generate(&env);
+    let reserve = Address::generate(&env);
+    let recipients = soroban_sdk::vec![&env, ops.clone(), grants.clone(), reserve.clone()];
+
+    assert_eq!(
+        client.try_withdraw_fees_split(&admin, &token_id, &recipients, &soroban_sdk::vec![&env, 200i128, 200i128, 101i128]),
+        Err(Ok(TradingError::InsufficientBalance))
+    );
+    assert_eq!(
+        client.try_withdraw_fees_split(&admin, &token_id, &recipients, &soroban_sdk::vec![&env, 200i128, 200i128]),
+        Err(Ok(TradingError::BatchLengthMismatch))
+    );
+    assert_eq!(
+        client.try_withdraw_fees_split(&admin, &token_id, &recipients, &soroban_sdk::vec![&env, 200i128, 0i128, 100i128]),
+        Err(Ok(TradingError::InvalidAmount))
+    );
+
+    client.withdraw_fees_split(&admin, &token_id, &recipients, &soroban_sdk::vec![&env, 250i128, 150i128, 60i128]);
+    assert_eq!(client.accrued_fees(&token_id), 40);
+    assert_eq!(token_client.balance(&contract_id), 40);
+    assert_eq!(token_client.balance(&ops), 250);
+    assert_eq!(token_client.balance(&grants), 150);
+    assert_eq!(token_client.balance(&reserve), 60);
+}
+
+#[test]
+fn test_trade_and_reward_rolls_back_on_missing_add_reward() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+
+    // An allowlisted contract that has no add_reward, as after a bad upgrade.
+    // The host reports the missing function like any other callee failure.
+    let reward_id = env.register_contract(None, VersionedReward);
+    client.add_reward_contract(&admin, &reward_id);
+
+    let result = client.try_trade_and_reward(
+        &trader,
+        &Symbol::new(&env, "XLMUSDC"),
+        &5000,
+        &10,
+        &true,
+        &token_id,
+        &fee_recipient,
+        &reward_id,
+        &10,
+        &None,
+    );
+
+    assert_eq!(result, Err(Ok(TradingError::RewardFailed)));
+    assert_eq!(token_client.balance(&trader), 1000);
+    assert_eq!(token_client.balance(&fee_recipient), 0);
+    assert_eq!(client.total_fees(&token_id), 0);
+    assert_eq!(client.total_trades(), 0);
+}
+
+#[test]
+fn test_trade_and_reward_reports_trapping_reward_as_failed() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+
+    let reward_id = env.register_contract_wasm(None, soroban_sdk::Bytes::from_slice(&env, &trapping_reward_wasm()));
+    client.add_reward_contract(&admin, &reward_id);
+
+    // The reward contract panics, which is a failed reward, not a changed interface
+    let result = client.try_trade_and_reward(
+        &trader,
+        &Symbol::new(&env, "XLMUSDC"),
+        &5000,
+        &10,
+        &true,
+        &token_id,
+        &fee_recipient,
+        &reward_id,
+        &10,
+        &None,
+    );
+    assert_eq!(result, Err(Ok(TradingError::RewardFailed)));
+    assert_eq!(token_client.balance(&trader), 1000);
+    assert_eq!(token_client.balance(&fee_recipient), 0);
+    assert_eq!(client.total_trades(), 0);
+
+    // Only a call classified as a missing function is an interface mismatch
+    let reward_error = UpgradeableTradingContract::reward_error;
+    assert_eq!(reward_error(safe_call_errors::FUNCTION_NOT_FOUND), TradingError::RewardIfaceMismatch);
+    assert_eq!(reward_error(safe_call_errors::CALLEE_CONTRACT_ERROR), TradingError::RewardFailed);
+    assert_eq!(reward_error(safe_call_errors::AUTH_FAILED), TradingError::RewardFailed);
+    assert_eq!(reward_error(safe_call_errors::UNKNOWN_FAILURE), TradingError::RewardFailed);
+}
+
+#[test]
+fn test_burn_bps_splits_fee_between_burn_and_recipient() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    assert_eq!(client.try_set_burn_bps(&admin, &10_001), Err(Ok(TradingError::InvalidFeeBps)));
+    assert_eq!(client.try_set_burn_bps(&admin, &u32::MAX), Err(Ok(TradingError::InvalidFeeBps)));
+    client.set_burn_bps(&admin, &3_333);
+    assert_eq!(client.burn_bps(), 3_333);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1_000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    // A 100 fee burns 33 and the rounding remainder goes to the recipient
+    client.trade(&trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(token_client.balance(&trader), 900);
+    assert_eq!(token_client.balance(&fee_recipient), 67);
+    assert_eq!(client.total_fees(&token_id), 100);
+
+    // The burned share left circulation rather than sitting anywhere
+    let circulating = token_client.balance(&trader)
+        + token_client.balance(&fee_recipient)
+        + token_client.balance(&contract_id);
+    assert_eq!(circulating, 967);
+
+    client.set_burn_bps(&admin, &10_000);
+    client.trade(&trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(token_client.balance(&trader), 800);
+    assert_eq!(token_client.balance(&fee_recipient), 67);
+}
+
+#[test]
+fn test_rebate_pool_accrues_and_pays_proportional_claims() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    // Burn and rebate shares together may not exceed the fee
+    client.set_burn_bps(&admin, &2_000);
+    assert_eq!(client.try_set_rebate_bps(&admin, &8_001), Err(Ok(TradingError::InvalidFeeBps)));
+    assert_eq!(client.try_set_rebate_bps(&admin, &u32::MAX), Err(Ok(TradingError::InvalidFeeBps)));
+    client.set_rebate_bps(&admin, &1_000);
+    assert_eq!(client.rebate_bps(), 1_000);
+    assert_eq!(client.try_set_burn_bps(&admin, &9_001), Err(Ok(TradingError::InvalidFeeBps)));
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&alice, &1_000);
+    token_admin.mint(&bob, &1_000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    // Each 100 fee burns 20, pools 10 and pays 70 to the recipient
+    for _ in 0..3 {
+        client.trade(&alice, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    }
+    client.trade(&bob, &pair, &20_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(token_client.balance(&fee_recipient), 350);
+    assert_eq!(client.rebate_pool_balance(&token_id), 50);
+    assert_eq!(token_client.balance(&contract_id), 50);
+
+    // Alice paid 300 of the 500 in fees behind the pool
+    assert_eq!(client.claim_rebate(&alice, &token_id), 30);
+    assert_eq!(token_client.balance(&alice), 730);
+    assert_eq!(client.claim_rebate(&alice, &token_id), 0);
+
+    assert_eq!(client.claim_rebate(&bob, &token_id), 20);
+    assert_eq!(token_client.balance(&bob), 820);
+    assert_eq!(client.rebate_pool_balance(&token_id), 0);
+    assert_eq!(token_client.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_escrow_releases_after_deadline() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1_000);
+    let mut thresholds = Vec::new(&env);
+    thresholds.push_back(200);
+    client.set_tier_thresholds(&admin, &thresholds);
+    client.set_daily_limit(&admin, &300);
+
+    let release_after = env.ledger().sequence() + 100;
+    let escrow_id = client.trade_escrow(&trader, &token_id, &250, &recipient, &release_after);
+    let record = client.escrow(&escrow_id);
+    assert_eq!(record.amount, 250);
+    assert_eq!(record.status, EscrowStatus::Held);
+    assert_eq!(token_client.balance(&trader), 750);
+    assert_eq!(token_client.balance(&contract_id), 250);
+    assert_eq!(client.escrow_held(&token_id), 250);
+
+    assert_eq!(client.try_release_escrow(&escrow_id), Err(Ok(TradingError::EscrowLocked)));
+
+    // Opening an escrow runs the same per-trader checks as a trade
+    let result = client.try_trade_escrow(&trader, &token_id, &100, &recipient, &release_after);
+    assert_eq!(result, Err(Ok(TradingError::RateLimited)));
+    let result = client.try_trade_escrow(&trader, &token_id, &10, &trader, &release_after);
+    assert_eq!(result, Err(Ok(TradingError::SelfTrade)));
+    assert_eq!(client.tier(&trader), Tier::None);
+
+    // Held fees are not stray tokens
+    client.pause(&admin);
+    assert_eq!(
+        client.try_sweep(&admin, &token_id, &admin, &1),
+        Err(Ok(TradingError::InsufficientBalance))
+    );
+    client.unpause(&admin);
+
+    advance_ledger(&env, 100);
+    client.release_escrow(&escrow_id);
+    assert_eq!(client.escrow(&escrow_id).status, EscrowStatus::Released);
+    assert_eq!(token_client.balance(&recipient), 250);
+    assert_eq!(client.escrow_held(&token_id), 0);
+    assert_eq!(client.total_fees(&token_id), 250);
+    assert_eq!(client.received_total(&recipient), 250);
+    assert_eq!(client.received_count(&recipient), 1);
+    assert_eq!(client.tier(&trader), Tier::Bronze);
+
+    assert_eq!(client.try_release_escrow(&escrow_id), Err(Ok(TradingError::EscrowClosed)));
+    assert_eq!(client.try_refund_escrow(&admin, &escrow_id), Err(Ok(TradingError::EscrowClosed)));
+    assert_eq!(client.try_escrow(&99), Err(Ok(TradingError::EscrowNotFound)));
+}
+
+#[test]
+fn test_escrow_admin_refund_before_deadline() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1_000);
+
+    let release_after = env.ledger().sequence() + 100;
+    let escrow_id = client.trade_escrow(&trader, &token_id, &400, &recipient, &release_after);
+
+    let outsider = Address::generate(&env);
+    assert_eq!(client.try_refund_escrow(&outsider, &escrow_id), Err(Ok(TradingError::NotAdmin)));
+
+    client.refund_escrow(&admin, &escrow_id);
+    assert_eq!(client.escrow(&escrow_id).status, EscrowStatus::Refunded);
+    assert_eq!(token_client.balance(&trader), 1_000);
+    assert_eq!(token_client.balance(&recipient), 0);
+    assert_eq!(client.escrow_held(&token_id), 0);
+    assert_eq!(client.total_fees(&token_id), 0);
+    assert_eq!(client.received_total(&recipient), 0);
+
+    // Once the window has passed the recipient's claim wins
+    let second = client.trade_escrow(&trader, &token_id, &100, &recipient, &release_after);
+    advance_ledger(&env, 100);
+    assert_eq!(client.try_refund_escrow(&admin, &second), Err(Ok(TradingError::Expired)));
+}
+
+#[test]
+fn test_scheduled_trade_executes_at_target_ledger() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1_000);
+
+    let execute_at = env.ledger().sequence() + 50;
+    let escrowed = client.schedule_trade(&trader, &Symbol::new(&env, "XLMUSDC"), &10_000, &10, &true, &token_id, &200, &recipient, &execute_at, &true);
+    assert_eq!(client.scheduled_trade(&escrowed).status, ScheduleStatus::Pending);
+    assert_eq!(token_client.balance(&trader), 800);
+    assert_eq!(client.escrow_held(&token_id), 200);
+
+    // Without escrow the fee is pulled from the allowance on execution
+    token_client.approve(&trader, &contract_id, &300, &(env.ledger().sequence() + 1000));
+    let deferred = client.schedule_trade(&trader, &Symbol::new(&env, "XLMUSDC"), &10_000, &10, &true, &token_id, &300, &recipient, &execute_at, &false);
+    assert_eq!(token_client.balance(&trader), 800);
+
+    assert_eq!(client.try_execute_scheduled(&escrowed), Err(Ok(TradingError::EscrowLocked)));
+    assert_eq!(client.try_execute_scheduled(&deferred), Err(Ok(TradingError::EscrowLocked)));
+
+    // Any keeper can execute; nobody signs the call
+    advance_ledger(&env, 50);
+    env.mock_auths(&[]);
+    client.execute_scheduled(&escrowed);
+    client.execute_scheduled(&deferred);
+    env.mock_all_auths();
+    assert_eq!(client.scheduled_trade(&escrowed).status, ScheduleStatus::Executed);
+    assert_eq!(client.scheduled_trade(&deferred).status, ScheduleStatus::Executed);
+    assert_eq!(token_client.balance(&recipient), 500);
+    assert_eq!(token_client.balance(&trader), 500);
+    assert_eq!(client.escrow_held(&token_id), 0);
+    assert_eq!(client.total_fees(&token_id), 500);
+    assert_eq!(client.received_total(&recipient), 500);
+    assert_eq!(client.total_trades(), 2);
+    assert_eq!(client.recent_trades(&0, &10).len(), 2);
+
+    assert_eq!(client.try_execute_scheduled(&escrowed), Err(Ok(TradingError::EscrowClosed)));
+    assert_eq!(client.try_cancel_scheduled(&trader, &escrowed), Err(Ok(TradingError::EscrowClosed)));
+    assert_eq!(client.try_scheduled_trade(&99), Err(Ok(TradingError::EscrowNotFound)));
+}
+
+#[test]
+fn test_execute_scheduled_applies_trade_checks() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1_000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+    let mut thresholds = Vec::new(&env);
+    thresholds.push_back(300);
+    client.set_tier_thresholds(&admin, &thresholds);
+
+    let small = client.schedule_trade(&trader, &pair, &100, &10, &true, &token_id, &100, &recipient, &0, &true);
+    let to_self = client.schedule_trade(&trader, &pair, &10_000, &10, &true, &token_id, &100, &trader, &0, &true);
+    let large = client.schedule_trade(&trader, &pair, &10_000, &10, &true, &token_id, &400, &recipient, &0, &true);
+    let second = client.schedule_trade(&trader, &pair, &10_000, &10, &true, &token_id, &100, &recipient, &0, &true);
+
+    // The same checks as `trade`, evaluated when the trade executes
+    client.set_min_trade_amount(&admin, &1_000);
+    assert_eq!(client.try_execute_scheduled(&small), Err(Ok(TradingError::BelowMinTrade)));
+    assert_eq!(client.try_execute_scheduled(&to_self), Err(Ok(TradingError::SelfTrade)));
+    client.set_daily_limit(&admin, &450);
+    let trade_id = client.execute_scheduled(&large);
+    assert_eq!(client.try_execute_scheduled(&second), Err(Ok(TradingError::RateLimited)));
+    assert_eq!(client.scheduled_trade(&second).status, ScheduleStatus::Pending);
+
+    // Executed trades count like any other
+    assert_eq!(client.get_trade(&trade_id).unwrap().trader, trader);
+    assert_eq!(client.received_total(&recipient), 400);
+    assert_eq!(client.received_count(&recipient), 1);
+    assert_eq!(client.tier(&trader), Tier::Bronze);
+    assert_eq!(token_client.balance(&recipient), 400);
+}
+
+#[test]
+fn test_cancel_scheduled_trade_returns_escrow() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1_000);
+
+    let execute_at = env.ledger().sequence() + 50;
+    let schedule_id = client.schedule_trade(&trader, &Symbol::new(&env, "XLMUSDC"), &10_000, &10, &true, &token_id, &400, &recipient, &execute_at, &true);
+    assert_eq!(token_client.balance(&trader), 600);
+
+    let outsider = Address::generate(&env);
+    assert_eq!(client.try_cancel_scheduled(&outsider, &schedule_id), Err(Ok(TradingError::Unauthorized)));
+
+    client.cancel_scheduled(&trader, &schedule_id);
+    assert_eq!(client.scheduled_trade(&schedule_id).status, ScheduleStatus::Cancelled);
+    assert_eq!(token_client.balance(&trader), 1_000);
+    assert_eq!(client.escrow_held(&token_id), 0);
+
+    advance_ledger(&env, 50);
+    assert_eq!(client.try_execute_scheduled(&schedule_id), Err(Ok(TradingError::EscrowClosed)));
+    assert_eq!(token_client.balance(&recipient), 0);
+}
+
+#[test]
+fn test_received_totals_per_recipient() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (token_id, _token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let ops = Address::generate(&env);
+    token_admin.mint(&trader, &10_000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    let reward_id = env.register_contract(None, MockRewardContract);
+    client.add_reward_contract(&admin, &reward_id);
+
+    client.trade(&trader, &pair, &10_000, &10, &true, &token_id, &treasury, &None, &None);
+    client.trade(&trader, &pair, &30_000, &10, &true, &token_id, &treasury, &None, &None);
+    client.trade_and_reward(&trader, &pair, &5_000, &10, &true, &token_id, &ops, &reward_id, &10, &None);
+
+    // A rolled-back trade_and_reward leaves the counters untouched
+    let result = client.try_trade_and_reward(&trader, &pair, &5_000, &10, &true, &token_id, &ops, &reward_id, &0, &None);
+    assert_eq!(result, Err(Ok(TradingError::RewardFailed)));
+
+    assert_eq!(client.received_total(&treasury), 400);
+    assert_eq!(client.received_count(&treasury), 2);
+    assert_eq!(client.received_total(&ops), 50);
+    assert_eq!(client.received_count(&ops), 1);
+    assert_eq!(client.received_total(&trader), 0);
+}
+
+#[test]
+fn test_deposit_trade_from_balance_and_withdraw() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    token_admin.mint(&trader, &1_000);
+
+    assert_eq!(client.deposit(&trader, &token_id, &600), 600);
+    assert_eq!(client.balance_of(&trader, &token_id), 600);
+    assert_eq!(token_client.balance(&trader), 400);
+    assert_eq!(token_client.balance(&contract_id), 600);
+
+    // Fees move between internal balances without token transfers
+    client.trade_from_balance(&trader, &token_id, &150, &treasury);
+    assert_eq!(client.balance_of(&trader, &token_id), 450);
+    assert_eq!(client.balance_of(&treasury, &token_id), 150);
+    assert_eq!(token_client.balance(&contract_id), 600);
+    assert_eq!(client.total_fees(&token_id), 150);
+    assert_eq!(client.received_total(&treasury), 150);
+
+    assert_eq!(
+        client.try_trade_from_balance(&trader, &token_id, &451, &treasury),
+        Err(Ok(TradingError::InsufficientBalance))
+    );
+    assert_eq!(
+        client.try_withdraw(&trader, &token_id, &451),
+        Err(Ok(TradingError::InsufficientBalance))
+    );
+
+    // Deposits cannot be swept as stray tokens
+    client.pause(&admin);
+    assert_eq!(
+        client.try_sweep(&admin, &token_id, &admin, &1),
+        Err(Ok(TradingError::InsufficientBalance))
+    );
+
+    // Withdrawals still work while paused
+    assert_eq!(client.withdraw(&trader, &token_id, &450), 0);
+    assert_eq!(client.withdraw(&treasury, &token_id, &150), 0);
+    assert_eq!(token_client.balance(&trader), 850);
+    assert_eq!(token_client.balance(&treasury), 150);
+    assert_eq!(token_client.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_unpause_waits_out_grace_period() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, _token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1_000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    client.set_unpause_delay(&admin, &50);
+    assert_eq!(client.unpause_delay(), 50);
+
+    // Pausing is instant
+    client.pause(&admin);
+    assert!(client.is_paused());
+
+    client.unpause(&admin);
+    let resume_ledger = env.ledger().sequence() + 50;
+    assert!(client.is_paused());
+    assert_eq!(client.pending_resume(), Some((PAUSE_ALL, resume_ledger)));
+
+    advance_ledger(&env, 49);
+    let result = client.try_trade(&trader, &pair, &1_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(result, Err(Ok(TradingError::Paused)));
+
+    advance_ledger(&env, 1);
+    assert!(!client.is_paused());
+    assert_eq!(client.pending_resume(), None);
+    client.trade(&trader, &pair, &1_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+
+    // Re-pausing during the grace period cancels the scheduled resumption
+    client.pause(&admin);
+    client.unpause(&admin);
+    client.pause(&admin);
+    advance_ledger(&env, 100);
+    assert!(client.is_paused());
+    assert_eq!(client.pending_resume(), None);
+
+    // Per-operation resumes wait as well
+    client.unpause(&admin);
+    advance_ledger(&env, 50);
+    client.pause_operation(&admin, &PAUSE_TRADE);
+    client.unpause_operation(&admin, &PAUSE_TRADE);
+    assert_eq!(client.pause_flags(), PAUSE_TRADE);
+    advance_ledger(&env, 50);
+    assert_eq!(client.pause_flags(), 0);
+}
+
+#[test]
+fn test_contract_balances_reports_each_token() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (usdc, _usdc_client, usdc_admin) = setup_fee_token(&env);
+    let (xlm, _xlm_client, xlm_admin) = setup_fee_token(&env);
+    usdc_admin.mint(&contract_id, &1_500);
+    xlm_admin.mint(&contract_id, &42);
+    let not_a_token = env.register_contract(None, TestOracle);
+
+    let balances = client.contract_balances(&soroban_sdk::vec![&env, usdc.clone(), xlm.clone(), not_a_token.clone()]);
+    assert_eq!(
+        balances,
+        soroban_sdk::vec![&env, (usdc, 1_500i128), (xlm, 42i128), (not_a_token, 0i128)]
+    );
+}
+
+#[test]
+fn test_failing_reward_contract_is_auto_disabled() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, _token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    let reward_id = env.register_contract(None, MockRewardContract);
+    let reward_client = MockRewardContractClient::new(&env, &reward_id);
+    client.add_reward_contract(&admin, &reward_id);
+    client.set_max_reward_failures(&admin, &3);
+
+    // A success resets the run of failures
+    reward_client.block(&trader);
+    client.trade_and_reward_refundable(&trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &reward_id, &10);
+    client.trade_and_reward_refundable(&trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &reward_id, &10);
+    assert_eq!(client.reward_failure_count(&reward_id), 2);
+    let other = Address::generate(&env);
+    token_admin.mint(&other, &1000);
+    client.trade_and_reward_refundable(&other, &pair, &5000, &10, &true, &token_id, &fee_recipient, &reward_id, &10);
+    assert_eq!(client.reward_failure_count(&reward_id), 0);
+
+    // Reverted failures do not count
+    let result = client.try_trade_and_reward(&trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &reward_id, &10, &None);
+    assert_eq!(result, Err(Ok(TradingError::RewardFailed)));
+    assert_eq!(client.reward_failure_count(&reward_id), 0);
+
+    for _ in 0..3 {
+        client.trade_and_reward_refundable(&trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &reward_id, &10);
+    }
+    assert!(!client.is_reward_contract_allowed(&reward_id));
+    let disabled = env.events().all().iter().any(|(emitter, topics, _)| {
+        emitter == contract_id
+            && Symbol::from_val(&env, &topics.first().unwrap()) == Symbol::new(&env, "reward_disabled")
+    });
+    assert!(disabled);
+
+    let result = client.try_trade_and_reward(&other, &pair, &5000, &10, &true, &token_id, &fee_recipient, &reward_id, &10, &None);
+    assert_eq!(result, Err(Ok(TradingError::RewardContractNotAllowed)));
+}
+
+#[test]
+fn test_trade_with_permit_settles_in_one_call() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let token_id = env.register_contract(None, PermitToken);
+    let token = PermitTokenClient::new(&env, &token_id);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token.mint(&trader, &1_000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+    let deadline = env.ledger().sequence() + 10;
+    let signature = BytesN::from_array(&env, &[7u8; 64]);
+
+    // The trader signs only the trade; the permit stands in for an approve
+    let args = (&trader, &pair, 10_000_i128, 10_i128, true, &token_id, &fee_recipient, deadline, signature.clone()).into_val(&env);
+    mock_only_auth(&env, &trader, &contract_id, "trade_with_permit", args);
+    let trade_id = client.trade_with_permit(
+        &trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &deadline, &signature,
+    );
+    env.mock_all_auths();
+    assert_eq!(trade_id, 1);
+    assert_eq!(token.balance(&trader), 900);
+    assert_eq!(token.balance(&fee_recipient), 100);
+    assert_eq!(token.balance(&contract_id), 0);
+    assert_eq!(client.total_fees(&token_id), 100);
+
+    let result = client.try_trade_with_permit(
+        &trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &deadline,
+        &BytesN::from_array(&env, &[0u8; 64]),
+    );
+    assert_eq!(result, Err(Ok(TradingError::PermitRejected)));
+
+    // Stellar Asset Contracts have no permit entrypoint
+    let (sac_id, _sac_client, sac_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &sac_id);
+    sac_admin.mint(&trader, &1_000);
+    let result = client.try_trade_with_permit(
+        &trader, &pair, &10_000, &10, &true, &sac_id, &fee_recipient, &deadline, &signature,
+    );
+    assert_eq!(result, Err(Ok(TradingError::PermitUnsupported)));
+}
+
+#[test]
+fn test_event_mode_controls_trade_events() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (token_id, _token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1_000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    // Events the trading contract has emitted so far
+    let contract_events = |env: &Env| -> std::vec::Vec<(Symbol, soroban_sdk::Val)> {
+        env.events()
+            .all()
+            .iter()
+            .filter(|(emitter, _, _)| *emitter == contract_id)
+            .map(|(_, topics, data)| (Symbol::from_val(env, &topics.first().unwrap()), data))
+            .collect()
+    };
+
+    assert_eq!(client.event_mode(), EventMode::Full);
+    let before = contract_events(&env).len();
+    client.trade(&trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    let events = contract_events(&env).split_off(before);
+    assert_eq!(events.len(), 2);
+    let trade_event: TradeExecutedEvent = events[1].1.into_val(&env);
+    assert_eq!(trade_event.trade_id, 1);
+
+    client.set_event_mode(&admin, &EventMode::Minimal);
+    let before = contract_events(&env).len();
+    client.trade(&trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    let events = contract_events(&env).split_off(before);
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].0, symbol_short!("fee"));
+    assert_eq!(events[1].0, symbol_short!("trade"));
+    assert!(events.iter().all(|(_, data)| data.is_void()));
+
+    client.set_event_mode(&admin, &EventMode::Off);
+    let before = contract_events(&env).len();
+    client.trade(&trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(contract_events(&env).len(), before);
+    assert_eq!(client.total_trades(), 3);
+
+    let outsider = Address::generate(&env);
+    assert_eq!(client.try_set_event_mode(&outsider, &EventMode::Full), Err(Ok(TradingError::NotAdmin)));
+}
+
+#[test]
+fn test_guardian_can_pause_but_not_unpause() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let guardian = Address::generate(&env);
+    client.grant_role(&admin, &guardian, &TradingRole::Guardian);
+    assert!(client.has_role(&guardian, &TradingRole::Guardian));
+    assert!(!client.has_role(&guardian, &TradingRole::Pauser));
+
+    client.pause(&guardian);
+    assert!(client.is_paused());
+    assert_eq!(client.try_unpause(&guardian), Err(Ok(TradingError::Unauthorized)));
+    assert_eq!(
+        client.try_set_pause_with_reason(&guardian, &false, &Symbol::new(&env, "")),
+        Err(Ok(TradingError::Unauthorized))
+    );
+    client.unpause(&admin);
+
+    // Single-operation pauses follow the same asymmetry
+    client.pause_operation(&guardian, &PAUSE_TRADE);
+    assert_eq!(client.pause_flags(), PAUSE_TRADE);
+    assert_eq!(client.try_unpause_operation(&guardian, &PAUSE_TRADE), Err(Ok(TradingError::Unauthorized)));
+
+    let pauser = Address::generate(&env);
+    client.grant_role(&admin, &pauser, &TradingRole::Pauser);
+    client.unpause_operation(&pauser, &PAUSE_TRADE);
+    assert!(!client.is_paused());
+}
+
+#[test]
+fn test_min_trade_amount_rejects_dust_trades() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    assert_eq!(client.try_set_min_trade_amount(&admin, &-1), Err(Ok(TradingError::InvalidAmount)));
+    client.set_min_trade_amount(&admin, &500);
+    assert_eq!(client.min_trade_amount(), 500);
+
+    // Exactly at the minimum is accepted
+    assert_eq!(client.can_trade(&trader, &token_id, &0, &500), TradeCheck::Ok);
+    client.trade(&trader, &pair, &500, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(client.total_trades(), 1);
+
+    assert_eq!(client.can_trade(&trader, &token_id, &0, &499), TradeCheck::BelowMinTrade);
+    let result = client.try_trade(&trader, &pair, &499, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(result, Err(Ok(TradingError::BelowMinTrade)));
+    assert_eq!(client.total_trades(), 1);
+    assert_eq!(token_client.balance(&trader), 1000);
+
+    let outsider = Address::generate(&env);
+    assert_eq!(client.try_set_min_trade_amount(&outsider, &0), Err(Ok(TradingError::NotAdmin)));
+}
+
+#[test]
+fn test_operator_trades_for_trader_until_revoked() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let operator = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    token_client.approve(&trader, &contract_id, &1000, &(env.ledger().sequence() + 1000));
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    // Not an operator yet
+    let result = client.try_trade_as(&operator, &trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient);
+    assert_eq!(result, Err(Ok(TradingError::Unauthorized)));
+
+    client.add_operator(&trader, &operator);
+    assert!(client.is_operator(&trader, &operator));
+
+    // The fee comes out of the trader's allowance and the trade is theirs
+    let args = (&operator, &trader, &pair, 10_000_i128, 10_i128, true, &token_id, &fee_recipient).into_val(&env);
+    mock_only_auth(&env, &operator, &contract_id, "trade_as", args);
+    let trade_id = client.trade_as(&operator, &trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient);
+    env.mock_all_auths();
+    assert!(env.auths().iter().any(|(address, _)| *address == operator));
+    assert!(env.auths().iter().all(|(address, _)| *address != trader));
+    assert_eq!(client.get_trade(&trade_id).unwrap().trader, trader);
+    assert_eq!(token_client.balance(&trader), 900);
+    assert_eq!(token_client.balance(&fee_recipient), 100);
+    assert_eq!(token_client.balance(&operator), 0);
+    assert_eq!(token_client.allowance(&trader, &contract_id), 900);
+
+    client.remove_operator(&trader, &operator);
+    assert!(!client.is_operator(&trader, &operator));
+    let result = client.try_trade_as(&operator, &trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient);
+    assert_eq!(result, Err(Ok(TradingError::Unauthorized)));
+    assert_eq!(client.total_trades(), 1);
+}
+
+#[test]
+fn test_trade_with_allowance_needs_no_trader_signature() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let relayer = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    token_client.approve(&trader, &contract_id, &1000, &(env.ledger().sequence() + 1000));
+
+    // An open allowance alone does not let arbitrary relayers spend it
+    let result = client.try_trade_with_allowance(&relayer, &trader, &pair, &10_000, &10, &true, &token_id, &relayer);
+    assert_eq!(result, Err(Ok(TradingError::Unauthorized)));
+    assert_eq!(token_client.balance(&trader), 1000);
+
+    client.approve_relayer(&trader, &relayer);
+    assert!(client.is_relayer(&trader, &relayer));
+    let stranger = Address::generate(&env);
+    let result = client.try_trade_with_allowance(&stranger, &trader, &pair, &10_000, &10, &true, &token_id, &stranger);
+    assert_eq!(result, Err(Ok(TradingError::Unauthorized)));
+
+    // The fee moves via transfer_from and only the relayer signs
+    let args = (&relayer, &trader, &pair, 10_000_i128, 10_i128, true, &token_id, &fee_recipient).into_val(&env);
+    mock_only_auth(&env, &relayer, &contract_id, "trade_with_allowance", args);
+    let trade_id = client.trade_with_allowance(&relayer, &trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient);
+    env.mock_all_auths();
+    assert!(env.auths().iter().any(|(address, _)| *address == relayer));
+    assert!(env.auths().iter().all(|(address, _)| *address != trader));
+    assert_eq!(client.get_trade(&trade_id).unwrap().trader, trader);
+    assert_eq!(token_client.balance(&trader), 900);
+    assert_eq!(token_client.balance(&fee_recipient), 100);
+    assert_eq!(token_client.allowance(&trader, &contract_id), 900);
+
+    // Revoking the allowance stops further trades
+    token_client.approve(&trader, &contract_id, &0, &env.ledger().sequence());
+    let result = client.try_trade_with_allowance(&relayer, &trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient);
+    assert_eq!(result, Err(Ok(TradingError::InsufficientBalance)));
+    assert_eq!(token_client.balance(&trader), 900);
+    assert_eq!(client.total_trades(), 1);
+
+    // So does revoking the relayer
+    token_client.approve(&trader, &contract_id, &1000, &(env.ledger().sequence() + 1000));
+    client.revoke_relayer(&trader, &relayer);
+    let result = client.try_trade_with_allowance(&relayer, &trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient);
+    assert_eq!(result, Err(Ok(TradingError::Unauthorized)));
+    assert_eq!(token_client.balance(&trader), 900);
+}
+
+#[test]
+fn test_max_fee_pct_caps_fee_against_balance() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    assert_eq!(client.try_set_max_fee_pct(&admin, &10_001), Err(Ok(TradingError::InvalidFeeBps)));
+    client.set_max_fee_pct(&admin, &1_000);
+    assert_eq!(client.max_fee_pct(), 1_000);
+
+    // A 110 fee is more than 10% of the 1000 balance
+    let result = client.try_trade(&trader, &pair, &11_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(result, Err(Ok(TradingError::FeeTooHigh)));
+    assert_eq!(token_client.balance(&trader), 1000);
+
+    // A 99 fee stays just under the cap
+    client.trade(&trader, &pair, &9_900, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(token_client.balance(&trader), 901);
+
+    client.set_max_fee_pct(&admin, &0);
+    client.trade(&trader, &pair, &11_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(token_client.balance(&trader), 791);
+}
+
+#[test]
+fn test_cooldown_spaces_out_trades_per_trader() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, _, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let other = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    token_admin.mint(&other, &1000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    set_timestamp(&env, 1_000);
+    client.set_cooldown(&admin, &60);
+    assert_eq!(client.next_allowed_trade(&trader), 0);
+    client.trade(&trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(client.next_allowed_trade(&trader), 1_060);
+
+    // Back-to-back trades are rejected; other traders are unaffected
+    set_timestamp(&env, 1_059);
+    let result = client.try_trade(&trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(result, Err(Ok(TradingError::RateLimited)));
+    assert_eq!(client.can_trade(&trader, &token_id, &0, &10_000), TradeCheck::RateLimited);
+    client.trade(&other, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+
+    set_timestamp(&env, 1_060);
+    client.trade(&trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(client.next_allowed_trade(&trader), 1_120);
+    assert_eq!(client.total_trades(), 3);
+
+    // Zero turns the cooldown off
+    client.set_cooldown(&admin, &0);
+    client.trade(&trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(client.next_allowed_trade(&trader), 0);
+}
+
+#[test]
+fn test_trade_rejects_under_delivering_fee_token() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let token_id = env.register_contract(None, LossyToken);
+    let token = LossyTokenClient::new(&env, &token_id);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token.mint(&trader, &1000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    // The recipient would only get 99 of the 100 fee
+    let result = client.try_trade(&trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(result, Err(Ok(TradingError::TransferAmountMismatch)));
+
+    // Everything rolled back
+    assert_eq!(token.balance(&trader), 1000);
+    assert_eq!(token.balance(&fee_recipient), 0);
+    assert_eq!(client.total_trades(), 0);
+    assert_eq!(client.total_fees(&token_id), 0);
+}
+
+#[test]
+fn test_add_reward_contract_checks_reward_version() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    assert_eq!(client.min_reward_version(), 1);
+
+    let reward_id = env.register_contract(None, VersionedReward);
+    let reward = VersionedRewardClient::new(&env, &reward_id);
+    reward.set_version(&2);
+    client.set_min_reward_version(&admin, &3);
+
+    // Older than the minimum
+    let result = client.try_add_reward_contract(&admin, &reward_id);
+    assert_eq!(result, Err(Ok(TradingError::RewardIfaceMismatch)));
+    assert!(!client.is_reward_contract_allowed(&reward_id));
+
+    // No reward_version at all
+    let oracle_id = env.register_contract(None, TestOracle);
+    let result = client.try_add_reward_contract(&admin, &oracle_id);
+    assert_eq!(result, Err(Ok(TradingError::RewardIfaceMismatch)));
+
+    // Compatible once it reports the minimum
+    reward.set_version(&3);
+    client.add_reward_contract(&admin, &reward_id);
+    assert!(client.is_reward_contract_allowed(&reward_id));
+}
+
+#[test]
+fn test_reset_config_restores_defaults_and_keeps_accrued_fees() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, _token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    client.set_fee_sink(&admin, &FeeSinkMode::Accrue);
+    client.set_fee_bps(&admin, &100);
+    let trader = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    client.trade(&trader, &Symbol::new(&env, "XLMUSDC"), &10_000, &10, &true, &token_id, &Address::generate(&env), &None, &None);
+    assert_eq!(client.accrued_fees(&token_id), 100);
+
+    client.set_token_fee_bps(&admin, &token_id, &50);
+    client.set_fee_bounds(&admin, &5, &500);
+    client.set_min_trade_amount(&admin, &1_000);
+    client.set_unpause_delay(&admin, &10);
+    client.set_reward_required(&admin, &false);
+    client.set_event_mode(&admin, &EventMode::Minimal);
+    client.set_allow_self_trade(&admin, &true);
+    let mut thresholds = Vec::new(&env);
+    thresholds.push_back(1_000);
+    client.set_tier_thresholds(&admin, &thresholds);
+    let reward_id = env.register_contract(None, MockRewardContract);
+    client.add_reward_contract(&admin, &reward_id);
+    client.pause_token(&admin, &token_id);
+
+    // Only while paused
+    assert_eq!(client.try_reset_config(&admin), Err(Ok(TradingError::NotPaused)));
+    client.pause(&admin);
+    let outsider = Address::generate(&env);
+    assert_eq!(client.try_reset_config(&outsider), Err(Ok(TradingError::NotAdmin)));
+
+    client.reset_config(&admin);
+    assert!(!client.is_paused());
+    assert_eq!(client.fee_bps(), 0);
+    assert_eq!(client.token_fee_bps(&token_id), 0);
+    assert_eq!(client.fee_bounds(), (0, i128::MAX));
+    assert_eq!(client.min_trade_amount(), 0);
+    assert_eq!(client.unpause_delay(), 0);
+    assert!(client.reward_required());
+    assert_eq!(client.event_mode(), EventMode::Full);
+    assert_eq!(client.fee_sink(), FeeSinkMode::Direct);
+    assert!(!client.allow_self_trade());
+    assert_eq!(client.tier_thresholds().len(), 0);
+    assert!(!client.is_token_paused(&token_id));
+    assert!(!client.is_fee_token_allowed(&token_id));
+    assert!(!client.is_reward_contract_allowed(&reward_id));
+    assert_eq!(client.reward_contract_count(), 0);
+
+    // Balances and governance are untouched
+    assert_eq!(client.accrued_fees(&token_id), 100);
+    assert_eq!(client.total_trades(), 1);
+    client.set_fee_bps(&admin, &10);
+}
+
+#[test]
+fn test_pause_token_blocks_only_that_token() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (bad_token, bad_client, bad_admin) = setup_fee_token(&env);
+    let (good_token, good_client, good_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &bad_token);
+    client.allow_fee_token(&admin, &good_token);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    bad_admin.mint(&trader, &1000);
+    good_admin.mint(&trader, &1000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+    client.deposit(&trader, &bad_token, &100);
+    let schedule_id = client.schedule_trade(&trader, &pair, &10_000, &10, &true, &bad_token, &10, &fee_recipient, &0, &true);
+
+    // Guardians can pause a token but only pausers can lift it
+    let guardian = Address::generate(&env);
+    client.grant_role(&admin, &guardian, &TradingRole::Guardian);
+    client.pause_token(&guardian, &bad_token);
+    assert!(client.is_token_paused(&bad_token));
+    assert!(!client.is_token_paused(&good_token));
+    assert!(!client.is_paused());
+    assert_eq!(client.try_unpause_token(&guardian, &bad_token), Err(Ok(TradingError::Unauthorized)));
+
+    let result = client.try_trade(&trader, &pair, &10_000, &10, &true, &bad_token, &fee_recipient, &None, &None);
+    assert_eq!(result, Err(Ok(TradingError::Paused)));
+    let result = client.try_trade(&trader, &pair, &10_000, &10, &true, &good_token, &fee_recipient, &Some(bad_token.clone()), &None);
+    assert_eq!(result, Err(Ok(TradingError::Paused)));
+    assert_eq!(client.can_trade(&trader, &bad_token, &100, &10_000), TradeCheck::Paused);
+
+    // Every other path that moves the token is blocked too
+    let mut recipients = Vec::new(&env);
+    recipients.push_back(fee_recipient.clone());
+    let mut weights = Vec::new(&env);
+    weights.push_back(10_000);
+    let mut fees = Vec::new(&env);
+    fees.push_back(10);
+    assert_eq!(client.try_trade_split(&trader, &bad_token, &10, &recipients, &weights), Err(Ok(TradingError::Paused)));
+    assert_eq!(client.try_batch_trade_atomic(&trader, &bad_token, &fees, &recipients), Err(Ok(TradingError::Paused)));
+    assert_eq!(client.try_deposit(&trader, &bad_token, &10), Err(Ok(TradingError::Paused)));
+    assert_eq!(client.try_trade_from_balance(&trader, &bad_token, &10, &fee_recipient), Err(Ok(TradingError::Paused)));
+    assert_eq!(client.try_trade_escrow(&trader, &bad_token, &10, &fee_recipient, &0), Err(Ok(TradingError::Paused)));
+    assert_eq!(client.try_schedule_trade(&trader, &pair, &10_000, &10, &true, &bad_token, &10, &fee_recipient, &0, &false), Err(Ok(TradingError::Paused)));
+    assert_eq!(client.try_execute_scheduled(&schedule_id), Err(Ok(TradingError::Paused)));
+
+    client.trade(&trader, &pair, &10_000, &10, &true, &good_token, &fee_recipient, &None, &None);
+    assert_eq!(good_client.balance(&trader), 900);
+    assert_eq!(bad_client.balance(&trader), 890);
+
+    client.unpause_token(&admin, &bad_token);
+    assert!(!client.is_token_paused(&bad_token));
+    client.trade(&trader, &pair, &10_000, &10, &true, &bad_token, &fee_recipient, &None, &None);
+    assert_eq!(bad_client.balance(&trader), 790);
+    client.execute_scheduled(&schedule_id);
+}
+
+#[test]
+fn test_admin_log_records_actions_in_order() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    assert_eq!(client.admin_log(&0, &10).len(), 0);
+
+    let token = Address::generate(&env);
+    let pauser = Address::generate(&env);
+    client.set_fee_bps(&admin, &25);
+    client.allow_fee_token(&admin, &token);
+    client.grant_role(&admin, &pauser, &TradingRole::Pauser);
+    advance_ledger(&env, 3);
+    client.pause(&pauser);
+    client.disallow_fee_token(&admin, &token);
+
+    let log = client.admin_log(&0, &10);
+    let actions: std::vec::Vec<(Address, Symbol, i128)> =
+        log.iter().map(|entry| (entry.actor, entry.action, entry.detail)).collect();
+    assert_eq!(
+        actions,
+        std::vec![
+            (admin.clone(), Symbol::new(&env, "set_fee_bps"), 25),
+            (admin.clone(), Symbol::new(&env, "allow_fee_token"), 0),
+            (admin.clone(), Symbol::new(&env, "grant_role"), TradingRole::Pauser as i128),
+            (pauser.clone(), Symbol::new(&env, "set_pause"), PAUSE_ALL as i128),
+            (admin.clone(), Symbol::new(&env, "disallow_fee_token"), 0),
+        ]
+    );
+    assert_eq!(log.get(3).unwrap().ledger, log.get(0).unwrap().ledger + 3);
+
+    // Paging
+    let page = client.admin_log(&3, &10);
+    assert_eq!(page.len(), 2);
+    assert_eq!(page.get(0).unwrap(), log.get(3).unwrap());
+    assert_eq!(client.admin_log(&1, &1).get(0).unwrap(), log.get(1).unwrap());
+    assert_eq!(client.admin_log(&5, &10).len(), 0);
+}
+
+#[test]
+fn test_rejected_fee_transfer_goes_to_fallback_recipient() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let token_id = env.register_contract(None, RejectingToken);
+    let token = RejectingTokenClient::new(&env, &token_id);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let fallback = Address::generate(&env);
+    token.mint(&trader, &1000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    client.set_fallback_recipient(&admin, &Some(fallback.clone()));
+    assert_eq!(client.fallback_recipient(), Some(fallback.clone()));
+
+    // Accepted transfers still go to the recipient
+    client.trade(&trader, &pair, &10_000, &10, &true, &token_id, &recipient, &None, &None);
+    assert_eq!(token.balance(&recipient), 100);
+    assert_eq!(token.balance(&fallback), 0);
+
+    token.block(&recipient);
+    client.trade(&trader, &pair, &10_000, &10, &true, &token_id, &recipient, &None, &None);
+    let rerouted = env.events().all().iter().find(|(_, topics, _)| {
+        Symbol::from_val(&env, &topics.get(0).unwrap()) == Symbol::new(&env, "fee_rerouted")
+    });
+    let (_, topics, data) = rerouted.unwrap();
+    assert_eq!(Address::from_val(&env, &topics.get(1).unwrap()), recipient);
+    assert_eq!(<(Address, i128)>::from_val(&env, &data), (fallback.clone(), 100));
+
+    assert_eq!(token.balance(&trader), 800);
+    assert_eq!(token.balance(&recipient), 100);
+    assert_eq!(token.balance(&fallback), 100);
+    assert_eq!(client.total_trades(), 2);
+}
+
+#[test]
+fn test_trade_auto_uses_cheapest_affordable_token() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (cheap_token, cheap_client, cheap_admin) = setup_fee_token(&env);
+    let (dear_token, dear_client, dear_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &cheap_token);
+    client.allow_fee_token(&admin, &dear_token);
+    client.set_token_fee_bps(&admin, &cheap_token, &50);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    dear_admin.mint(&trader, &1000);
+    cheap_admin.mint(&trader, &10);
+    let pair = Symbol::new(&env, "XLMUSDC");
+    let mut candidates = Vec::new(&env);
+    candidates.push_back(cheap_token.clone());
+    candidates.push_back(dear_token.clone());
+
+    // Only the pricier token covers its fee
+    let receipt = client.trade_auto(&trader, &pair, &10_000, &10, &true, &candidates, &fee_recipient);
+    assert_eq!(receipt.fee_token, dear_token);
+    assert_eq!(receipt.fee_paid, 100);
+    assert_eq!(dear_client.balance(&trader), 900);
+    assert_eq!(cheap_client.balance(&trader), 10);
+    assert_eq!(client.get_trade(&receipt.trade_id).unwrap().trader, trader);
+
+    // Once affordable, the lower fee wins
+    cheap_admin.mint(&trader, &90);
+    let receipt = client.trade_auto(&trader, &pair, &10_000, &10, &true, &candidates, &fee_recipient);
+    assert_eq!(receipt.fee_token, cheap_token);
+    assert_eq!(receipt.fee_paid, 50);
+    assert_eq!(cheap_client.balance(&trader), 50);
+    assert_eq!(dear_client.balance(&trader), 900);
+
+    // Nothing affordable, or nothing usable
+    let result = client.try_trade_auto(&trader, &pair, &1_000_000, &10, &true, &candidates, &fee_recipient);
+    assert_eq!(result, Err(Ok(TradingError::InsufficientBalance)));
+    let mut unlisted = Vec::new(&env);
+    unlisted.push_back(Address::generate(&env));
+    let result = client.try_trade_auto(&trader, &pair, &10_000, &10, &true, &unlisted, &fee_recipient);
+    assert_eq!(result, Err(Ok(TradingError::TokenNotAllowed)));
+    assert_eq!(client.total_trades(), 2);
+}
+
+#[test]
+fn test_list_allowlists() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    assert_eq!(client.list_fee_tokens().len(), 0);
+    assert_eq!(client.list_reward_contracts().len(), 0);
+
+    let tokens = [Address::generate(&env), Address::generate(&env), Address::generate(&env)];
+    let rewards = [
+        env.register_contract(None, VersionedReward),
+        env.register_contract(None, VersionedReward),
+        env.register_contract(None, VersionedReward),
+    ];
+    for (token, reward_id) in tokens.iter().zip(rewards.iter()) {
+        client.allow_fee_token(&admin, token);
+        client.add_reward_contract(&admin, reward_id);
+    }
+    // Re-adding an entry does not duplicate it
+    client.allow_fee_token(&admin, &tokens[0]);
+
+    client.disallow_fee_token(&admin, &tokens[1]);
+    client.remove_reward_contract(&admin, &rewards[0]);
+
+    let mut expected_tokens = Vec::new(&env);
+    expected_tokens.push_back(tokens[0].clone());
+    expected_tokens.push_back(tokens[2].clone());
+    assert_eq!(client.list_fee_tokens(), expected_tokens);
+
+    let mut expected_rewards = Vec::new(&env);
+    expected_rewards.push_back(rewards[1].clone());
+    expected_rewards.push_back(rewards[2].clone());
+    assert_eq!(client.list_reward_contracts(), expected_rewards);
+}
+
+#[test]
+fn test_fee_token_allowlist_is_capped() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let first = Address::generate(&env);
+    client.allow_fee_token(&admin, &first);
+    for _ in 1..MAX_FEE_TOKENS {
+        client.allow_fee_token(&admin, &Address::generate(&env));
+    }
+    assert_eq!(client.list_fee_tokens().len(), MAX_FEE_TOKENS);
+
+    let extra = Address::generate(&env);
+    assert_eq!(client.try_allow_fee_token(&admin, &extra), Err(Ok(TradingError::AllowlistFull)));
+    assert!(!client.is_fee_token_allowed(&extra));
+    // Re-allowing an existing token is still fine at the cap
+    client.allow_fee_token(&admin, &first);
+
+    client.disallow_fee_token(&admin, &first);
+    client.allow_fee_token(&admin, &extra);
+    assert_eq!(client.list_fee_tokens().len(), MAX_FEE_TOKENS);
+}
+
+#[test]
+fn test_initialize_full_applies_config_once() {
+    let _guard = ();
+    let (env, admin, _, _, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+
+    let fee_token = Address::generate(&env);
+    let mut fee_tokens = Vec::new(&env);
+    fee_tokens.push_back(fee_token.clone());
+    let reward_id = env.register_contract(None, VersionedReward);
+    let mut reward_contracts = Vec::new(&env);
+    reward_contracts.push_back(reward_id.clone());
+    let config = TradingConfig {
+        admin: admin.clone(),
+        paused: true,
+        fee_bps: 30,
+        min_fee: 5,
+        max_fee: 500,
+        version: 0,
+    };
+
+    let mut wrong_admin = config.clone();
+    wrong_admin.admin = Address::generate(&env);
+    assert_eq!(
+        client.try_initialize_full(&admin, &wrong_admin, &fee_tokens, &reward_contracts),
+        Err(Ok(TradingError::InvalidAdmin))
+    );
+
+    client.initialize_full(&admin, &config, &fee_tokens, &reward_contracts);
+
+    assert_eq!(client.config(), TradingConfig { version: CONTRACT_VERSION, ..config.clone() });
+    assert_eq!(client.fee_bps(), 30);
+    assert_eq!(client.fee_bounds(), (5, 500));
+    assert_eq!(client.list_fee_tokens(), fee_tokens);
+    assert_eq!(client.list_reward_contracts(), reward_contracts);
+    assert!(client.is_paused());
+
+    assert_eq!(
+        client.try_initialize_full(&admin, &config, &fee_tokens, &reward_contracts),
+        Err(Ok(TradingError::AlreadyInitialized))
+    );
+    assert_eq!(
+        client.try_init(&admin, &Vec::new(&env), &admin, &None),
+        Err(Ok(TradingError::AlreadyInitialized))
+    );
+}
+
+#[test]
+fn test_decimal_derived_min_fee() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &30);
+    client.set_min_nonzero_fee(&admin, &3);
+
+    let seven = env.register_contract(None, DecimalsToken);
+    DecimalsTokenClient::new(&env, &seven).set_decimals(&7);
+    let eighteen = env.register_contract(None, DecimalsToken);
+    DecimalsTokenClient::new(&env, &eighteen).set_decimals(&18);
+    // No decimals method, so the fixed floor applies
+    let opaque = Address::generate(&env);
+
+    assert_eq!(client.min_fee_mode(), MinFeeMode::Fixed);
+    assert_eq!(client.quote_fee(&100, &seven), 3);
+    assert_eq!(client.quote_fee(&100, &eighteen), 3);
+
+    client.set_min_fee_mode(&admin, &MinFeeMode::DecimalDerived(5));
+    assert_eq!(client.min_fee_mode(), MinFeeMode::DecimalDerived(5));
+    assert_eq!(client.quote_fee(&100, &seven), 100);
+    assert_eq!(client.quote_fee(&100, &eighteen), 10_000_000_000_000);
+    assert_eq!(client.quote_fee(&100, &opaque), 3);
+    // Fees that do not round to zero are unaffected
+    assert_eq!(client.quote_fee(&1_000_000, &seven), 3_000);
+
+    // A token with fewer decimals than n gets the smallest unit
+    client.set_min_fee_mode(&admin, &MinFeeMode::DecimalDerived(9));
+    assert_eq!(client.quote_fee(&100, &seven), 1);
+
+    // Quotes do not write the decimals cache; an explicit lookup does
+    let cached = |token: &Address| env.as_contract(&contract_id, || TradingStorage::get_token_decimals(&env, token));
+    assert_eq!(cached(&eighteen), None);
+    assert_eq!(client.decimals(&eighteen), Some(18));
+    assert_eq!(cached(&eighteen), Some(18));
+
+    // A floor too large for an i128 saturates, and the fee bounds still apply
+    let huge = env.register_contract(None, DecimalsToken);
+    DecimalsTokenClient::new(&env, &huge).set_decimals(&60);
+    assert_eq!(client.quote_fee(&100, &huge), i128::MAX);
+    client.set_fee_bounds(&admin, &0, &500);
+    assert_eq!(client.quote_fee(&100, &huge), 500);
+}
+
+#[test]
+fn test_admin_queries() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    assert_eq!(client.try_admin(), Err(Ok(TradingError::NotInitialized)));
+    assert!(!client.is_admin(&admin));
+
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver.clone());
+    init_contract(&client, &admin, approvers, &executor);
+
+    assert_eq!(client.admin(), admin);
+    assert!(client.is_admin(&admin));
+    assert!(!client.is_admin(&approver));
+    assert!(!client.is_admin(&Address::generate(&env)));
+    // Pure reads: no authorization was requested
+    assert!(env.auths().is_empty());
+}
+
+#[test]
+fn test_tier_up_fires_once_per_crossing() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (token_id, _, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let fee_recipient = Address::generate(&env);
+    let trader = Address::generate(&env);
+    token_admin.mint(&trader, &10_000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    let mut unordered = Vec::new(&env);
+    unordered.push_back(300);
+    unordered.push_back(100);
+    assert_eq!(client.try_set_tier_thresholds(&admin, &unordered), Err(Ok(TradingError::InvalidAmount)));
+    let mut thresholds = Vec::new(&env);
+    thresholds.push_back(100);
+    thresholds.push_back(300);
+    thresholds.push_back(600);
+    client.set_tier_thresholds(&admin, &thresholds);
+    assert_eq!(client.tier(&trader), Tier::None);
+
+    let tier_up = Symbol::new(&env, "tier_up");
+    // Each 5_000 trade pays a 50 fee
+    let expected = [
+        Tier::None, Tier::Bronze, Tier::Bronze, Tier::Bronze, Tier::Bronze, Tier::Silver,
+        Tier::Silver, Tier::Silver, Tier::Silver, Tier::Silver, Tier::Silver, Tier::Gold, Tier::Gold,
+    ];
+    let tier_up_events = || -> std::vec::Vec<Tier> {
+        env.events()
+            .all()
+            .iter()
+            .filter(|(_, topics, _)| *topics == (tier_up.clone(), trader.clone()).into_val(&env))
+            .map(|(_, _, data)| Tier::from_val(&env, &data))
+            .collect()
+    };
+    let mut tier_ups = 0;
+    for (index, tier) in expected.iter().enumerate() {
+        let seen = tier_up_events().len();
+        client.trade(&trader, &pair, &5_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+        let raised = tier_up_events().split_off(seen);
+        let previous = if index == 0 { Tier::None } else { expected[index - 1] };
+        if *tier != previous {
+            assert_eq!(raised, std::vec![*tier]);
+            tier_ups += 1;
+        } else {
+            assert!(raised.is_empty());
+        }
+        assert_eq!(client.tier(&trader), *tier);
+    }
+    assert_eq!(tier_ups, 3);
+    assert_eq!(client.trader_fee_volume(&trader), 650);
+}
+
+#[test]
+fn test_pause_unpause_authorization() {
+    let _guard = (); // serial_lock disabled
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let non_admin = Address::generate(&env);
+    let result = client.try_pause(&non_admin);
+    assert_eq!(result, Err(Ok(TradingError::Unauthorized)));
+
+    client.pause(&admin);
+    let result = client.try_unpause(&non_admin);
+    assert_eq!(result, Err(Ok(TradingError::Unauthorized)));
+
+    client.unpause(&admin);
+}
+
+#[test]
+fn test_pause_with_reason_records_info() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    assert_eq!(client.pause_info(), (false, Symbol::new(&env, ""), 0));
+
+    set_timestamp(&env, 1_000);
+    client.set_pause_with_reason(&admin, &true, &Symbol::new(&env, "oracle_exploit"));
+    assert_eq!(client.pause_info(), (true, Symbol::new(&env, "oracle_exploit"), 1_000));
+
+    // Unpausing overwrites the reason and timestamp
+    set_timestamp(&env, 2_500);
+    client.set_pause_with_reason(&admin, &false, &Symbol::new(&env, "patched"));
+    assert_eq!(client.pause_info(), (false, Symbol::new(&env, "patched"), 2_500));
+
+    // The plain wrappers record an empty reason
+    set_timestamp(&env, 3_000);
+    client.pause(&admin);
+    assert_eq!(client.pause_info(), (true, Symbol::new(&env, ""), 3_000));
+
+    let outsider = Address::generate(&env);
+    let result = client.try_set_pause_with_reason(&outsider, &false, &Symbol::new(&env, "x"));
+    assert_eq!(result, Err(Ok(TradingError::Unauthorized)));
+}
+
+#[test]
+fn test_last_pauser_tracks_latest_change() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let first = Address::generate(&env);
+    let second = Address::generate(&env);
+    client.grant_role(&admin, &first, &TradingRole::Pauser);
+    client.grant_role(&admin, &second, &TradingRole::Pauser);
+    assert_eq!(client.last_pauser(), None);
+
+    client.set_pause_with_reason(&first, &true, &Symbol::new(&env, "incident"));
+    assert_eq!(client.last_pauser(), Some(first.clone()));
+
+    client.unpause(&second);
+    assert_eq!(client.last_pauser(), Some(second.clone()));
+
+    client.pause(&first);
+    assert_eq!(client.last_pauser(), Some(first));
+}
+
+#[test]
+fn test_pauser_role_can_pause_but_not_set_fees() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let pauser = Address::generate(&env);
+    assert!(!client.has_role(&pauser, &TradingRole::Pauser));
+    assert_eq!(client.try_pause(&pauser), Err(Ok(TradingError::Unauthorized)));
+
+    client.grant_role(&admin, &pauser, &TradingRole::Pauser);
+    assert!(client.has_role(&pauser, &TradingRole::Pauser));
+
+    client.pause(&pauser);
+    client.unpause(&pauser);
+
+    let result = client.try_set_fee_bps(&pauser, &50);
+    assert_eq!(result, Err(Ok(TradingError::NotAdmin)));
+    assert_eq!(client.fee_bps(), 0);
+}
+
+#[test]
+fn test_revoked_pauser_is_rejected() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let pauser = Address::generate(&env);
+    client.grant_role(&admin, &pauser, &TradingRole::Pauser);
+    client.pause(&pauser);
+
+    // Only the admin manages roles
+    let result = client.try_revoke_role(&pauser, &pauser, &TradingRole::Pauser);
+    assert_eq!(result, Err(Ok(TradingError::NotAdmin)));
+    let result = client.try_grant_role(&pauser, &Address::generate(&env), &TradingRole::Pauser);
+    assert_eq!(result, Err(Ok(TradingError::NotAdmin)));
+
+    client.revoke_role(&admin, &pauser, &TradingRole::Pauser);
+    assert!(!client.has_role(&pauser, &TradingRole::Pauser));
+    assert_eq!(client.try_unpause(&pauser), Err(Ok(TradingError::Unauthorized)));
+
+    client.unpause(&admin);
+}
+
+#[test]
+fn test_two_step_admin_transfer() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let new_admin = Address::generate(&env);
+    client.propose_admin(&admin, &new_admin);
+    assert_eq!(client.pending_admin(), Some(new_admin.clone()));
+
+    // The old admin keeps operating until the proposal is accepted
+    client.pause(&admin);
+    client.unpause(&admin);
+    let result = client.try_pause(&new_admin);
+    assert_eq!(result, Err(Ok(TradingError::Unauthorized)));
+
+    client.accept_admin(&new_admin);
+    assert_eq!(client.pending_admin(), None);
+
+    client.pause(&new_admin);
+    let result = client.try_unpause(&admin);
+    assert_eq!(result, Err(Ok(TradingError::Unauthorized)));
+    client.unpause(&new_admin);
+}
+
+#[test]
+fn test_accept_admin_errors() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let new_admin = Address::generate(&env);
+    let stranger = Address::generate(&env);
+
+    let result = client.try_accept_admin(&new_admin);
+    assert_eq!(result, Err(Ok(TradingError::NoPendingAdmin)));
+
+    let result = client.try_propose_admin(&stranger, &new_admin);
+    assert_eq!(result, Err(Ok(TradingError::NotAdmin)));
+
+    client.propose_admin(&admin, &new_admin);
+    let result = client.try_accept_admin(&stranger);
+    assert_eq!(result, Err(Ok(TradingError::Unauthorized)));
+    assert_eq!(client.pending_admin(), Some(new_admin.clone()));
+
+    let result = client.try_cancel_admin_proposal(&stranger);
+    assert_eq!(result, Err(Ok(TradingError::NotAdmin)));
+
+    client.cancel_admin_proposal(&admin);
+    assert_eq!(client.pending_admin(), None);
+    let result = client.try_accept_admin(&new_admin);
+    assert_eq!(result, Err(Ok(TradingError::NoPendingAdmin)));
+    let result = client.try_cancel_admin_proposal(&admin);
+    assert_eq!(result, Err(Ok(TradingError::NoPendingAdmin)));
+}
+
+/// Smallest wasm the host accepts as contract code: an empty module that only
+/// declares the protocol 20 interface version in its `contractenvmetav0` section.
+const EMPTY_CONTRACT_WASM: &[u8] = &[
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // magic + version
+    0x00, 0x1e, 0x11, // custom section, 30 bytes, 17-byte name
+    b'c', b'o', b'n', b't', b'r', b'a', b'c', b't', b'e', b'n', b'v', b'm', b'e', b't', b'a', b'v', b'0',
+    0x00, 0x00, 0x00, 0x00, // ScEnvMetaEntry::InterfaceVersion
+    0x00, 0x00, 0x00, 0x14, 0x00, 0x00, 0x00, 0x00, // protocol 20, pre-release 0
+];
+
+#[test]
+fn test_upgrade_requires_admin_and_pause() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let wasm_hash = env.deployer().upload_contract_wasm(EMPTY_CONTRACT_WASM);
+
+    let result = client.try_upgrade(&admin, &wasm_hash);
+    assert_eq!(result, Err(Ok(TradingError::NotPaused)));
+
+    client.pause(&admin);
+    let non_admin = Address::generate(&env);
+    let result = client.try_upgrade(&non_admin, &wasm_hash);
+    assert_eq!(result, Err(Ok(TradingError::NotAdmin)));
+
+    client.upgrade(&admin, &wasm_hash);
+
+    // Instance state survives the code swap
+    let version = env.as_contract(&contract_id, || TradingStorage::get_version(&env));
+    assert_eq!(version, 2);
+}
+
+#[test]
+fn test_sweep_recovers_stuck_tokens_while_paused() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver.clone());
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    let rescue = Address::generate(&env);
+    token_admin.mint(&contract_id, &300);
+
+    // Refused while live
+    let result = client.try_sweep(&admin, &token_id, &rescue, &100);
+    assert_eq!(result, Err(Ok(TradingError::NotPaused)));
+
+    client.pause(&admin);
+
+    assert_eq!(client.try_sweep(&approver, &token_id, &rescue, &100), Err(Ok(TradingError::NotAdmin)));
+    assert_eq!(client.try_sweep(&admin, &token_id, &rescue, &0), Err(Ok(TradingError::InvalidAmount)));
+    assert_eq!(client.try_sweep(&admin, &token_id, &rescue, &301), Err(Ok(TradingError::InsufficientBalance)));
+
+    client.sweep(&admin, &token_id, &rescue, &300);
+    assert_eq!(token_client.balance(&rescue), 300);
+    assert_eq!(token_client.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_migrate_bumps_version() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    // Simulate state written by the previous contract version
+    env.as_contract(&contract_id, || TradingStorage::set_version(&env, 1));
+    assert_eq!(client.get_version(), 1);
+
+    let non_admin = Address::generate(&env);
+    assert_eq!(client.try_migrate(&non_admin), Err(Ok(TradingError::NotAdmin)));
+
+    assert_eq!(client.migrate(&admin), 2);
+    assert_eq!(client.get_version(), 2);
+
+    // Migrating an up-to-date contract is a no-op
+    assert_eq!(client.migrate(&admin), 2);
+}
+
+#[test]
+fn test_bump_ttl_keeps_admin_alive() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    // Instance storage starts with the minimum persistent TTL (4096 ledgers)
+    advance_ledger(&env, 4000);
+    client.bump_ttl();
+
+    // Well past the original expiry, the admin can still operate
+    advance_ledger(&env, 100_000);
+    client.pause(&admin);
+    client.unpause(&admin);
+    assert_eq!(client.get_version(), 2);
+}
+
+#[test]
+fn test_upgrade_proposal_flow_and_errors() {
+    let _guard = (); // serial_lock disabled
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver.clone());
+    init_contract(&client, &admin, approvers.clone(), &executor);
+
+    // Invalid threshold -> mapped to Unauthorized
+    let invalid = client.try_propose_upgrade(
+        &admin,
+        &symbol_short!("v2hash"),
+        &symbol_short!("Upgrade"),
+        &approvers,
+        &0,
+        &3600,
+    );
+    assert_eq!(invalid, Err(Ok(TradingError::Unauthorized)));
+
+    // Valid proposal
+    let proposal_id = client.propose_upgrade(
+        &admin,
+        &symbol_short!("v2hash"),
+        &symbol_short!("Upgrade"),
+        &approvers,
+        &1,
+        &3600,
+    );
+
+    let proposal = client.get_upgrade_proposal(&proposal_id);
+    assert_eq!(proposal.status, ProposalStatus::Pending);
+
+    client.approve_upgrade(&proposal_id, &approver);
+    let duplicate = client.try_approve_upgrade(&proposal_id, &approver);
+    assert_eq!(duplicate, Err(Ok(TradingError::Unauthorized)));
+    let proposal = client.get_upgrade_proposal(&proposal_id);
+    assert_eq!(proposal.status, ProposalStatus::Approved);
+
+    // Execute too early
+    let execute_err = client.try_execute_upgrade(&proposal_id, &executor);
+    assert_eq!(execute_err, Err(Ok(TradingError::Unauthorized)));
+
+    set_timestamp(&env, 1000 + 3601);
+    client.execute_upgrade(&proposal_id, &executor);
+
+    let proposal = client.get_upgrade_proposal(&proposal_id);
+    assert_eq!(proposal.status, ProposalStatus::Executed);
+
+    // Cancelling executed proposal should fail
+    let cancel_err = client.try_cancel_upgrade(&proposal_id, &admin);
+    assert_eq!(cancel_err, Err(Ok(TradingError::Unauthorized)));
+}
+
+#[test]
+fn test_reject_and_get_proposal_errors() {
+    let _guard = (); // serial_lock disabled
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver.clone());
+    init_contract(&client, &admin, approvers.clone(), &executor);
+
+    let proposal_id = client.propose_upgrade(
+        &admin,
+        &symbol_short!("v2hash"),
+        &symbol_short!("Upgrade"),
+        &approvers,
+        &1,
+        &3600,
+    );
+
+    client.reject_upgrade(&proposal_id, &approver);
+    let proposal = client.get_upgrade_proposal(&proposal_id);
+    assert_eq!(proposal.status, ProposalStatus::Rejected);
+
+    let missing = client.try_get_upgrade_proposal(&999);
+    assert_eq!(missing, Err(Ok(TradingError::Unauthorized)));
+}
+
+#[test]
+fn test_upgrade_governance_pause_and_timelock_validation() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver.clone());
+    init_contract(&client, &admin, approvers.clone(), &executor);
+
+    let invalid_timelock = client.try_propose_upgrade(
+        &admin,
+        &symbol_short!("v2hash"),
+        &symbol_short!("Upgrade"),
+        &approvers,
+        &1,
+        &3599,
+    );
+    assert_eq!(invalid_timelock, Err(Ok(TradingError::Unauthorized)));
+
+    client.pause_upgrade_governance(&admin);
+
+    let paused_proposal = client.try_propose_upgrade(
+        &admin,
+        &symbol_short!("v2hash"),
+        &symbol_short!("Upgrade"),
+        &approvers,
+        &1,
+        &3600,
+    );
+    assert_eq!(paused_proposal, Err(Ok(TradingError::Unauthorized)));
+
+    client.resume_upgrade_governance(&admin);
+
+    let proposal_id = client.propose_upgrade(
+        &admin,
+        &symbol_short!("v2hash"),
+        &symbol_short!("Upgrade"),
+        &approvers,
+        &1,
+        &3600,
+    );
+
+    let proposal = client.get_upgrade_proposal(&proposal_id);
+    assert_eq!(proposal.status, ProposalStatus::Pending);
+}
+
+#[test]
+fn test_oracle_refresh_updates_status() {
+    let _guard = (); // serial_lock disabled
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let oracle_id = env.register_contract(None, TestOracle);
+    let mut oracles = Vec::new(&env);
+    oracles.push_back(oracle_id);
+
+    client.set_oracle_config(&admin, &oracles, &100, &1);
+
+    let pair = Symbol::new(&env, "XLMUSDC");
+    let aggregate = client.refresh_oracle_price(&pair);
+
+    assert_eq!(aggregate.median_price, 100);
+    assert_eq!(aggregate.source_count, 1);
+
+    let status = client.get_oracle_status();
+    assert_eq!(status.last_price, 100);
+    assert_eq!(status.last_source_count, 1);
+    assert_eq!(status.consecutive_failures, 0);
+}
+
+// =============================================================================
+// Batch Operations Tests
+// =============================================================================
+
+#[test]
+fn test_batch_trade_happy_path() {
+    let _guard = (); // serial_lock disabled
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader1 = Address::generate(&env);
+    let trader2 = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+
+    // Mint tokens for traders
+    token_admin.mint(&trader1, &1000);
+    token_admin.mint(&trader2, &1000);
+
+    // Create batch requests
+    let mut requests = Vec::new(&env);
+    requests.push_back(BatchTradeRequest {
+        trader: trader1.clone(),
+        pair: Symbol::new(&env, "XLMUSDC"),
+        amount: 250,
+        price: 10,
+        is_buy: true,
+        fee_token: token_id.clone(),
+        fee_amount: 100,
+        fee_recipient: fee_recipient.clone(),
+    });
+    requests.push_back(BatchTradeRequest {
+        trader: trader2.clone(),
+        pair: Symbol::new(&env, "XLMUSDC"),
+        amount: 150,
+        price: 12,
+        is_buy: false,
+        fee_token: token_id.clone(),
+        fee_amount: 50,
+        fee_recipient: fee_recipient.clone(),
+    });
+
+    let result = client.batch_trade(&requests);
+
+    assert_eq!(result.successful_trades.len(), 2);
+    assert_eq!(result.failed_trades.len(), 2); // All results are stored in failed_trades
+    assert_eq!(result.total_fees_collected, 150);
+    assert!(result.gas_saved > 0);
+
+    // Check token balances
+    assert_eq!(token_client.balance(&trader1), 900);
+    assert_eq!(token_client.balance(&trader2), 950);
+    assert_eq!(token_client.balance(&fee_recipient), 150);
+
+    // Check stats
+    let stats = client.get_stats();
+    assert_eq!(stats.total_trades, 2);
+    assert_eq!(stats.total_volume, 400);
+    assert_eq!(stats.last_trade_id, 2);
+}
+
+#[test]
+fn test_batch_trade_size_limit() {
+    let _guard = (); // serial_lock disabled
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, _token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+
+    token_admin.mint(&trader, &50000); // Enough for many trades
+
+    // Create batch with more than MAX_BATCH_SIZE (50) requests
+    let mut requests = Vec::new(&env);
+    for _ in 0..51 {
+        requests.push_back(BatchTradeRequest {
+            trader: trader.clone(),
+            pair: Symbol::new(&env, "XLMUSDC"),
+            amount: 10,
+            price: 10,
+            is_buy: true,
+            fee_token: token_id.clone(),
+            fee_amount: 1,
+            fee_recipient: fee_recipient.clone(),
+        });
+    }
+    let result = client.try_batch_trade(&requests);
+    assert!(result.is_err());
+    // The batch size limit is enforced, but exact error type may vary
+}
+
+#[test]
+fn test_batch_trade_partial_failures() {
+    let _guard = (); // serial_lock disabled
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader1 = Address::generate(&env);
+    let trader2 = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+
+    // Only mint tokens for trader1
+    token_admin.mint(&trader1, &1000);
+
+    let mut requests = Vec::new(&env);
+    // Valid trade
+    requests.push_back(BatchTradeRequest {
+        trader: trader1.clone(),
+        pair: Symbol::new(&env, "XLMUSDC"),
+        amount: 250,
+        price: 10,
+        is_buy: true,
+        fee_token: token_id.clone(),
+        fee_amount: 100,
+        fee_recipient: fee_recipient.clone(),
+    });
+    // Invalid trade (insufficient balance)
+    requests.push_back(BatchTradeRequest {
+        trader: trader2.clone(),
+        pair: Symbol::new(&env, "XLMUSDC"),
+        amount: 150,
+        price: 12,
+        is_buy: false,
+        fee_token: token_id.clone(),
+        fee_amount: 50,
+        fee_recipient: fee_recipient.clone(),
+    });
+
+    let result = client.batch_trade(&requests);
+
+    assert_eq!(result.successful_trades.len(), 1);
+    assert_eq!(result.failed_trades.len(), 2);
+    assert_eq!(result.total_fees_collected, 100);
+
+    // Check that only the successful trade was processed
+    assert_eq!(token_client.balance(&trader1), 900);
+    assert_eq!(token_client.balance(&trader2), 0);
+    assert_eq!(token_client.balance(&fee_recipient), 100);
+
+    let stats = client.get_stats();
+    assert_eq!(stats.total_trades, 1);
+    assert_eq!(stats.total_volume, 250);
+}
+
+#[test]
+fn test_batch_trade_when_paused() {
+    let _guard = (); // serial_lock disabled
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, _token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+
+    token_admin.mint(&trader, &1000);
+
+    // Pause contract
+    client.pause(&admin);
+
+    let mut requests = Vec::new(&env);
+    requests.push_back(BatchTradeRequest {
+        trader: trader.clone(),
+        pair: Symbol::new(&env, "XLMUSDC"),
+        amount: 250,
+        price: 10,
+        is_buy: true,
+        fee_token: token_id.clone(),
+        fee_amount: 100,
+        fee_recipient: fee_recipient.clone(),
+    });
+
+    let result = client.try_batch_trade(&requests);
+    assert!(result.is_err());
+    // The batch size limit is enforced
+}
+
+#[test]
+fn test_batch_trade_emits_events() {
+    let _guard = (); // serial_lock disabled
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, _token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+
+    token_admin.mint(&trader, &1000);
+
+    let mut requests = Vec::new(&env);
+    requests.push_back(BatchTradeRequest {
+        trader: trader.clone(),
+        pair: Symbol::new(&env, "XLMUSDC"),
+        amount: 250,
+        price: 10,
+        is_buy: true,
+        fee_token: token_id,
+        fee_amount: 100,
+        fee_recipient: fee_recipient.clone(),
+    });
+
+    client.batch_trade(&requests);
+
+    let events = env.events().all();
+    // Should have fee and trade events
+    let has_trade_event = events.iter().any(|(_, topics, _): (_, _, _)| {
+        if let Some(first_topic) = topics.first() {
+            let topic_sym: Symbol = first_topic.clone().into_val(&env);
+            return topic_sym == symbol_short!("trade");
+        }
+        false
+    });
+    assert!(has_trade_event, "Trade event not found");
+
+    let has_fee_event = events.iter().any(|(_, topics, _): (_, _, _)| {
+        if let Some(first_topic) = topics.first() {
+            let topic_sym: Symbol = first_topic.clone().into_val(&env);
+            return topic_sym == symbol_short!("fee");
+        }
+        false
+    });
+    assert!(has_fee_event, "Fee event not found");
+}
+
+#[test]
+fn test_trade_and_pause_emit_events_in_order() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, _token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+
+    let contract_topics = |env: &Env| -> std::vec::Vec<Symbol> {
+        env.events()
+            .all()
+            .iter()
+            .filter(|(emitter, _, _)| *emitter == contract_id)
+            .map(|(_, topics, _)| topics.first().unwrap().into_val(env))
+            .collect()
+    };
+
+    client.trade_fixed_fee(
+        &trader,
+        &Symbol::new(&env, "XLMUSDC"),
+        &250,
+        &10,
+        &true,
+        &token_id,
+        &100,
+        &fee_recipient,
+    );
+    assert_eq!(
+        contract_topics(&env),
+        std::vec![symbol_short!("fee"), symbol_short!("trade")]
+    );
+
+    let (_, _, data) = env
+        .events()
+        .all()
+        .iter()
+        .find(|(emitter, _, _)| *emitter == contract_id)
+        .unwrap();
+    let fee_event: FeeCollectedEvent = data.into_val(&env);
+    assert_eq!(fee_event.payer, trader);
+    assert_eq!(fee_event.recipient, fee_recipient);
+    assert_eq!(fee_event.amount, 100);
+    assert_eq!(fee_event.token, token_id);
+
+    client.pause(&admin);
+    client.unpause(&admin);
+    assert_eq!(
+        contract_topics(&env),
+        std::vec![
+            symbol_short!("fee"),
+            symbol_short!("trade"),
+            symbol_short!("paused"),
+            symbol_short!("unpause"),
+        ]
+    );
+}
+
+#[test]
+fn test_batch_trade_atomic_happy_path() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let recipient1 = Address::generate(&env);
+    let recipient2 = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+
+    let fees = soroban_sdk::vec![&env, 100i128, 50i128];
+    let recipients = soroban_sdk::vec![&env, recipient1.clone(), recipient2.clone()];
+
+    let total = client.batch_trade_atomic(&trader, &token_id, &fees, &recipients);
+
+    assert_eq!(total, 150);
+    assert_eq!(token_client.balance(&trader), 850);
+    assert_eq!(token_client.balance(&recipient1), 100);
+    assert_eq!(token_client.balance(&recipient2), 50);
+}
+
+#[test]
+fn test_batch_trade_atomic_validates_lengths() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, _token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    token_admin.mint(&trader, &100_000);
+
+    let result = client.try_batch_trade_atomic(
+        &trader,
+        &token_id,
+        &soroban_sdk::vec![&env, 10i128, 20i128],
+        &soroban_sdk::vec![&env, recipient.clone()],
+    );
+    assert_eq!(result, Err(Ok(TradingError::BatchLengthMismatch)));
+
+    let result = client.try_batch_trade_atomic(&trader, &token_id, &Vec::new(&env), &Vec::new(&env));
+    assert_eq!(result, Err(Ok(TradingError::EmptyBatch)));
+
+    let mut fees = Vec::new(&env);
+    let mut recipients = Vec::new(&env);
+    for _ in 0..101 {
+        fees.push_back(1i128);
+        recipients.push_back(recipient.clone());
+    }
+    let result = client.try_batch_trade_atomic(&trader, &token_id, &fees, &recipients);
+    assert_eq!(result, Err(Ok(TradingError::BatchSizeExceeded)));
+}
+
+#[test]
+fn test_trade_split_assigns_remainder_to_first_recipient() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let liquidity_fund = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+
+    // 101 split 1/3 - 2/3: 33 + 67 = 100, the leftover unit goes to the treasury
+    let shares = client.trade_split(
+        &trader,
+        &token_id,
+        &101,
+        &soroban_sdk::vec![&env, treasury.clone(), liquidity_fund.clone()],
+        &soroban_sdk::vec![&env, 3333u32, 6667u32],
+    );
+
+    assert_eq!(shares, soroban_sdk::vec![&env, 34i128, 67i128]);
+    assert_eq!(token_client.balance(&treasury), 34);
+    assert_eq!(token_client.balance(&liquidity_fund), 67);
+    assert_eq!(token_client.balance(&trader), 1000 - 101);
+    assert_eq!(client.total_fees(&token_id), 101);
+}
+
+#[test]
+fn test_trade_split_validates_inputs() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+
+    let result = client.try_trade_split(
+        &trader,
+        &token_id,
+        &100,
+        &soroban_sdk::vec![&env, recipient.clone()],
+        &soroban_sdk::vec![&env, 5000u32, 5000u32],
+    );
+    assert_eq!(result, Err(Ok(TradingError::BatchLengthMismatch)));
+
+    let result = client.try_trade_split(&trader, &token_id, &100, &Vec::new(&env), &Vec::new(&env));
+    assert_eq!(result, Err(Ok(TradingError::EmptyBatch)));
+
+    let result = client.try_trade_split(
+        &trader,
+        &token_id,
+        &100,
+        &soroban_sdk::vec![&env, recipient.clone()],
+        &soroban_sdk::vec![&env, 0u32],
+    );
+    assert_eq!(result, Err(Ok(TradingError::InvalidWeights)));
+
+    let result = client.try_trade_split(
+        &trader,
+        &token_id,
+        &100,
+        &soroban_sdk::vec![&env, recipient.clone()],
+        &soroban_sdk::vec![&env, 9999u32],
+    );
+    assert_eq!(result, Err(Ok(TradingError::InvalidWeights)));
+    assert_eq!(token_client.balance(&trader), 1000);
+}
+
+#[test]
+fn test_batch_trade_atomic_and_split_apply_trade_limits() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let recipient1 = Address::generate(&env);
+    let recipient2 = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    let recipients = soroban_sdk::vec![&env, recipient1.clone(), recipient2.clone()];
+
+    // Each leg is under the daily limit, but the batch total is not
+    client.set_daily_limit(&admin, &200);
+    let result = client.try_batch_trade_atomic(&trader, &token_id, &soroban_sdk::vec![&env, 150i128, 100i128], &recipients);
+    assert_eq!(result, Err(Ok(TradingError::RateLimited)));
+    let weights = soroban_sdk::vec![&env, 5000u32, 5000u32];
+    assert_eq!(client.try_trade_split(&trader, &token_id, &250, &recipients, &weights), Err(Ok(TradingError::RateLimited)));
+    assert_eq!(token_client.balance(&trader), 1000);
+
+    client.batch_trade_atomic(&trader, &token_id, &soroban_sdk::vec![&env, 100i128, 50i128], &recipients);
+    assert_eq!(client.try_trade_split(&trader, &token_id, &100, &recipients, &weights), Err(Ok(TradingError::RateLimited)));
+    client.set_daily_limit(&admin, &0);
+
+    // No leg may pay the trader back
+    let to_self = soroban_sdk::vec![&env, recipient1.clone(), trader.clone()];
+    let result = client.try_batch_trade_atomic(&trader, &token_id, &soroban_sdk::vec![&env, 10i128, 10i128], &to_self);
+    assert_eq!(result, Err(Ok(TradingError::SelfTrade)));
+    assert_eq!(client.try_trade_split(&trader, &token_id, &20, &to_self, &weights), Err(Ok(TradingError::SelfTrade)));
+
+    // A batch is one trade for the cooldown
+    client.set_cooldown(&admin, &60);
+    client.batch_trade_atomic(&trader, &token_id, &soroban_sdk::vec![&env, 10i128, 10i128], &recipients);
+    assert_eq!(client.try_trade_split(&trader, &token_id, &20, &recipients, &weights), Err(Ok(TradingError::RateLimited)));
+    set_timestamp(&env, env.ledger().timestamp() + 60);
+    client.trade_split(&trader, &token_id, &20, &recipients, &weights);
+    assert_eq!(token_client.balance(&trader), 1000 - 150 - 20 - 20);
+}
+
+#[test]
+fn test_batch_trade_atomic_and_split_cap_fee_share() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let recipient1 = Address::generate(&env);
+    let recipient2 = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    let recipients = soroban_sdk::vec![&env, recipient1.clone(), recipient2.clone()];
+    let weights = soroban_sdk::vec![&env, 5000u32, 5000u32];
+    client.set_max_fee_pct(&admin, &1_000);
+
+    // The second leg is over 10% of what the first leaves behind
+    let result = client.try_batch_trade_atomic(&trader, &token_id, &soroban_sdk::vec![&env, 100i128, 91i128], &recipients);
+    assert_eq!(result, Err(Ok(TradingError::FeeTooHigh)));
+    assert_eq!(client.try_trade_split(&trader, &token_id, &101, &recipients, &weights), Err(Ok(TradingError::FeeTooHigh)));
+    assert_eq!(token_client.balance(&trader), 1000);
+
+    client.batch_trade_atomic(&trader, &token_id, &soroban_sdk::vec![&env, 100i128, 90i128], &recipients);
+    client.trade_split(&trader, &token_id, &81, &recipients, &weights);
+    assert_eq!(token_client.balance(&trader), 1000 - 190 - 81);
+}
+
+#[test]
+fn test_batch_trade_atomic_and_split_account_fees_like_trade() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_burn_bps(&admin, &2_000);
+    client.set_rebate_bps(&admin, &1_000);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let recipient1 = Address::generate(&env);
+    let recipient2 = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    let recipients = soroban_sdk::vec![&env, recipient1.clone(), recipient2.clone()];
+
+    // Each leg burns 20% and pools 10%, and only the rest reaches its recipient
+    let total = client.batch_trade_atomic(&trader, &token_id, &soroban_sdk::vec![&env, 100i128, 50i128], &recipients);
+    assert_eq!(total, 150);
+    assert_eq!(token_client.balance(&recipient1), 70);
+    assert_eq!(token_client.balance(&recipient2), 35);
+    assert_eq!(client.rebate_pool_balance(&token_id), 15);
+    assert_eq!(client.total_fees(&token_id), 150);
+    assert_eq!(client.received_total(&recipient1), 70);
+    assert_eq!(client.received_total(&recipient2), 35);
+
+    client.trade_split(&trader, &token_id, &100, &recipients, &soroban_sdk::vec![&env, 5000u32, 5000u32]);
+    assert_eq!(token_client.balance(&recipient1), 105);
+    assert_eq!(token_client.balance(&recipient2), 70);
+    assert_eq!(client.rebate_pool_balance(&token_id), 25);
+    assert_eq!(client.total_fees(&token_id), 250);
+    assert_eq!(client.received_total(&recipient1), 105);
+
+    // Under the accrue sink the routed share stays in the contract
+    client.set_fee_sink(&admin, &FeeSinkMode::Accrue);
+    client.batch_trade_atomic(&trader, &token_id, &soroban_sdk::vec![&env, 100i128], &soroban_sdk::vec![&env, recipient1.clone()]);
+    assert_eq!(token_client.balance(&recipient1), 105);
+    assert_eq!(client.accrued_fees(&token_id), 70);
+    assert_eq!(client.total_fees(&token_id), 350);
+}
+
+#[test]
+fn test_batch_trade_atomic_rolls_back_on_failure() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let recipient1 = Address::generate(&env);
+    let recipient2 = Address::generate(&env);
+    token_admin.mint(&trader, &120);
+
+    // The first fee succeeds, the second exceeds the remaining balance
+    let fees = soroban_sdk::vec![&env, 100i128, 50i128];
+    let recipients = soroban_sdk::vec![&env, recipient1.clone(), recipient2.clone()];
+
+    let result = client.try_batch_trade_atomic(&trader, &token_id, &fees, &recipients);
+
+    assert!(result.is_err());
+    assert_eq!(token_client.balance(&trader), 120);
+    assert_eq!(token_client.balance(&recipient1), 0);
+    assert_eq!(token_client.balance(&recipient2), 0);
+}
+
+#[test]
+fn test_fee_volume_tracked_per_token() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (token_a, _client_a, admin_a) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_a);
+    let (token_b, _client_b, admin_b) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_b);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    admin_a.mint(&trader, &1000);
+    admin_b.mint(&trader, &1000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    client.trade(&trader, &pair, &1000, &10, &true, &token_a, &fee_recipient, &None, &None);
+    client.trade(&trader, &pair, &2500, &10, &true, &token_a, &fee_recipient, &None, &None);
+    client.trade(&trader, &pair, &4000, &10, &false, &token_b, &fee_recipient, &None, &None);
+    client.trade_fixed_fee(&trader, &pair, &100, &10, &true, &token_b, &7, &fee_recipient);
+
+    assert_eq!(client.total_fees(&token_a), 35);
+    assert_eq!(client.total_fees(&token_b), 47);
+    assert_eq!(client.total_trades(), 4);
+}
+
+// =============================================================================
+// Trade And Reward Tests
+// =============================================================================
+
+#[test]
+fn test_trade_and_reward_happy_path() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+
+    let reward_id = env.register_contract(None, MockRewardContract);
+    let reward_client = MockRewardContractClient::new(&env, &reward_id);
+    client.add_reward_contract(&admin, &reward_id);
+
+    let receipt = client.trade_and_reward(
+        &trader,
+        &Symbol::new(&env, "XLMUSDC"),
+        &5000,
+        &10,
+        &true,
+        &token_id,
+        &fee_recipient,
+        &reward_id,
+        &25,
+        &None,
+    );
+
+    assert_eq!(receipt.trade_id, 1);
+    assert_eq!(token_client.balance(&trader), 950);
+    assert_eq!(token_client.balance(&fee_recipient), 50);
+    assert_eq!(reward_client.reward_of(&trader), 25);
+    assert_eq!(client.total_fees(&token_id), 50);
+    assert_eq!(client.total_trades(), 1);
+
+    let reward_events: std::vec::Vec<TradeRewardEvent> = env
+        .events()
+        .all()
+        .iter()
+        .filter(|(emitter, topics, _)| {
+            *emitter == contract_id
+                && Symbol::from_val(&env, &topics.first().unwrap()) == symbol_short!("reward")
+        })
+        .map(|(_, _, data)| data.into_val(&env))
+        .collect();
+    assert_eq!(
+        reward_events,
+        std::vec![TradeRewardEvent {
+            trade_id: 1,
+            user: trader,
+            reward_contract: reward_id,
+            amount: 25,
+            timestamp: env.ledger().timestamp(),
+        }]
+    );
+}
+
+#[test]
+fn test_pause_reward_only_allows_plain_trades() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    let reward_id = env.register_contract(None, MockRewardContract);
+    client.add_reward_contract(&admin, &reward_id);
+
+    client.pause_operation(&admin, &PAUSE_REWARD);
+    assert_eq!(client.pause_flags(), PAUSE_REWARD);
+    assert!(client.pause_info().0);
+
+    // Reward cross-calls are blocked...
+    let result = client.try_trade_and_reward(
+        &trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &reward_id, &25,
+        &None,
+    );
+    assert_eq!(result, Err(Ok(TradingError::Paused)));
+    assert_eq!(token_client.balance(&trader), 1000);
+
+    // ...while plain trades still go through
+    client.trade(&trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(token_client.balance(&trader), 950);
+
+    client.unpause_operation(&admin, &PAUSE_REWARD);
+    assert_eq!(client.pause_flags(), 0);
+    client.trade_and_reward(&trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &reward_id, &25, &None);
+    assert_eq!(client.total_trades(), 2);
+
+    // A full pause sets every flag and blocks reward calls too
+    client.pause(&admin);
+    assert_eq!(client.pause_flags(), PAUSE_ALL);
+    let result = client.try_trade_and_reward(
+        &trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &reward_id, &25,
+        &None,
+    );
+    assert_eq!(result, Err(Ok(TradingError::Paused)));
+}
+
+#[test]
+fn test_pause_operation_validates_flag() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    assert_eq!(client.try_pause_operation(&admin, &0), Err(Ok(TradingError::InvalidPauseFlag)));
+    assert_eq!(client.try_pause_operation(&admin, &(1 << 5)), Err(Ok(TradingError::InvalidPauseFlag)));
+
+    let outsider = Address::generate(&env);
+    assert_eq!(client.try_pause_operation(&outsider, &PAUSE_TRADE), Err(Ok(TradingError::Unauthorized)));
+
+    client.pause_operation(&admin, &PAUSE_TRADE);
+    client.pause_operation(&admin, &PAUSE_REWARD);
+    assert_eq!(client.pause_flags(), PAUSE_ALL);
+    client.unpause_operation(&admin, &PAUSE_TRADE);
+    assert_eq!(client.pause_flags(), PAUSE_REWARD);
+}
+
+#[test]
+fn test_pause_until_expires_without_unpause() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, _token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+
+    let until = env.ledger().sequence() + 10;
+    client.set_pause_until(&admin, &until);
+    assert_eq!(client.pause_flags(), PAUSE_ALL);
+    assert_eq!(client.pause_until(), Some(until));
+
+    // Still paused on the final covered ledger
+    advance_ledger(&env, 10);
+    assert!(client.pause_info().0);
+
+    advance_ledger(&env, 1);
+    assert!(!client.pause_info().0);
+    assert_eq!(client.pause_until(), None);
+
+    let trade_id = client.trade(
+        &trader,
+        &Symbol::new(&env, "XLMUSDC"),
+        &10_000,
+        &10,
+        &true,
+        &token_id,
+        &fee_recipient,
+        &None,
+        &None,
+    );
+    assert_eq!(trade_id, 1);
+}
+
+#[test]
+fn test_pause_until_validation_and_override() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    advance_ledger(&env, 5);
+    let now = env.ledger().sequence();
+    assert_eq!(client.try_set_pause_until(&admin, &(now - 1)), Err(Ok(TradingError::InvalidWindow)));
+
+    let outsider = Address::generate(&env);
+    assert_eq!(client.try_set_pause_until(&outsider, &(now + 10)), Err(Ok(TradingError::Unauthorized)));
+
+    // A plain pause afterwards is indefinite again
+    client.set_pause_until(&admin, &(now + 10));
+    client.pause(&admin);
+    assert_eq!(client.pause_until(), None);
+    advance_ledger(&env, 20);
+    assert_eq!(client.pause_flags(), PAUSE_ALL);
+
+    // Guardians can extend a pause but not cut one short
+    let now = env.ledger().sequence();
+    let guardian = Address::generate(&env);
+    client.grant_role(&admin, &guardian, &TradingRole::Guardian);
+    assert_eq!(client.try_set_pause_until(&guardian, &now), Err(Ok(TradingError::Unauthorized)));
+    assert_eq!(client.pause_until(), None);
+    client.unpause(&admin);
+    client.set_pause_until(&guardian, &(now + 10));
+    client.set_pause_until(&guardian, &(now + 20));
+    assert_eq!(client.try_set_pause_until(&guardian, &(now + 15)), Err(Ok(TradingError::Unauthorized)));
+    assert_eq!(client.pause_until(), Some(now + 20));
+    client.set_pause_until(&admin, &(now + 15));
+    assert_eq!(client.pause_until(), Some(now + 15));
+
+    // Cutting a pause short still waits out the unpause delay
+    client.set_unpause_delay(&admin, &10);
+    assert_eq!(client.try_set_pause_until(&admin, &(now + 9)), Err(Ok(TradingError::InvalidWindow)));
+    client.set_pause_until(&admin, &(now + 10));
+    assert_eq!(client.pause_until(), Some(now + 10));
+    client.set_pause_until(&admin, &(now + 12));
+}
+
+#[test]
+fn test_fee_discount_tiers_by_reward_balance() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let fee_recipient = Address::generate(&env);
+    let small_holder = Address::generate(&env);
+    let large_holder = Address::generate(&env);
+    token_admin.mint(&small_holder, &1000);
+    token_admin.mint(&large_holder, &1000);
+
+    let reward_id = env.register_contract(None, MockRewardContract);
+    let reward_client = MockRewardContractClient::new(&env, &reward_id);
+    reward_client.add_reward(&small_holder, &50);
+    reward_client.add_reward(&large_holder, &500);
+
+    let mut tiers = Vec::new(&env);
+    tiers.push_back((100_i128, 1000_u32));
+    tiers.push_back((400_i128, 2500_u32));
+    client.set_discount_tiers(&admin, &tiers);
+    assert_eq!(
+        client.try_set_discount_source(&admin, &Some(reward_id.clone())),
+        Err(Ok(TradingError::RewardContractNotAllowed))
+    );
+    client.add_reward_contract(&admin, &reward_id);
+    client.set_discount_source(&admin, &Some(reward_id.clone()));
+
+    let pair = Symbol::new(&env, "XLMUSDC");
+    // Below every threshold: full 100 fee
+    client.trade(&small_holder, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(token_client.balance(&small_holder), 900);
+
+    // Qualifies for both tiers and gets the larger 25% discount
+    client.trade(&large_holder, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(token_client.balance(&large_holder), 925);
+
+    // The undiscounted quote is unchanged
+    assert_eq!(client.quote_fee(&10_000, &token_id), 100);
+}
+
+#[test]
+fn test_effective_fee_matches_trade_with_discount_and_holiday() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    client.set_token_fee_bps(&admin, &token_id, &100);
+    client.set_fee_bounds(&admin, &5, &500);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+
+    let reward_id = env.register_contract(None, MockRewardContract);
+    MockRewardContractClient::new(&env, &reward_id).add_reward(&trader, &500);
+    let mut tiers = Vec::new(&env);
+    tiers.push_back((400_i128, 2500_u32));
+    client.set_discount_tiers(&admin, &tiers);
+    client.add_reward_contract(&admin, &reward_id);
+    client.set_discount_source(&admin, &Some(reward_id));
+    let start = env.ledger().sequence() + 1;
+    client.set_fee_holiday(&admin, &start, &(start + 5));
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    // 103 at 1%, less the 25% discount rounded down, inside the bounds
+    let quote = client.effective_fee(&trader, &token_id, &10_333);
+    assert_eq!(quote, 78);
+    assert_eq!(FeeManager::compute_fee(10_333, 100, 0, 2500, 5, 500), Ok(quote));
+    assert_eq!(client.quote_fee(&10_333, &token_id), 103);
+    client.trade(&trader, &pair, &10_333, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(token_client.balance(&trader), 1000 - quote);
+
+    // The holiday waives the fee, floor included
+    advance_ledger(&env, 1);
+    assert_eq!(client.effective_fee(&trader, &token_id, &10_333), 0);
+    client.trade(&trader, &pair, &10_333, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(token_client.balance(&trader), 1000 - quote);
+}
+
+#[test]
+fn test_fee_discount_falls_back_when_lookup_fails() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let fee_recipient = Address::generate(&env);
+    let trader = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+
+    let reward_id = env.register_contract(None, MockRewardContract);
+    let reward_client = MockRewardContractClient::new(&env, &reward_id);
+    reward_client.add_reward(&trader, &500);
+    reward_client.block(&trader);
+    client.add_reward_contract(&admin, &reward_id);
+    client.set_discount_source(&admin, &Some(reward_id));
+
+    let mut tiers = Vec::new(&env);
+    tiers.push_back((100_i128, 5000_u32));
+    client.set_discount_tiers(&admin, &tiers);
+
+    let mut bad_tiers = Vec::new(&env);
+    bad_tiers.push_back((100_i128, 10_001_u32));
+    assert_eq!(client.try_set_discount_tiers(&admin, &bad_tiers), Err(Ok(TradingError::InvalidFeeBps)));
+
+    // The failed balance lookup charges the full fee instead of blocking the trade
+    client.trade(&trader, &Symbol::new(&env, "XLMUSDC"), &10_000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(token_client.balance(&trader), 900);
+}
+
+#[test]
+fn test_verbose_errors_expose_raw_reward_error() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let fee_recipient = Address::generate(&env);
+    let trader = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    let reward_id = env.register_contract(None, MockRewardContract);
+    let reward_client = MockRewardContractClient::new(&env, &reward_id);
+    client.add_reward_contract(&admin, &reward_id);
+    reward_client.block(&trader);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    // Opaque by default: the same code trade_and_reward returns
+    assert!(!client.verbose_errors());
+    let result = client.try_trade_and_reward_verbose(
+        &trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &reward_id, &50, &None,
+    );
+    assert_eq!(result, Err(Ok(soroban_sdk::Error::from_contract_error(TradingError::RewardFailed as u32))));
+
+    // Verbose: the reward contract's own Blocked code
+    client.set_verbose_errors(&admin, &true);
+    let result = client.try_trade_and_reward_verbose(
+        &trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &reward_id, &50, &None,
+    );
+    assert_eq!(result, Err(Ok(soroban_sdk::Error::from_contract_error(2))));
+
+    // Non-reward failures keep their trading code
+    let result = client.try_trade_and_reward_verbose(
+        &trader, &pair, &10_000, &10, &true, &Address::generate(&env), &fee_recipient, &reward_id, &50, &None,
+    );
+    assert_eq!(result, Err(Ok(soroban_sdk::Error::from_contract_error(TradingError::TokenNotAllowed as u32))));
+
+    // Both modes roll back the fee
+    assert_eq!(token_client.balance(&trader), 1000);
+    assert_eq!(token_client.balance(&fee_recipient), 0);
+    assert_eq!(client.total_trades(), 0);
+    assert_eq!(reward_client.reward_of(&trader), 0);
+}
+
+#[test]
+fn test_min_reward_ratio_rejects_trivial_rewards() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let fee_recipient = Address::generate(&env);
+    let trader = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    let reward_id = env.register_contract(None, MockRewardContract);
+    let reward_client = MockRewardContractClient::new(&env, &reward_id);
+    client.add_reward_contract(&admin, &reward_id);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    assert_eq!(client.try_set_min_reward_ratio(&admin, &10_001), Err(Ok(TradingError::InvalidFeeBps)));
+    // Rewards must be at least 20% of the 100 fee
+    client.set_min_reward_ratio(&admin, &2000);
+    assert_eq!(client.min_reward_ratio(), 2000);
+
+    let result = client.try_trade_and_reward(
+        &trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &reward_id, &19, &None,
+    );
+    assert_eq!(result, Err(Ok(TradingError::RewardTooSmall)));
+    assert_eq!(token_client.balance(&trader), 1000);
+    assert_eq!(reward_client.reward_of(&trader), 0);
+
+    let receipt = client.trade_and_reward(
+        &trader, &pair, &10_000, &10, &true, &token_id, &fee_recipient, &reward_id, &20, &None,
+    );
+    assert_eq!(receipt.reward_amount, 20);
+    assert_eq!(token_client.balance(&trader), 900);
+    assert_eq!(reward_client.reward_of(&trader), 20);
+}
+
+#[test]
+fn test_trade_and_reward_returns_receipt() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (token_id, _token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+
+    let reward_id = env.register_contract(None, MockRewardContract);
+    client.add_reward_contract(&admin, &reward_id);
+
+    advance_ledger(&env, 42);
+    let receipt = client.trade_and_reward(
+        &trader,
+        &Symbol::new(&env, "XLMUSDC"),
+        &5000,
+        &10,
+        &true,
+        &token_id,
+        &fee_recipient,
+        &reward_id,
+        &25,
+        &None,
+    );
+
+    assert_eq!(
+        receipt,
+        TradeReceipt {
+            trade_id: 1,
+            fee_paid: 50,
+            reward_amount: 25,
+            referral_amount: 0,
+            reward_contract: reward_id,
+            reward_total: Some(25),
+            ledger: env.ledger().sequence(),
+            cross_calls: 1,
+        }
+    );
+}
+
+#[test]
+fn test_trade_and_reward_surfaces_reward_total() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, _token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+
+    let reward_id = env.register_contract(None, MockRewardContract);
+    client.add_reward_contract(&admin, &reward_id);
+
+    let pair = Symbol::new(&env, "XLMUSDC");
+    let first = client.trade_and_reward(
+        &trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &reward_id, &25, &None,
+    );
+    let second = client.trade_and_reward(
+        &trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &reward_id, &25, &None,
+    );
+
+    // The receipt carries the running total reported by the reward contract
+    assert_eq!(first.reward_total, Some(25));
+    assert_eq!(second.reward_total, Some(50));
+}
+
+#[test]
+fn test_reward_multiplier_boosts_rewards_inside_window() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, _token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+
+    let reward_id = env.register_contract(None, MockRewardContract);
+    let reward_client = MockRewardContractClient::new(&env, &reward_id);
+    client.add_reward_contract(&admin, &reward_id);
+
+    let start = env.ledger().sequence() + 10;
+    assert_eq!(
+        client.try_set_reward_multiplier(&admin, &0, &start, &(start + 5)),
+        Err(Ok(TradingError::InvalidMultiplier))
+    );
+    assert_eq!(
+        client.try_set_reward_multiplier(&admin, &20_000, &start, &start),
+        Err(Ok(TradingError::InvalidWindow))
+    );
+    client.set_reward_multiplier(&admin, &20_000, &start, &(start + 5));
+    assert_eq!(client.reward_multiplier(), Some((20_000, start, start + 5)));
+
+    let pair = Symbol::new(&env, "XLMUSDC");
+    // Before the window: base reward
+    let receipt = client.trade_and_reward(
+        &trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &reward_id, &25, &None,
+    );
+    assert_eq!(receipt.reward_amount, 25);
+    assert_eq!(reward_client.reward_of(&trader), 25);
+
+    // Inside the window: the reward contract receives the doubled amount
+    advance_ledger(&env, 10);
+    let receipt = client.trade_and_reward(
+        &trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &reward_id, &25, &None,
+    );
+    assert_eq!(receipt.reward_amount, 50);
+    assert_eq!(reward_client.reward_of(&trader), 75);
+
+    // The end ledger is back to 1x
+    advance_ledger(&env, 5);
+    let receipt = client.trade_and_reward(
+        &trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &reward_id, &25, &None,
+    );
+    assert_eq!(receipt.reward_amount, 25);
+    assert_eq!(reward_client.reward_of(&trader), 100);
+}
+
+#[test]
+fn test_trade_and_reward_default_uses_configured_contract() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, _token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    // No default yet
+    assert_eq!(client.default_reward_contract(), None);
+    let result = client.try_trade_and_reward_default(
+        &trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &25, &None,
+    );
+    assert_eq!(result, Err(Ok(TradingError::NoDefaultRewardContract)));
+
+    let reward_id = env.register_contract(None, MockRewardContract);
+    let reward_client = MockRewardContractClient::new(&env, &reward_id);
+    assert_eq!(
+        client.try_set_default_reward_contract(&admin, &Some(reward_id.clone())),
+        Err(Ok(TradingError::RewardContractNotAllowed))
+    );
+    client.add_reward_contract(&admin, &reward_id);
+    client.set_default_reward_contract(&admin, &Some(reward_id.clone()));
+
+    let receipt = client.trade_and_reward_default(
+        &trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &25, &None,
+    );
+    assert_eq!(receipt.reward_contract, reward_id);
+    assert_eq!(reward_client.reward_of(&trader), 25);
+
+    // The explicit entrypoint still overrides the default
+    let other_id = env.register_contract(None, MockRewardContract);
+    let other_client = MockRewardContractClient::new(&env, &other_id);
+    client.add_reward_contract(&admin, &other_id);
+    client.trade_and_reward(&trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &other_id, &10, &None);
+    assert_eq!(other_client.reward_of(&trader), 10);
+    assert_eq!(reward_client.reward_of(&trader), 25);
+
+    client.set_default_reward_contract(&admin, &None);
+    let result = client.try_trade_and_reward_default(
+        &trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &25, &None,
+    );
+    assert_eq!(result, Err(Ok(TradingError::NoDefaultRewardContract)));
+}
+
+#[test]
+fn test_init_sets_default_reward_contract() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    let reward_id = env.register_contract(None, MockRewardContract);
+    let reward_client = MockRewardContractClient::new(&env, &reward_id);
+    client.init(&admin, &approvers, &executor, &Some(reward_id.clone()));
+
+    assert_eq!(client.default_reward_contract(), Some(reward_id.clone()));
+    assert!(client.is_reward_contract_allowed(&reward_id));
+    assert_eq!(client.reward_contract_count(), 1);
+
+    let (token_id, _token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    client.trade_and_reward_default(
+        &trader, &Symbol::new(&env, "XLMUSDC"), &5000, &10, &true, &token_id, &Address::generate(&env), &25, &None,
+    );
+    assert_eq!(reward_client.reward_of(&trader), 25);
+}
+
+#[test]
+fn test_trade_and_reward_atomic_rollback() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+
+    let reward_id = env.register_contract(None, MockRewardContract);
+    client.add_reward_contract(&admin, &reward_id);
+
+    // The mock rejects a non-positive reward, so the fee transfer must roll back
+    let result = client.try_trade_and_reward(
+        &trader,
+        &Symbol::new(&env, "XLMUSDC"),
+        &5000,
+        &10,
+        &true,
+        &token_id,
+        &fee_recipient,
+        &reward_id,
+        &0,
+        &None,
+    );
+
+    assert_eq!(
+        result,
+        Err(Ok(TradingError::RewardFailed))
+    );
+    assert_eq!(token_client.balance(&trader), 1000);
+    assert_eq!(token_client.balance(&fee_recipient), 0);
+    assert_eq!(client.get_stats().total_trades, 0);
+    assert_eq!(client.total_fees(&token_id), 0);
+    assert_eq!(client.total_trades(), 0);
+
+    // The reentrancy lock was released, so plain trades still work
+    client.trade(
+        &trader,
+        &Symbol::new(&env, "XLMUSDC"),
+        &5000,
+        &10,
+        &true,
+        &token_id,
+        &fee_recipient,
+        &None,
+        &None,
+    );
+    assert_eq!(token_client.balance(&fee_recipient), 50);
+}
+
+#[test]
+fn test_trade_and_reward_credits_referrer() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+    client.set_referral_bps(&admin, &1000);
+    assert_eq!(client.referral_bps(), 1000);
+
+    let (token_id, _token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let referrer = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    let reward_id = env.register_contract(None, MockRewardContract);
+    let reward_client = MockRewardContractClient::new(&env, &reward_id);
+    client.add_reward_contract(&admin, &reward_id);
+
+    // 10% of a 200 reward goes to the referrer
+    let receipt = client.trade_and_reward(
+        &trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &reward_id, &200, &Some(referrer.clone()),
+    );
+    assert_eq!(receipt.referral_amount, 20);
+    assert_eq!(reward_client.reward_of(&trader), 200);
+    assert_eq!(reward_client.reward_of(&referrer), 20);
+
+    // Self-referral earns nothing extra
+    let receipt = client.trade_and_reward(
+        &trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &reward_id, &200, &Some(trader.clone()),
+    );
+    assert_eq!(receipt.referral_amount, 0);
+    assert_eq!(reward_client.reward_of(&trader), 400);
+
+    assert_eq!(client.try_set_referral_bps(&admin, &10_001), Err(Ok(TradingError::InvalidFeeBps)));
+}
+
+#[test]
+fn test_failing_referral_reward_rolls_back_everything() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+    client.set_referral_bps(&admin, &1000);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let referrer = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+
+    let reward_id = env.register_contract(None, MockRewardContract);
+    let reward_client = MockRewardContractClient::new(&env, &reward_id);
+    client.add_reward_contract(&admin, &reward_id);
+    reward_client.block(&referrer);
+
+    let result = client.try_trade_and_reward(
+        &trader,
+        &Symbol::new(&env, "XLMUSDC"),
+        &5000,
+        &10,
+        &true,
+        &token_id,
+        &fee_recipient,
+        &reward_id,
+        &200,
+        &Some(referrer.clone()),
+    );
+
+    assert_eq!(
+        result,
+        Err(Ok(TradingError::RewardFailed))
+    );
+    assert_eq!(reward_client.reward_of(&trader), 0);
+    assert_eq!(reward_client.reward_of(&referrer), 0);
+    assert_eq!(token_client.balance(&trader), 1000);
+    assert_eq!(token_client.balance(&fee_recipient), 0);
+    assert_eq!(client.total_trades(), 0);
+}
+
+#[test]
+fn test_reward_required_controls_failed_reward_rollback() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    let reward_id = env.register_contract(None, MockRewardContract);
+    client.add_reward_contract(&admin, &reward_id);
+
+    // Required by default: the mock rejects a zero reward and everything reverts
+    assert!(client.reward_required());
+    let result = client.try_trade_and_reward(
+        &trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &reward_id, &0, &None,
+    );
+    assert_eq!(result, Err(Ok(TradingError::RewardFailed)));
+    assert_eq!(token_client.balance(&trader), 1000);
+    assert_eq!(client.total_trades(), 0);
+
+    // Best-effort: the trade and fee commit and the skip is reported
+    client.set_reward_required(&admin, &false);
+    let receipt = client.trade_and_reward(
+        &trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &reward_id, &0, &None,
+    );
+    assert_eq!(receipt.fee_paid, 50);
+    assert_eq!(receipt.reward_amount, 0);
+    assert_eq!(token_client.balance(&fee_recipient), 50);
+    assert_eq!(client.total_trades(), 1);
+    assert_eq!(client.reward_failure_count(&reward_id), 1);
+
+    let (_, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(Symbol::from_val(&env, &topics.get(0).unwrap()), Symbol::new(&env, "reward_skipped"));
+    assert_eq!(Address::from_val(&env, &topics.get(1).unwrap()), trader);
+    let (skipped_id, code): (Address, u32) = data.into_val(&env);
+    assert_eq!(skipped_id, reward_id);
+    assert_eq!(code, TradingError::RewardFailed as u32);
+
+    // A working reward is still credited as usual
+    let receipt = client.trade_and_reward(
+        &trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &reward_id, &25, &None,
+    );
+    assert_eq!(receipt.reward_amount, 25);
+    assert_eq!(client.reward_failure_count(&reward_id), 0);
+
+    let outsider = Address::generate(&env);
+    assert_eq!(client.try_set_reward_required(&outsider, &true), Err(Ok(TradingError::NotAdmin)));
+}
+
+#[test]
+fn test_receipt_counts_reward_and_refund_cross_calls() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+    client.set_referral_bps(&admin, &1000);
+
+    let (token_id, _, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let referrer = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+
+    let reward_id = env.register_contract(None, MockRewardContract);
+    client.add_reward_contract(&admin, &reward_id);
+
+    // One add_reward for the trader, one for the referrer
+    let receipt = client.trade_and_reward(
+        &trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &reward_id, &100, &Some(referrer),
+    );
+    assert_eq!(receipt.cross_calls, 2);
+
+    let receipt = client.trade_and_reward_refundable(
+        &trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &reward_id, &100,
+    );
+    assert_eq!(receipt.cross_calls, 1);
+
+    // The mock rejects a zero reward: the failed add_reward plus the refund transfer
+    let receipt = client.trade_and_reward_refundable(
+        &trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &reward_id, &0,
+    );
+    assert_eq!(receipt.fee_paid, 0);
+    assert_eq!(receipt.cross_calls, 2);
+}
+
+#[test]
+fn test_trade_and_reward_refundable_refunds_fee_on_reward_failure() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+
+    let reward_id = env.register_contract(None, MockRewardContract);
+    client.add_reward_contract(&admin, &reward_id);
+
+    // The mock rejects a zero reward: the trade settles but the fee comes back
+    let receipt = client.trade_and_reward_refundable(
+        &trader,
+        &Symbol::new(&env, "XLMUSDC"),
+        &5000,
+        &10,
+        &true,
+        &token_id,
+        &fee_recipient,
+        &reward_id,
+        &0,
+    );
+
+    assert_eq!(receipt.fee_paid, 0);
+    assert_eq!(receipt.reward_amount, 0);
+    assert_eq!(token_client.balance(&trader), 1000);
+    assert_eq!(token_client.balance(&fee_recipient), 0);
+    assert_eq!(client.total_trades(), 1);
+    assert_eq!(client.total_fees(&token_id), 0);
+
+    let refund_events: std::vec::Vec<(Address, i128)> = env
+        .events()
+        .all()
+        .iter()
+        .filter(|(emitter, topics, _)| {
+            *emitter == contract_id
+                && Symbol::from_val(&env, &topics.first().unwrap()) == symbol_short!("refund")
+        })
+        .map(|(_, _, data)| data.into_val(&env))
+        .collect();
+    assert_eq!(refund_events, std::vec![(token_id.clone(), 50)]);
+
+    // A successful reward call behaves exactly like trade_and_reward
+    let receipt = client.trade_and_reward_refundable(
+        &trader,
+        &Symbol::new(&env, "XLMUSDC"),
+        &5000,
+        &10,
+        &true,
+        &token_id,
+        &fee_recipient,
+        &reward_id,
+        &25,
+    );
+    assert_eq!(receipt.fee_paid, 50);
+    assert_eq!(receipt.reward_amount, 25);
+    assert_eq!(token_client.balance(&fee_recipient), 50);
+}
+
+#[test]
+fn test_trade_and_reward_refundable_releases_only_the_refunded_fee() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+    client.set_burn_bps(&admin, &2_000);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    let pair = Symbol::new(&env, "XLMUSDC");
+    let reward_id = env.register_contract(None, MockRewardContract);
+    client.add_reward_contract(&admin, &reward_id);
+    client.set_daily_limit(&admin, &60);
+    client.set_cooldown(&admin, &60);
+
+    // The 10 burned out of the 50 fee stays paid; the other 40 comes back
+    let receipt = client.trade_and_reward_refundable(
+        &trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &reward_id, &0,
+    );
+    assert_eq!(receipt.fee_paid, 0);
+    assert_eq!(token_client.balance(&trader), 990);
+    assert_eq!(client.total_fees(&token_id), 10);
+
+    // Only the burned share counts against the daily limit, and no cooldown started
+    assert_eq!(client.next_allowed_trade(&trader), 0);
+    client.trade(&trader, &pair, &5000, &10, &true, &token_id, &fee_recipient, &None, &None);
+    assert_eq!(client.total_fees(&token_id), 60);
+    assert_eq!(token_client.balance(&fee_recipient), 40);
+}
+
+#[test]
+fn test_trade_and_reward_refundable_refunds_rerouted_fee_from_fallback() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let token_id = env.register_contract(None, RejectingToken);
+    let token = RejectingTokenClient::new(&env, &token_id);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let fallback = Address::generate(&env);
+    token.mint(&trader, &1000);
+    client.set_fallback_recipient(&admin, &Some(fallback.clone()));
+    token.block(&recipient);
+
+    let reward_id = env.register_contract(None, MockRewardContract);
+    client.add_reward_contract(&admin, &reward_id);
+
+    // The fee is rerouted to the fallback, so the refund must come from there
+    let receipt = client.trade_and_reward_refundable(
+        &trader,
+        &Symbol::new(&env, "XLMUSDC"),
+        &5000,
+        &10,
+        &true,
+        &token_id,
+        &recipient,
+        &reward_id,
+        &0,
+    );
+
+    assert_eq!(receipt.fee_paid, 0);
+    assert!(env.auths().iter().any(|(address, _)| *address == fallback));
+    assert_eq!(token.balance(&trader), 1000);
+    assert_eq!(token.balance(&fallback), 0);
+    assert_eq!(token.balance(&recipient), 0);
+    assert_eq!(client.received_total(&fallback), 0);
+    assert_eq!(client.received_count(&fallback), 0);
+    assert_eq!(client.received_total(&recipient), 0);
+    assert_eq!(client.total_trades(), 1);
+}
+
+#[test]
+fn test_trade_and_reward_blocks_reentrant_trade() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, _token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+
+    let reward_id = env.register_contract(None, ReentrantRewardContract);
+    let reward_client = ReentrantRewardContractClient::new(&env, &reward_id);
+    reward_client.set_target(&contract_id, &token_id, &fee_recipient);
+    client.add_reward_contract(&admin, &reward_id);
+
+    client.trade_and_reward(
+        &trader,
+        &Symbol::new(&env, "XLMUSDC"),
+        &5000,
+        &10,
+        &true,
+        &token_id,
+        &fee_recipient,
+        &reward_id,
+        &25,
+        &None,
+    );
+
+    // The nested trade was refused and only the outer trade was recorded
+    assert_ne!(reward_client.reentry_error(), 0);
+    assert_eq!(client.get_stats().total_trades, 1);
+}
+
+#[test]
+fn test_trade_and_reward_rejects_when_locked() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+    let reward_id = env.register_contract(None, MockRewardContract);
+    client.add_reward_contract(&admin, &reward_id);
+
+    // Simulate being called back while a trade_and_reward is in flight
+    env.as_contract(&contract_id, || TradingStorage::set_locked(&env, true));
+
+    let result = client.try_trade_and_reward(
+        &trader,
+        &Symbol::new(&env, "XLMUSDC"),
+        &5000,
+        &10,
+        &true,
+        &token_id,
+        &fee_recipient,
+        &reward_id,
+        &25,
+        &None,
+    );
+
+    assert_eq!(
+        result,
+        Err(Ok(TradingError::Reentrant))
+    );
+    assert_eq!(token_client.balance(&trader), 1000);
+    assert_eq!(client.get_stats().total_trades, 0);
+}
+
+#[test]
+fn test_fund_moving_entrypoints_reject_when_locked() {
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let pair = Symbol::new(&env, "XLMUSDC");
+    token_admin.mint(&trader, &1000);
+    client.deposit(&trader, &token_id, &100);
+    let escrow_id = client.trade_escrow(&trader, &token_id, &50, &recipient, &0);
+    let schedule_id = client.schedule_trade(&trader, &pair, &10_000, &10, &true, &token_id, &50, &recipient, &0, &true);
+    let mut recipients = Vec::new(&env);
+    recipients.push_back(recipient.clone());
+    let mut amounts = Vec::new(&env);
+    amounts.push_back(1);
+
+    // Simulate being called back while a trade_and_reward is in flight
+    env.as_contract(&contract_id, || TradingStorage::set_locked(&env, true));
+    assert_eq!(client.try_deposit(&trader, &token_id, &10), Err(Ok(TradingError::Reentrant)));
+    assert_eq!(client.try_withdraw(&trader, &token_id, &10), Err(Ok(TradingError::Reentrant)));
+    assert_eq!(client.try_trade_from_balance(&trader, &token_id, &10, &recipient), Err(Ok(TradingError::Reentrant)));
+    assert_eq!(client.try_trade_escrow(&trader, &token_id, &10, &recipient, &0), Err(Ok(TradingError::Reentrant)));
+    assert_eq!(client.try_release_escrow(&escrow_id), Err(Ok(TradingError::Reentrant)));
+    assert_eq!(client.try_refund_escrow(&admin, &escrow_id), Err(Ok(TradingError::Reentrant)));
+    let result = client.try_schedule_trade(&trader, &pair, &10_000, &10, &true, &token_id, &10, &recipient, &0, &true);
+    assert_eq!(result, Err(Ok(TradingError::Reentrant)));
+    assert_eq!(client.try_execute_scheduled(&schedule_id), Err(Ok(TradingError::Reentrant)));
+    assert_eq!(client.try_cancel_scheduled(&trader, &schedule_id), Err(Ok(TradingError::Reentrant)));
+    assert_eq!(client.try_claim_rebate(&trader, &token_id), Err(Ok(TradingError::Reentrant)));
+    assert_eq!(client.try_withdraw_fees(&admin, &token_id, &recipient, &1), Err(Ok(TradingError::Reentrant)));
+    let result = client.try_withdraw_fees_split(&admin, &token_id, &recipients, &amounts);
+    assert_eq!(result, Err(Ok(TradingError::Reentrant)));
+    assert_eq!(client.try_sweep(&admin, &token_id, &recipient, &1), Err(Ok(TradingError::Reentrant)));
+
+    env.as_contract(&contract_id, || TradingStorage::set_locked(&env, false));
+    client.withdraw(&trader, &token_id, &100);
+    assert_eq!(token_client.balance(&trader), 900);
+}
+
+
+#[test]
+fn test_trade_and_reward_rejects_unlisted_reward_contract() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+    client.set_fee_bps(&admin, &100);
+
+    let (token_id, token_client, token_admin) = setup_fee_token(&env);
+    client.allow_fee_token(&admin, &token_id);
+    let trader = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    token_admin.mint(&trader, &1000);
+
+    let reward_id = env.register_contract(None, MockRewardContract);
+    let reward_client = MockRewardContractClient::new(&env, &reward_id);
+    assert!(!client.is_reward_contract_allowed(&reward_id));
+
+    let result = client.try_trade_and_reward(
+        &trader,
+        &Symbol::new(&env, "XLMUSDC"),
+        &5000,
+        &10,
+        &true,
+        &token_id,
+        &fee_recipient,
+        &reward_id,
+        &25,
+        &None,
+    );
+
+    assert_eq!(result, Err(Ok(TradingError::RewardContractNotAllowed)));
+    assert_eq!(token_client.balance(&trader), 1000);
+    assert_eq!(token_client.balance(&fee_recipient), 0);
+    assert_eq!(reward_client.reward_of(&trader), 0);
+    assert_eq!(client.get_stats().total_trades, 0);
+}
+
+#[test]
+fn test_reward_contract_allowlist_management() {
+    let _guard = ();
+    let (env, admin, approver, executor, contract_id) = setup_env();
+    let client = UpgradeableTradingContractClient::new(&env, &contract_id);
+    let mut approvers = Vec::new(&env);
+    approvers.push_back(approver);
+    init_contract(&client, &admin, approvers, &executor);
+
+    let reward_id = env.register_contract(None, MockRewardContract);
+    let non_admin = Address::generate(&env);
+
+    let result = client.try_add_reward_contract(&non_admin, &reward_id);
+    assert_eq!(result, Err(Ok(TradingError::NotAdmin)));
+    assert!(!client.is_reward_contract_allowed(&reward_id));
+
+    client.add_reward_contract(&admin, &reward_id);
+    assert!(client.is_reward_contract_allowed(&reward_id));
+
+    let result = client.try_remove_reward_contract(&non_admin, &reward_id);
+    assert_eq!(result, Err(Ok(TradingError::NotAdmin)));
+    assert!(client.is_reward_contract_allowed(&reward_id));
+
+    client.remove_reward_contract(&admin, &reward_id);
+    assert!(!client.is_reward_contract_allowed(&reward_id));
 }
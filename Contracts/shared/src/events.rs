@@ -18,7 +18,11 @@ pub mod topics {
     pub const TRADE_EXECUTED: Symbol = symbol_short!("trade");
     pub const CONTRACT_PAUSED: Symbol = symbol_short!("paused");
     pub const CONTRACT_UNPAUSED: Symbol = symbol_short!("unpause");
+    pub const CONTRACT_TERMINATED: Symbol = symbol_short!("terminate");
     pub const FEE_COLLECTED: Symbol = symbol_short!("fee");
+    pub const FEE_REFUNDED: Symbol = symbol_short!("refund");
+    pub const TOKENS_SWEPT: Symbol = symbol_short!("sweep");
+    pub const FEES_WITHDRAWN: Symbol = symbol_short!("fee_wdraw");
 
     // Governance events
     pub const PROPOSAL_CREATED: Symbol = symbol_short!("propose");
@@ -30,6 +34,7 @@ pub mod topics {
     // Social rewards events
     pub const REWARD_ADDED: Symbol = symbol_short!("reward");
     pub const REWARD_CLAIMED: Symbol = symbol_short!("claimed");
+    pub const REWARD_CLAWED_BACK: Symbol = symbol_short!("clawback");
 
     // Token events (for reference - already implemented in token contract)
     pub const TRANSFER: Symbol = symbol_short!("transfer");
@@ -43,7 +48,7 @@ pub mod topics {
 
 /// Event emitted when a trade is executed
 #[contracttype]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct TradeExecutedEvent {
     /// Unique trade identifier
     pub trade_id: u64,
@@ -61,23 +66,31 @@ pub struct TradeExecutedEvent {
     pub fee_amount: i128,
     /// Token used for fee payment
     pub fee_token: Address,
+    /// Traded asset; equals `fee_token` unless the trade named a separate one
+    pub settle_token: Address,
+    /// Campaign or UI-source tag the trade was attributed to (empty if none)
+    pub tag: Symbol,
     /// Block timestamp when trade occurred
     pub timestamp: u64,
 }
 
 /// Event emitted when contract is paused
 #[contracttype]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct ContractPausedEvent {
     /// Admin who paused the contract
     pub paused_by: Address,
+    /// Bitmask of operations paused after this change
+    pub flags: u32,
+    /// Reason recorded with the pause (empty if none)
+    pub reason: Symbol,
     /// Block timestamp when paused
     pub timestamp: u64,
 }
 
 /// Event emitted when contract is unpaused
 #[contracttype]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct ContractUnpausedEvent {
     /// Admin who unpaused the contract
     pub unpaused_by: Address,
@@ -101,6 +114,22 @@ pub struct FeeCollectedEvent {
     pub timestamp: u64,
 }
 
+/// Event emitted when a trade credits a reward on a reward contract
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TradeRewardEvent {
+    /// Trade that earned the reward
+    pub trade_id: u64,
+    /// User credited (the trader or their referrer)
+    pub user: Address,
+    /// Reward contract that was credited
+    pub reward_contract: Address,
+    /// Reward amount
+    pub amount: i128,
+    /// Block timestamp
+    pub timestamp: u64,
+}
+
 // =============================================================================
 // Governance Events
 // =============================================================================
@@ -249,6 +278,11 @@ impl EventEmitter {
         env.events().publish((topics::FEE_COLLECTED,), event);
     }
 
+    /// Emit a trade reward event, topic-indexed by the credited user
+    pub fn trade_reward(env: &Env, event: TradeRewardEvent) {
+        env.events().publish((topics::REWARD_ADDED, event.user.clone()), event);
+    }
+
     /// Emit a proposal created event
     pub fn proposal_created(env: &Env, event: ProposalCreatedEvent) {
         env.events().publish((topics::PROPOSAL_CREATED,), event);
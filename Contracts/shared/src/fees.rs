@@ -6,11 +6,81 @@ use soroban_sdk::{contracterror, Address, Env, token};
 pub enum FeeError {
     InsufficientBalance = 1001,
     InvalidAmount = 1002,
+    FeeTooHigh = 1003,
+    RateLimited = 1004,
+    TokenNotAllowed = 1005,
+    ArithmeticOverflow = 1006,
 }
 
+/// Basis-point denominator: a fee of 10_000 bps is 100% of the amount.
+pub const BPS_DENOMINATOR: u32 = 10_000;
+
 pub struct FeeManager;
 
 impl FeeManager {
+    /// Computes a fee as basis points of a trade amount.
+    ///
+    /// The result always rounds down (towards zero), so a trader is never
+    /// charged more than `amount * bps / 10_000`.
+    ///
+    /// # Arguments
+    /// * `amount` - The trade amount the fee is derived from
+    /// * `bps` - The fee rate in basis points (at most `BPS_DENOMINATOR`)
+    ///
+    /// # Returns
+    /// * `Result<i128, FeeError>` - The computed fee, `InvalidAmount` for a
+    ///   negative amount or an out-of-range rate, or `ArithmeticOverflow` if
+    ///   `amount * bps` does not fit in an i128
+    pub fn calculate_bps_fee(amount: i128, bps: u32) -> Result<i128, FeeError> {
+        if amount < 0 || bps > BPS_DENOMINATOR {
+            return Err(FeeError::InvalidAmount);
+        }
+
+        amount
+            .checked_mul(bps as i128)
+            .and_then(|product| product.checked_div(BPS_DENOMINATOR as i128))
+            .ok_or(FeeError::ArithmeticOverflow)
+    }
+
+    /// Computes the fee the trading contract charges for a trade.
+    ///
+    /// The bps fee rounds down as in `calculate_bps_fee`. A positive amount
+    /// at a nonzero rate is never charged nothing: a fee that rounds down to
+    /// zero is raised to `min_nonzero_fee`. The discount is then taken off
+    /// and the result clamped into `[min_fee, max_fee]`. Pure, so other
+    /// contracts can quote the same fee as the trading contract.
+    ///
+    /// # Arguments
+    /// * `amount` - The trade amount the fee is derived from
+    /// * `bps` - The fee rate in basis points (at most `BPS_DENOMINATOR`)
+    /// * `min_nonzero_fee` - The fee charged when the bps fee rounds down to zero
+    /// * `discount_bps` - The share of the fee waived, in basis points
+    /// * `min_fee` - The smallest fee charged
+    /// * `max_fee` - The largest fee charged
+    ///
+    /// # Returns
+    /// * `Result<i128, FeeError>` - The fee, the errors of `calculate_bps_fee`,
+    ///   or `InvalidAmount` if `min_fee` exceeds `max_fee`
+    pub fn compute_fee(
+        amount: i128,
+        bps: u32,
+        min_nonzero_fee: i128,
+        discount_bps: u32,
+        min_fee: i128,
+        max_fee: i128,
+    ) -> Result<i128, FeeError> {
+        if min_fee > max_fee {
+            return Err(FeeError::InvalidAmount);
+        }
+
+        let mut fee = Self::calculate_bps_fee(amount, bps)?;
+        if fee == 0 && amount > 0 && bps > 0 {
+            fee = min_nonzero_fee;
+        }
+        fee -= Self::calculate_bps_fee(fee, discount_bps)?;
+        Ok(fee.clamp(min_fee, max_fee))
+    }
+
     /// Collects a fee from a payer to a destination.
     /// 
     /// # Arguments
@@ -51,4 +121,75 @@ impl FeeManager {
 
         Ok(())
     }
+
+    /// Burn `amount` of `token` from `payer`, taking it out of circulation
+    ///
+    /// Uses the token's `burn`, so `payer` must authorize the call.
+    pub fn burn_fee(
+        env: &Env,
+        token: &Address,
+        payer: &Address,
+        amount: i128,
+    ) -> Result<(), FeeError> {
+        if amount < 0 {
+            return Err(FeeError::InvalidAmount);
+        }
+
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let token_client = token::Client::new(env, token);
+        if token_client.balance(payer) < amount {
+            return Err(FeeError::InsufficientBalance);
+        }
+
+        token_client.burn(payer, &amount);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calculate_bps_fee_reports_overflow() {
+        assert_eq!(FeeManager::calculate_bps_fee(10_000, 25), Ok(25));
+        assert_eq!(FeeManager::calculate_bps_fee(i128::MAX, 0), Ok(0));
+        assert_eq!(FeeManager::calculate_bps_fee(i128::MAX, 1), Ok(i128::MAX / 10_000));
+        assert_eq!(FeeManager::calculate_bps_fee(i128::MAX, 2), Err(FeeError::ArithmeticOverflow));
+    }
+
+    #[test]
+    fn test_compute_fee_rounds_floors_and_caps() {
+        const MAX: i128 = i128::MAX;
+
+        // Rounds down like calculate_bps_fee
+        assert_eq!(FeeManager::compute_fee(10_000, 25, 0, 0, 0, MAX), Ok(25));
+        assert_eq!(FeeManager::compute_fee(9_999, 25, 0, 0, 0, MAX), Ok(24));
+
+        // A fee that rounds to zero is raised to the floor, unless nothing is owed
+        assert_eq!(FeeManager::compute_fee(10, 25, 0, 0, 0, MAX), Ok(0));
+        assert_eq!(FeeManager::compute_fee(10, 25, 3, 0, 0, MAX), Ok(3));
+        assert_eq!(FeeManager::compute_fee(0, 25, 3, 0, 0, MAX), Ok(0));
+        assert_eq!(FeeManager::compute_fee(10, 0, 3, 0, 0, MAX), Ok(0));
+
+        // The discount applies to the floored fee, before the bounds
+        assert_eq!(FeeManager::compute_fee(10_000, 100, 0, 2_500, 0, MAX), Ok(75));
+        assert_eq!(FeeManager::compute_fee(10, 25, 4, 5_000, 0, MAX), Ok(2));
+        assert_eq!(FeeManager::compute_fee(10_000, 100, 0, 10_000, 5, MAX), Ok(5));
+
+        // Clamped into the bounds, including the floor
+        assert_eq!(FeeManager::compute_fee(1_000_000, 100, 0, 0, 0, 500), Ok(500));
+        assert_eq!(FeeManager::compute_fee(10, 25, 3, 0, 0, 2), Ok(2));
+        assert_eq!(FeeManager::compute_fee(100, 100, 0, 0, 7, MAX), Ok(7));
+        assert_eq!(FeeManager::compute_fee(0, 100, 0, 0, 7, MAX), Ok(7));
+
+        assert_eq!(FeeManager::compute_fee(-1, 25, 0, 0, 0, MAX), Err(FeeError::InvalidAmount));
+        assert_eq!(FeeManager::compute_fee(10, 25, 0, 10_001, 0, MAX), Err(FeeError::InvalidAmount));
+        assert_eq!(FeeManager::compute_fee(10, 25, 0, 0, 5, 4), Err(FeeError::InvalidAmount));
+        assert_eq!(FeeManager::compute_fee(MAX, 2, 0, 0, 0, MAX), Err(FeeError::ArithmeticOverflow));
+    }
 }
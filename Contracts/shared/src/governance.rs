@@ -122,6 +122,7 @@ impl GovernanceManager {
     }
 
     /// Create a new upgrade proposal
+    #[allow(clippy::too_many_arguments)]
     pub fn propose_upgrade(
         env: &Env,
         proposer: Address,
@@ -137,11 +138,11 @@ impl GovernanceManager {
         Self::require_role(env, &proposer, GovernanceRole::Admin);
 
         // Validate threshold
-        if approval_threshold == 0 || approval_threshold > approvers.len() as u32 {
+        if approval_threshold == 0 || approval_threshold > approvers.len() {
             return Err(GovernanceError::InvalidThreshold);
         }
 
-        if timelock_delay < MIN_TIMELOCK_SECONDS || timelock_delay > MAX_TIMELOCK_SECONDS {
+        if !(MIN_TIMELOCK_SECONDS..=MAX_TIMELOCK_SECONDS).contains(&timelock_delay) {
             return Err(GovernanceError::InvalidTimelock);
         }
 
@@ -15,6 +15,7 @@ pub mod fees;
 pub mod governance;
 pub mod oracle;
 pub mod privacy;
+pub mod safe_call;
 pub mod storage;
 
 /// Standard contract error codes
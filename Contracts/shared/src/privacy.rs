@@ -344,7 +344,7 @@ impl RangeProof {
     /// Verify that a value is within valid range without revealing it
     /// Returns true if 0 <= value < 2^64
     pub fn verify_range(value: i128) -> bool {
-        value >= 0 && value < (1i128 << 64)
+        (0..(1i128 << 64)).contains(&value)
     }
 
     /// Create a commitment with range proof
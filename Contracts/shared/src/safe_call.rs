@@ -1,8 +1,14 @@
-use soroban_sdk::{Address, Env, Symbol, Val, Vec, Error};
+use soroban_sdk::xdr::{ScErrorCode, ScErrorType};
+use soroban_sdk::{Address, Env, Error, Symbol, TryFromVal, Val, Vec};
 
 pub mod errors {
     pub const CALL_FAILED: u32 = 2001;
     pub const CONTRACT_NOT_FOUND: u32 = 2002;
+    pub const REENTRANT: u32 = 2003;
+    pub const CALLEE_CONTRACT_ERROR: u32 = 2004;
+    pub const AUTH_FAILED: u32 = 2005;
+    pub const UNKNOWN_FAILURE: u32 = 2006;
+    pub const FUNCTION_NOT_FOUND: u32 = 2007;
 }
 
 /// Safely invokes a contract method with error handling checks.
@@ -26,22 +32,102 @@ pub fn safe_invoke(
     // or checking ledger entries, but try_call handles non-existence as an error.
 
     // 2. Try Call
-    // invoke_contract_try returns Result<Val, Error>
-    // We map generic errors to our specific codes if needed, or propagate.
-    
-    // Explicitly type the result to see what compiler thinks
-    let res: Result<Val, Error> = env.try_invoke_contract(contract, func, args);
+    // try_invoke_contract separates a callee failure (outer Err) from a return
+    // value that could not be converted to the requested type (inner Err).
+    // Callee failures are classified so callers can tell auth failures from
+    // business-rule errors.
+    let res = env.try_invoke_contract::<Val, Error>(contract, func, args);
 
     match res {
-        Ok(val) => Ok(val),
-        Err(e) => {
-            // Log the error for debugging
-            // env.events().publish((Symbol::new(env, "call_failed"),), e);
-            
-            // In a real module we might inspect `e` to see if it's a missing contract vs logic error.
-            // For now, we wrap it.
-            Err(errors::CALL_FAILED)
-        }
+        Ok(Ok(val)) => Ok(val),
+        Ok(Err(_)) => Err(errors::CALL_FAILED),
+        Err(Ok(e)) => Err(classify_error(&e)),
+        Err(Err(_)) => Err(errors::UNKNOWN_FAILURE),
+    }
+}
+
+/// Safely invokes a contract method and decodes its return value as `T`.
+///
+/// Use this instead of `safe_invoke` when the caller needs the callee's
+/// result. A return value that does not decode as `T` is reported as
+/// `CALL_FAILED`; callee failures are classified as in `safe_invoke`.
+///
+/// # Arguments
+/// * `env` - The environment
+/// * `contract` - The address of the contract to call
+/// * `func` - The function name to call
+/// * `args` - The arguments to pass
+///
+/// # Returns
+/// * `Result<T, u32>` - The decoded return value or an error code
+pub fn safe_invoke_with_result<T>(
+    env: &Env,
+    contract: &Address,
+    func: &Symbol,
+    args: Vec<Val>,
+) -> Result<T, u32>
+where
+    T: TryFromVal<Env, Val>,
+{
+    safe_invoke_detailed(env, contract, func, args).map_err(|(code, _)| code)
+}
+
+/// Like `safe_invoke_with_result`, but also returns the raw error behind a
+/// failure alongside its classified code.
+///
+/// The raw error can reveal a callee's internals, so callers should only
+/// surface it where that is acceptable, e.g. behind a debugging switch. A
+/// return value that does not decode as `T` is reported with a
+/// `Value`/`UnexpectedType` error.
+///
+/// # Returns
+/// * `Result<T, (u32, Error)>` - The decoded return value, or the error code and raw error
+pub fn safe_invoke_detailed<T>(
+    env: &Env,
+    contract: &Address,
+    func: &Symbol,
+    args: Vec<Val>,
+) -> Result<T, (u32, Error)>
+where
+    T: TryFromVal<Env, Val>,
+{
+    match env.try_invoke_contract::<T, Error>(contract, func, args) {
+        Ok(Ok(val)) => Ok(val),
+        Ok(Err(_)) => Err((
+            errors::CALL_FAILED,
+            Error::from_type_and_code(ScErrorType::Value, ScErrorCode::UnexpectedType),
+        )),
+        Err(Ok(e)) => Err((classify_error(&e), e)),
+        Err(Err(_)) => Err((
+            errors::UNKNOWN_FAILURE,
+            Error::from_type_and_code(ScErrorType::Context, ScErrorCode::InternalError),
+        )),
+    }
+}
+
+/// Maps a failed invocation's error onto a `SafeCallErrors` code.
+///
+/// * Errors returned by the callee's own error enum -> `CALLEE_CONTRACT_ERROR`
+/// * Missing authorization -> `AUTH_FAILED`
+/// * Calls to a contract with no deployed code -> `CONTRACT_NOT_FOUND`
+/// * Calls to a function the contract does not export -> `FUNCTION_NOT_FOUND`
+/// * Any other host error, including traps such as a panic or an arithmetic
+///   overflow in the callee -> `UNKNOWN_FAILURE`
+///
+/// The host narrows every non-contract error a callee returns through
+/// `try_invoke_contract` down to `Context`/`InvalidAction`, so in practice
+/// those failures all classify as `UNKNOWN_FAILURE`.
+pub fn classify_error(error: &Error) -> u32 {
+    if error.is_type(ScErrorType::Contract) {
+        errors::CALLEE_CONTRACT_ERROR
+    } else if error.is_type(ScErrorType::Auth) {
+        errors::AUTH_FAILED
+    } else if *error == Error::from_type_and_code(ScErrorType::Storage, ScErrorCode::MissingValue) {
+        errors::CONTRACT_NOT_FOUND
+    } else if *error == Error::from_type_and_code(ScErrorType::WasmVm, ScErrorCode::MissingValue) {
+        errors::FUNCTION_NOT_FOUND
+    } else {
+        errors::UNKNOWN_FAILURE
     }
 }
 
@@ -51,3 +137,38 @@ pub fn verify_target(_env: &Env, _contract: &Address) -> bool {
     // We could check if it's a contract address vs account address if needed.
     true
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_error() {
+        assert_eq!(classify_error(&Error::from_contract_error(1)), errors::CALLEE_CONTRACT_ERROR);
+        assert_eq!(
+            classify_error(&Error::from_type_and_code(ScErrorType::Auth, ScErrorCode::InvalidAction)),
+            errors::AUTH_FAILED
+        );
+        assert_eq!(
+            classify_error(&Error::from_type_and_code(ScErrorType::Storage, ScErrorCode::MissingValue)),
+            errors::CONTRACT_NOT_FOUND
+        );
+        assert_eq!(
+            classify_error(&Error::from_type_and_code(ScErrorType::Object, ScErrorCode::ArithDomain)),
+            errors::UNKNOWN_FAILURE
+        );
+        assert_eq!(
+            classify_error(&Error::from_type_and_code(ScErrorType::WasmVm, ScErrorCode::MissingValue)),
+            errors::FUNCTION_NOT_FOUND
+        );
+        // Traps and the host's narrowed callee error are not a missing function
+        assert_eq!(
+            classify_error(&Error::from_type_and_code(ScErrorType::WasmVm, ScErrorCode::InvalidAction)),
+            errors::UNKNOWN_FAILURE
+        );
+        assert_eq!(
+            classify_error(&Error::from_type_and_code(ScErrorType::Context, ScErrorCode::InvalidAction)),
+            errors::UNKNOWN_FAILURE
+        );
+    }
+}
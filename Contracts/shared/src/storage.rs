@@ -290,8 +290,7 @@ impl StorageCostEstimator {
     /// Estimate cost savings from key size optimization
     pub fn key_optimization_savings(original_key_size: u64, optimized_key_size: u64) -> i128 {
         // Smaller keys reduce storage costs
-        let savings_per_access = (original_key_size - optimized_key_size) as i128 * 10;
-        savings_per_access
+        (original_key_size - optimized_key_size) as i128 * 10
     }
 }
 